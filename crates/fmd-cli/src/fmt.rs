@@ -0,0 +1,258 @@
+// `fmd fmt`: canonical Markdown formatting
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use fmd_core::{parse, Document, Node, ProcessorOptions};
+use fmd_markdown::{equivalent, to_markdown, MarkdownOptions};
+
+/// Config file picked up from the working directory when `--config` is not given
+pub const CONFIG_FILE: &str = ".fmdfmt.json";
+
+/// Load formatter options from `path`, or from `.fmdfmt.json` if it exists
+pub fn load_options(path: Option<&Path>) -> Result<MarkdownOptions> {
+    let path = match path {
+        Some(p) => p.to_path_buf(),
+        None if Path::new(CONFIG_FILE).exists() => PathBuf::from(CONFIG_FILE),
+        None => return Ok(MarkdownOptions::default()),
+    };
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config: {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid config: {}", path.display()))
+}
+
+fn parse_source(content: &str) -> Result<Node> {
+    let options = ProcessorOptions {
+        gfm: true,
+        frontmatter: true,
+        ..Default::default()
+    };
+    let result = parse(&Document::new(content), options);
+    if !result.errors.is_empty() {
+        anyhow::bail!("Parse errors: {:?}", result.errors);
+    }
+    Ok(result.ast)
+}
+
+/// Format `content`, refusing if the result would parse to a different document
+pub fn format(content: &str, options: &MarkdownOptions) -> Result<String> {
+    let ast = parse_source(content)?;
+    let formatted = to_markdown(&ast, options);
+
+    if !equivalent(&ast, &parse_source(&formatted)?) {
+        anyhow::bail!("Formatting would change the document; leaving it untouched");
+    }
+    Ok(formatted)
+}
+
+/// Line diff of `old` against `new` in unified style (without hunk headers)
+pub fn diff(old: &str, new: &str, name: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let mut edits = Vec::with_capacity(a.len().max(b.len()));
+    diff_lines(&a, &b, &mut edits);
+
+    let mut out = format!("--- {}\n+++ {} (formatted)\n", name, name);
+    for (marker, line) in edits {
+        out.push(marker);
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Myers' diff in linear space: split at the middle snake and recurse
+fn diff_lines<'a>(a: &[&'a str], b: &[&'a str], out: &mut Vec<(char, &'a str)>) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a_rest, b_rest) = (&a[prefix..], &b[prefix..]);
+    let suffix = a_rest
+        .iter()
+        .rev()
+        .zip(b_rest.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (middle_a, middle_b) = (
+        &a_rest[..a_rest.len() - suffix],
+        &b_rest[..b_rest.len() - suffix],
+    );
+
+    out.extend(a[..prefix].iter().map(|line| (' ', *line)));
+    if middle_a.is_empty() || middle_b.is_empty() {
+        out.extend(middle_a.iter().map(|line| ('-', *line)));
+        out.extend(middle_b.iter().map(|line| ('+', *line)));
+    } else {
+        match middle_snake(middle_a, middle_b) {
+            Some((x, y)) => {
+                diff_lines(&middle_a[..x], &middle_b[..y], out);
+                diff_lines(&middle_a[x..], &middle_b[y..], out);
+            }
+            None => {
+                out.extend(middle_a.iter().map(|line| ('-', *line)));
+                out.extend(middle_b.iter().map(|line| ('+', *line)));
+            }
+        }
+    }
+    out.extend(
+        a_rest[a_rest.len() - suffix..]
+            .iter()
+            .map(|line| (' ', *line)),
+    );
+}
+
+/// Where the forward and backward searches for a shortest edit path meet
+fn middle_snake(a: &[&str], b: &[&str]) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d;
+    let width = 2 * max_d + 2;
+    let mut forward = vec![-1isize; width as usize];
+    let mut backward = vec![-1isize; width as usize];
+    forward[(offset + 1) as usize] = 0;
+    backward[(offset + 1) as usize] = 0;
+    let delta = n - m;
+    // With an odd delta the paths meet on a forward step, else on a backward one
+    let odd = delta % 2 != 0;
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+    for d in 0..max_d {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let i = (offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && forward[i - 1] < forward[i + 1]) {
+                forward[i + 1]
+            } else {
+                forward[i - 1] + 1
+            };
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && a[x1 as usize] == b[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            forward[i] = x1;
+            if x1 > n {
+                k1_end += 2;
+            } else if y1 > m {
+                k1_start += 2;
+            } else if odd {
+                let j = offset + delta - k1;
+                if (0..width).contains(&j)
+                    && backward[j as usize] != -1
+                    && x1 >= n - backward[j as usize]
+                {
+                    return Some((x1 as usize, y1 as usize));
+                }
+            }
+            k1 += 2;
+        }
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let i = (offset + k2) as usize;
+            let mut x2 = if k2 == -d || (k2 != d && backward[i - 1] < backward[i + 1]) {
+                backward[i + 1]
+            } else {
+                backward[i - 1] + 1
+            };
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && a[(n - x2 - 1) as usize] == b[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            backward[i] = x2;
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !odd {
+                let j = offset + delta - k2;
+                if (0..width).contains(&j) && forward[j as usize] != -1 {
+                    let x1 = forward[j as usize];
+                    let y1 = offset + x1 - j;
+                    if x1 >= n - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k2 += 2;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_is_canonical_and_idempotent() {
+        let input = "Title\n=====\n\n* a\n* b\n\n|x|y|\n|-|-|\n|long cell|z|\n";
+        let formatted = format(input, &MarkdownOptions::default()).unwrap();
+        assert_eq!(
+            formatted,
+            "# Title\n\n- a\n- b\n\n| x         | y   |\n| --------- | --- |\n| long cell | z   |\n"
+        );
+        assert_eq!(
+            format(&formatted, &MarkdownOptions::default()).unwrap(),
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff("a\nb\nc\n", "a\nB\nc\n", "doc.md"),
+            "--- doc.md\n+++ doc.md (formatted)\n a\n-b\n+B\n c\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_is_minimal() {
+        fn lcs(a: &[&str], b: &[&str]) -> usize {
+            let mut row = vec![0; b.len() + 1];
+            for x in a {
+                let mut diagonal = 0;
+                for (j, y) in b.iter().enumerate() {
+                    let above = row[j + 1];
+                    row[j + 1] = if x == y {
+                        diagonal + 1
+                    } else {
+                        above.max(row[j])
+                    };
+                    diagonal = above;
+                }
+            }
+            row[b.len()]
+        }
+
+        let cases = [
+            ("a b c a b b a", "c b a b a c"),
+            ("x y z", "p q"),
+            ("a a a b", "b a a a"),
+            ("1 2 3 4 5 6 7 8", "1 3 4 9 6 7 2 8"),
+        ];
+        for (old, new) in cases {
+            let a: Vec<&str> = old.split(' ').collect();
+            let b: Vec<&str> = new.split(' ').collect();
+            let mut edits = Vec::new();
+            diff_lines(&a, &b, &mut edits);
+            let side = |skip: char| -> Vec<&str> {
+                edits
+                    .iter()
+                    .filter(|(m, _)| *m != skip)
+                    .map(|(_, l)| *l)
+                    .collect()
+            };
+            assert_eq!(side('+'), a);
+            assert_eq!(side('-'), b);
+            let kept = edits.iter().filter(|(m, _)| *m == ' ').count();
+            assert_eq!(kept, lcs(&a, &b), "{} -> {}", old, new);
+        }
+
+        // Large inputs stay linear in memory
+        let old: String = (0..20_000).map(|i| format!("line {}\n", i)).collect();
+        let new = old.replace("line 10000\n", "changed\n");
+        let out = diff(&old, &new, "big.md");
+        assert!(out.contains("-line 10000\n+changed\n"));
+    }
+}
@@ -0,0 +1,131 @@
+// `fmd lint`: report output formats
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use fmd_core::lint::{Diagnostic, LintOptions, Linter};
+use fmd_core::Severity;
+
+/// Config file picked up from the working directory when `--config` is not given
+pub const CONFIG_FILE: &str = ".fmdlint.json";
+
+/// Load lint options from `path`, or from `.fmdlint.json` if it exists
+pub fn load_options(path: Option<&Path>) -> Result<LintOptions> {
+    let path = match path {
+        Some(p) => p.to_path_buf(),
+        None if Path::new(CONFIG_FILE).exists() => PathBuf::from(CONFIG_FILE),
+        None => return Ok(LintOptions::default()),
+    };
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config: {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid config: {}", path.display()))
+}
+
+/// `path:line:column  severity  message  rule-id` lines
+pub fn human(results: &[(String, Vec<Diagnostic>)]) -> String {
+    let mut out = String::new();
+    let mut total = 0;
+    for (path, diagnostics) in results {
+        for d in diagnostics {
+            out.push_str(&format!("{}:{}\n", path, d));
+        }
+        total += diagnostics.len();
+    }
+    if total > 0 {
+        out.push_str(&format!("\n{} problem(s)\n", total));
+    }
+    out
+}
+
+/// `[{ "path": …, "diagnostics": [...] }]`
+pub fn json(results: &[(String, Vec<Diagnostic>)]) -> Result<String> {
+    let files: Vec<Value> = results
+        .iter()
+        .map(|(path, diagnostics)| json!({ "path": path, "diagnostics": diagnostics }))
+        .collect();
+    serde_json::to_string_pretty(&files).context("Failed to serialize diagnostics")
+}
+
+/// SARIF 2.1.0 log with one run
+pub fn sarif(linter: &Linter, results: &[(String, Vec<Diagnostic>)]) -> Result<String> {
+    let rules: Vec<Value> = linter
+        .rules()
+        .map(|rule| {
+            json!({
+                "id": rule.id(),
+                "shortDescription": { "text": rule.description() },
+            })
+        })
+        .collect();
+
+    let sarif_results: Vec<Value> = results
+        .iter()
+        .flat_map(|(path, diagnostics)| {
+            diagnostics.iter().map(move |d| {
+                let level = match d.severity {
+                    Severity::Info => "note",
+                    Severity::Warning => "warning",
+                    Severity::Error => "error",
+                };
+                json!({
+                    "ruleId": d.rule_id,
+                    "level": level,
+                    "message": { "text": d.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": path },
+                            "region": {
+                                "startLine": d.position.start.line,
+                                "startColumn": d.position.start.column,
+                                "endLine": d.position.end.line,
+                                "endColumn": d.position.end.column,
+                            },
+                        },
+                    }],
+                })
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "fmd",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": sarif_results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).context("Failed to serialize SARIF")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_formats() {
+        let linter = Linter::new(LintOptions::default());
+        let results = vec![("doc.md".to_string(), linter.check("# A\n\n### B\n"))];
+
+        assert_eq!(
+            human(&results),
+            "doc.md:3:1: warning: Expected heading level 2 but found 3 [heading-increment]\n\n1 problem(s)\n"
+        );
+
+        let sarif: Value = serde_json::from_str(&sarif(&linter, &results).unwrap()).unwrap();
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "heading-increment");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            3
+        );
+    }
+}
@@ -4,10 +4,27 @@ use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use std::fs;
 use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use fmd_core::{parse, Document, ProcessorOptions};
-use fmd_html::{render_html, HtmlOptions};
+mod fmt;
+mod lint;
+
+use fmd_core::a11y::{self, A11yIssue};
+use fmd_core::lint::{apply_fixes, Linter};
+use fmd_core::transforms::HeadingIds;
+use fmd_core::{
+    parse, Document, DocumentInfo, Node, ProcessorOptions, Selector, Severity, TocOptions, VFile,
+};
+use fmd_html::code::BUILTIN_RENDERERS;
+use fmd_html::highlight::{HighlightStyle, SyntectHighlighter, THEMES};
+use fmd_html::sanitize::SanitizeOptions;
+use fmd_html::{
+    render_html, AnchorOptions, AnchorPlacement, CodeRenderers, HeadingIdOptions, HtmlOptions,
+};
+use fmd_html::{ExternalLinkOptions, ImageOptions, LinkOptions, MarkdownLinks};
+use fmd_html::{Layout, Quote, RawHtml, SafeOptions, SlugOptions, SlugStrategy, TableAlign};
+use fmd_markdown::{MarkdownOptions, ProseWrap};
 
 #[derive(Parser)]
 #[command(name = "fmd")]
@@ -36,6 +53,114 @@ struct Cli {
     #[arg(long)]
     allow_dangerous_html: bool,
 
+    /// Write XHTML void elements (`<br />`) and unminimized boolean attributes
+    #[arg(long)]
+    xhtml: bool,
+
+    /// Quote character around HTML attribute values
+    #[arg(long, default_value = "double", value_parser = ["double", "single"])]
+    quote: String,
+
+    /// Escape `'` as `&#39;` in all HTML text and attributes
+    #[arg(long)]
+    escape_apostrophe: bool,
+
+    /// Whitespace between HTML blocks: newlines (as parsed), compact (minified) or pretty (indented)
+    #[arg(long, default_value = "newlines", value_parser = ["newlines", "compact", "pretty"])]
+    layout: String,
+
+    /// Table column alignment in HTML output: attribute (`align=`) or style (`text-align`)
+    #[arg(long, default_value = "attribute", value_parser = ["attribute", "style"])]
+    table_align: String,
+
+    /// Add `loading="lazy"` and `decoding="async"` to images in HTML output
+    #[arg(long)]
+    lazy_images: bool,
+
+    /// Read image `width`/`height` from files under this directory
+    #[arg(long)]
+    image_root: Option<PathBuf>,
+
+    /// Render a paragraph holding only a titled image as `<figure>` with a caption
+    #[arg(long)]
+    image_figures: bool,
+
+    /// Resolve relative link and image URLs against this URL in HTML output
+    #[arg(long)]
+    base_url: Option<String>,
+
+    /// Rewrite relative `.md` links: keep, html (`a.html`) or clean (`a`)
+    #[arg(long, default_value = "keep", value_parser = ["keep", "html", "clean"])]
+    md_links: String,
+
+    /// Open links to other hosts in a new tab, with `rel="noopener noreferrer"` and class `external`
+    #[arg(long)]
+    external_links: bool,
+
+    /// Check URLs and escape raw HTML while rendering, instead of sanitizing the output
+    #[arg(long)]
+    safe: bool,
+
+    /// Raw HTML in --safe mode: escape (show as text) or drop
+    #[arg(long, default_value = "escape", value_parser = ["escape", "drop"])]
+    raw_html: String,
+
+    /// Sanitizer policy: strict, default, permissive, or a JSON policy file
+    #[arg(long, default_value = "default")]
+    sanitize_policy: String,
+
+    /// Replace `[[toc]]` / `<!-- toc -->` markers with a table of contents in HTML output
+    #[arg(long)]
+    toc: bool,
+
+    /// Render ids on headings in HTML output
+    #[arg(long)]
+    heading_ids: bool,
+
+    /// Prefix for rendered heading ids, e.g. `user-content-`
+    #[arg(long, default_value = "")]
+    heading_id_prefix: String,
+
+    /// Add a self-link anchor to headings (implies --heading-ids)
+    #[arg(long, value_parser = ["prepend", "append", "wrap"])]
+    heading_anchor: Option<String>,
+
+    /// Parse `# Title {#id .class}` heading attributes
+    #[arg(long)]
+    heading_attributes: bool,
+
+    /// Syntax-highlight fenced code in HTML output
+    #[arg(long)]
+    highlight: bool,
+
+    /// Highlight theme (github, InspiredGitHub, Solarized (dark), base16-ocean.dark, ...)
+    #[arg(long, default_value = "github")]
+    highlight_theme: String,
+
+    /// Emit `hl-*` classes instead of inline styles (see `fmd highlight-css`)
+    #[arg(long)]
+    highlight_classes: bool,
+
+    /// Render these code fence languages specially (comma-separated: mermaid, math, csv, svg)
+    #[arg(long, value_delimiter = ',')]
+    code_renderers: Vec<String>,
+
+    /// Heading id strategy: github, ascii (transliterated) or unicode
+    #[arg(long, default_value = "github", value_parser = ["github", "ascii", "unicode"])]
+    slug: String,
+
+    /// Maximum heading id length
+    #[arg(long)]
+    slug_max_length: Option<usize>,
+
+    /// Separator between words in heading ids
+    #[arg(long, default_value = "-")]
+    slug_separator: String,
+
+    /// Comma-separated words to leave out of heading ids
+    #[arg(long, value_delimiter = ',')]
+    slug_stop_words: Vec<String>,
+
     /// Output format (html, ast, or events)
     #[arg(short, long, default_value = "html")]
     format: String,
@@ -53,6 +178,54 @@ enum Commands {
         /// Input file
         input: Option<PathBuf>,
     },
+    /// Print nodes matching a CSS-like selector as JSON (`{ path, node }`)
+    Select {
+        /// Selector, e.g. "heading[depth=2] > text"
+        selector: String,
+        /// Input file
+        input: Option<PathBuf>,
+        /// Only print the first match
+        #[arg(long)]
+        first: bool,
+    },
+    /// Rewrite Markdown in a canonical style (GFM and frontmatter are always enabled)
+    Fmt(FmtArgs),
+    /// Check Markdown files against lint rules (GFM and frontmatter are always enabled)
+    Lint {
+        /// Files to lint (stdin if none)
+        files: Vec<PathBuf>,
+        /// Report format: human, json or sarif
+        #[arg(long, default_value = "human")]
+        format: String,
+        /// Apply autofixes in place
+        #[arg(long)]
+        fix: bool,
+        /// JSON file with lint options (defaults to `.fmdlint.json` if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Report accessibility problems (missing alt text, vague link text, skipped headings, …)
+    A11y {
+        /// Input file
+        input: Option<PathBuf>,
+        /// Print issues as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print document statistics: outline, links, images, word count and reading time
+    Stats {
+        /// Input file
+        input: Option<PathBuf>,
+        /// Print the full `DocumentInfo` as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the stylesheet for `--highlight-classes` output
+    HighlightCss {
+        /// Theme name
+        #[arg(default_value = "github")]
+        theme: String,
+    },
     /// Benchmark parsing performance
     Bench {
         /// Input file
@@ -63,20 +236,134 @@ enum Commands {
     },
 }
 
+#[derive(clap::Args)]
+struct FmtArgs {
+    /// Files to format in place (stdin to stdout if none)
+    files: Vec<PathBuf>,
+    /// Exit non-zero and print a diff if any file is not formatted
+    #[arg(long)]
+    check: bool,
+    /// Print formatted output instead of writing files
+    #[arg(long)]
+    stdout: bool,
+    /// JSON file with formatter options (defaults to `.fmdfmt.json` if present)
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Unordered list marker: -, * or +
+    #[arg(long)]
+    bullet: Option<char>,
+    /// Emphasis and strong marker: * or _
+    #[arg(long)]
+    emphasis: Option<char>,
+    /// Code fence character: ` or ~
+    #[arg(long)]
+    fence: Option<char>,
+    /// Use setext underlines for level 1 and 2 headings
+    #[arg(long)]
+    setext: bool,
+    /// Paragraph line breaking: preserve, always or never
+    #[arg(long)]
+    prose_wrap: Option<String>,
+    /// Line width for `--prose-wrap always`
+    #[arg(long)]
+    line_width: Option<usize>,
+    /// Do not pad table cells
+    #[arg(long)]
+    no_align_tables: bool,
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match &cli.command {
         Some(Commands::Html { input }) => {
             let content = read_input(input.as_ref())?;
-            let html = process_to_html(&content, build_options(&cli))?;
+            let html = process_to_html(&content, build_options(&cli), build_html_options(&cli)?)?;
             write_output(cli.output.as_ref(), &html)?;
         }
         Some(Commands::Ast { input }) => {
             let content = read_input(input.as_ref())?;
-            let ast = process_to_ast(&content, build_options(&cli))?;
+            let ast = process_to_ast(&content, build_options(&cli), &build_slug_options(&cli))?;
             write_output(cli.output.as_ref(), &ast)?;
         }
+        Some(Commands::Select {
+            selector,
+            input,
+            first,
+        }) => {
+            let content = read_input(input.as_ref())?;
+            let output = process_to_selection(
+                &content,
+                selector,
+                *first,
+                build_options(&cli),
+                &build_slug_options(&cli),
+            )?;
+            write_output(cli.output.as_ref(), &output)?;
+        }
+        Some(Commands::Fmt(args)) => {
+            if !format_files(args)? {
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Lint {
+            files,
+            format,
+            fix,
+            config,
+        }) => {
+            if !lint_files(files, format, *fix, config.as_deref())? {
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::A11y { input, json }) => {
+            let content = read_input(input.as_ref())?;
+            let issues =
+                audit_accessibility(&content, build_options(&cli), &build_slug_options(&cli))?;
+            let report = if *json {
+                serde_json::to_string_pretty(&issues).context("Failed to serialize issues")?
+            } else {
+                let name = input
+                    .as_ref()
+                    .map_or("<stdin>".to_string(), |p| p.display().to_string());
+                issues
+                    .iter()
+                    .map(|issue| {
+                        let (line, column) = issue
+                            .position
+                            .as_ref()
+                            .map_or((0, 0), |p| (p.start.line, p.start.column));
+                        format!(
+                            "{}:{}:{}: {} [{}, WCAG {} {}]\n",
+                            name,
+                            line,
+                            column,
+                            issue.message,
+                            issue.rule_id,
+                            issue.wcag.criterion,
+                            issue.wcag.title
+                        )
+                    })
+                    .collect()
+            };
+            write_output(cli.output.as_ref(), &report)?;
+            if !issues.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Stats { input, json }) => {
+            let content = read_input(input.as_ref())?;
+            let info = analyze_document(&content, build_options(&cli), &build_slug_options(&cli))?;
+            let report = if *json {
+                serde_json::to_string_pretty(&info).context("Failed to serialize stats")?
+            } else {
+                format_stats(&info)
+            };
+            write_output(cli.output.as_ref(), &report)?;
+        }
+        Some(Commands::HighlightCss { theme }) => {
+            write_output(cli.output.as_ref(), &highlighter(theme, true)?.theme_css())?;
+        }
         Some(Commands::Bench { input, iterations }) => {
             let content = fs::read_to_string(input)
                 .with_context(|| format!("Failed to read file: {}", input.display()))?;
@@ -86,8 +373,10 @@ fn main() -> Result<()> {
             // Default: process input to specified format
             let content = read_input(cli.input.as_ref())?;
             let output = match cli.format.as_str() {
-                "html" => process_to_html(&content, build_options(&cli))?,
-                "ast" => process_to_ast(&content, build_options(&cli))?,
+                "html" => {
+                    process_to_html(&content, build_options(&cli), build_html_options(&cli)?)?
+                }
+                "ast" => process_to_ast(&content, build_options(&cli), &build_slug_options(&cli))?,
                 _ => anyhow::bail!("Unknown format: {}", cli.format),
             };
             write_output(cli.output.as_ref(), &output)?;
@@ -131,38 +420,369 @@ fn build_options(cli: &Cli) -> ProcessorOptions {
         gfm: cli.gfm,
         frontmatter: cli.frontmatter,
         allow_dangerous_html: cli.allow_dangerous_html,
+        heading_attributes: cli.heading_attributes,
         position: false,
         incremental: false,
         ..Default::default()
     }
 }
 
-fn process_to_html(content: &str, options: ProcessorOptions) -> Result<String> {
-    let doc = Document::new(content);
-    let parse_result = parse(&doc, options);
+fn build_slug_options(cli: &Cli) -> SlugOptions {
+    SlugOptions {
+        strategy: match cli.slug.as_str() {
+            "ascii" => SlugStrategy::Ascii,
+            "unicode" => SlugStrategy::Unicode,
+            _ => SlugStrategy::Github,
+        },
+        max_length: cli.slug_max_length,
+        separator: cli.slug_separator.clone(),
+        stop_words: cli.slug_stop_words.clone(),
+    }
+}
 
-    if !parse_result.success {
-        anyhow::bail!("Parse errors: {:?}", parse_result.errors);
+/// Parse and run the built-in transforms, reporting warnings on stderr
+fn process(content: &str, options: ProcessorOptions, slug: &SlugOptions) -> Result<Node> {
+    let mut file = VFile::new(content);
+    let mut processor = fmd_gfm::processor(options);
+    processor.push(Box::new(HeadingIds::with_slugger(slug.slugger())));
+    let ast = processor.process(&mut file);
+
+    for message in &file.messages {
+        if message.severity == Severity::Warning {
+            eprintln!("{}", message);
+        }
+    }
+
+    if file.has_errors() {
+        let errors: Vec<_> = file
+            .messages
+            .iter()
+            .filter(|m| m.severity == Severity::Error)
+            .map(|m| m.reason.as_str())
+            .collect();
+        anyhow::bail!("Parse errors: {:?}", errors);
+    }
+
+    Ok(ast)
+}
+
+fn build_html_options(cli: &Cli) -> Result<HtmlOptions> {
+    let highlight = if cli.highlight {
+        Some(Arc::new(highlighter(&cli.highlight_theme, cli.highlight_classes)?) as _)
+    } else {
+        None
+    };
+    let mut code_renderers = CodeRenderers::new();
+    for lang in &cli.code_renderers {
+        if !code_renderers.register_builtin(lang) {
+            anyhow::bail!(
+                "Unknown code renderer `{}` (available: {})",
+                lang,
+                BUILTIN_RENDERERS.join(", ")
+            );
+        }
     }
+    Ok(HtmlOptions {
+        sanitize: !cli.allow_dangerous_html && !cli.safe,
+        xhtml: cli.xhtml,
+        quote: match cli.quote.as_str() {
+            "single" => Quote::Single,
+            _ => Quote::Double,
+        },
+        escape_apostrophe: cli.escape_apostrophe,
+        layout: match cli.layout.as_str() {
+            "compact" => Layout::Compact,
+            "pretty" => Layout::Pretty,
+            _ => Layout::Newlines,
+        },
+        table_align: match cli.table_align.as_str() {
+            "style" => TableAlign::Style,
+            _ => TableAlign::Attribute,
+        },
+        images: (cli.lazy_images || cli.image_root.is_some() || cli.image_figures).then(|| {
+            ImageOptions {
+                lazy: cli.lazy_images,
+                decode_async: cli.lazy_images,
+                root: cli.image_root.clone(),
+                figures: cli.image_figures,
+            }
+        }),
+        links: build_link_options(cli),
+        safe: cli.safe.then(|| SafeOptions {
+            raw_html: match cli.raw_html.as_str() {
+                "drop" => RawHtml::Drop,
+                _ => RawHtml::Escape,
+            },
+            ..Default::default()
+        }),
+        sanitize_options: sanitize_policy(&cli.sanitize_policy)?,
+        allow_dangerous_html: cli.allow_dangerous_html,
+        toc: cli.toc.then(TocOptions::default),
+        slug: build_slug_options(cli),
+        heading_ids: (cli.heading_ids || cli.heading_anchor.is_some()).then(|| HeadingIdOptions {
+            prefix: cli.heading_id_prefix.clone(),
+            anchor: cli
+                .heading_anchor
+                .as_deref()
+                .map(|placement| AnchorOptions {
+                    placement: match placement {
+                        "append" => AnchorPlacement::Append,
+                        "wrap" => AnchorPlacement::Wrap,
+                        _ => AnchorPlacement::Prepend,
+                    },
+                    ..Default::default()
+                }),
+        }),
+        highlight,
+        code_renderers,
+        ..Default::default()
+    })
+}
 
-    let html_options = HtmlOptions {
-        sanitize: !options.allow_dangerous_html,
-        allow_dangerous_html: options.allow_dangerous_html,
+fn build_link_options(cli: &Cli) -> Option<LinkOptions> {
+    let markdown_links = match cli.md_links.as_str() {
+        "html" => MarkdownLinks::Html,
+        "clean" => MarkdownLinks::Clean,
+        _ => MarkdownLinks::Keep,
+    };
+    if cli.base_url.is_none() && markdown_links == MarkdownLinks::Keep && !cli.external_links {
+        return None;
+    }
+    Some(LinkOptions {
+        base_url: cli.base_url.clone(),
+        markdown_links,
+        external: cli.external_links.then(ExternalLinkOptions::default),
         ..Default::default()
+    })
+}
+
+fn sanitize_policy(policy: &str) -> Result<SanitizeOptions> {
+    if let Some(options) = SanitizeOptions::preset(policy) {
+        return Ok(options);
+    }
+    let json = fs::read_to_string(policy)
+        .with_context(|| format!("Failed to read sanitize policy: {}", policy))?;
+    serde_json::from_str(&json).with_context(|| format!("Invalid sanitize policy: {}", policy))
+}
+
+fn highlighter(theme: &str, classes: bool) -> Result<SyntectHighlighter> {
+    let style = if classes {
+        HighlightStyle::Classes
+    } else {
+        HighlightStyle::Inline
     };
+    SyntectHighlighter::new(theme, style).with_context(|| {
+        format!(
+            "Unknown highlight theme `{}` (available: {})",
+            theme,
+            THEMES.join(", ")
+        )
+    })
+}
 
-    Ok(render_html(&parse_result.ast, html_options))
+fn process_to_html(
+    content: &str,
+    options: ProcessorOptions,
+    html_options: HtmlOptions,
+) -> Result<String> {
+    let ast = process(content, options, &html_options.slug)?;
+    Ok(render_html(&ast, html_options))
 }
 
-fn process_to_ast(content: &str, options: ProcessorOptions) -> Result<String> {
-    let doc = Document::new(content);
-    let parse_result = parse(&doc, options);
+fn process_to_ast(content: &str, options: ProcessorOptions, slug: &SlugOptions) -> Result<String> {
+    let ast = process(content, options, slug)?;
 
-    if !parse_result.success {
-        anyhow::bail!("Parse errors: {:?}", parse_result.errors);
+    serde_json::to_string_pretty(&ast).context("Failed to serialize AST")
+}
+
+fn process_to_selection(
+    content: &str,
+    selector: &str,
+    first: bool,
+    options: ProcessorOptions,
+    slug: &SlugOptions,
+) -> Result<String> {
+    let selector = Selector::parse(selector)?;
+    let ast = process(content, options, slug)?;
+
+    let json = if first {
+        serde_json::to_string_pretty(&selector.select(&ast))
+    } else {
+        serde_json::to_string_pretty(&selector.select_all(&ast))
+    };
+    json.context("Failed to serialize matches")
+}
+
+/// Run `fmd fmt`; returns false if `--check` found unformatted files
+fn format_files(args: &FmtArgs) -> Result<bool> {
+    let mut options = fmt::load_options(args.config.as_deref())?;
+    if let Some(bullet) = args.bullet {
+        options.bullet = bullet;
+    }
+    if let Some(marker) = args.emphasis {
+        options.emphasis = marker;
+        options.strong = marker;
+    }
+    if let Some(fence) = args.fence {
+        options.fence = fence;
+    }
+    if args.setext {
+        options.setext = true;
+    }
+    if let Some(wrap) = &args.prose_wrap {
+        options.prose_wrap = match wrap.as_str() {
+            "preserve" => ProseWrap::Preserve,
+            "always" => ProseWrap::Always,
+            "never" => ProseWrap::Never,
+            _ => anyhow::bail!("Unknown prose wrap: {}", wrap),
+        };
+    }
+    if let Some(width) = args.line_width {
+        options.line_width = width;
+    }
+    if args.no_align_tables {
+        options.align_tables = false;
+    }
+
+    if args.files.is_empty() {
+        let content = read_input(None)?;
+        let formatted = fmt::format(&content, &options)?;
+        if args.check {
+            if formatted != content {
+                print!("{}", fmt::diff(&content, &formatted, "<stdin>"));
+                return Ok(false);
+            }
+            return Ok(true);
+        }
+        write_output(None, &formatted)?;
+        return Ok(true);
     }
 
-    serde_json::to_string_pretty(&parse_result.ast).context("Failed to serialize AST")
+    let mut clean = true;
+    for path in &args.files {
+        // A file that cannot be read or formatted is reported; the rest still run
+        match format_file(path, &options, args) {
+            Ok(file_clean) => clean &= file_clean,
+            Err(err) => {
+                eprintln!("{:#}", err);
+                clean = false;
+            }
+        }
+    }
+    Ok(clean)
+}
+
+/// Format or check one file; returns false if `--check` found a difference
+fn format_file(path: &Path, options: &MarkdownOptions, args: &FmtArgs) -> Result<bool> {
+    let content = read_input(Some(&path.to_path_buf()))?;
+    let formatted = fmt::format(&content, options)
+        .with_context(|| format!("Failed to format {}", path.display()))?;
+
+    if args.check {
+        if formatted != content {
+            print!(
+                "{}",
+                fmt::diff(&content, &formatted, &path.display().to_string())
+            );
+            return Ok(false);
+        }
+    } else if args.stdout {
+        write_output(None, &formatted)?;
+    } else if formatted != content {
+        write_output(Some(&path.to_path_buf()), &formatted)?;
+    }
+    Ok(true)
+}
+
+/// Run `fmd lint`; returns false if any warning or error remains
+fn lint_files(files: &[PathBuf], format: &str, fix: bool, config: Option<&Path>) -> Result<bool> {
+    let linter = Linter::new(lint::load_options(config)?);
+
+    let mut results = Vec::new();
+    if files.is_empty() {
+        let content = read_input(None)?;
+        results.push(("<stdin>".to_string(), linter.check(&content)));
+    }
+    for path in files {
+        let content = read_input(Some(path))?;
+        let mut diagnostics = linter.check(&content);
+        if fix && diagnostics.iter().any(|d| !d.fix.is_empty()) {
+            let fixed = apply_fixes(&content, &diagnostics);
+            write_output(Some(path), &fixed)?;
+            diagnostics = linter.check(&fixed);
+        }
+        results.push((path.display().to_string(), diagnostics));
+    }
+
+    let report = match format {
+        "human" => lint::human(&results),
+        "json" => lint::json(&results)?,
+        "sarif" => lint::sarif(&linter, &results)?,
+        _ => anyhow::bail!("Unknown lint format: {}", format),
+    };
+    write_output(None, &report)?;
+
+    Ok(results
+        .iter()
+        .flat_map(|(_, diagnostics)| diagnostics)
+        .all(|d| d.severity == Severity::Info))
+}
+
+fn audit_accessibility(
+    content: &str,
+    options: ProcessorOptions,
+    slug: &SlugOptions,
+) -> Result<Vec<A11yIssue>> {
+    let ast = process(
+        content,
+        ProcessorOptions {
+            position: true,
+            ..options
+        },
+        slug,
+    )?;
+    Ok(a11y::audit(&ast))
+}
+
+fn analyze_document(
+    content: &str,
+    options: ProcessorOptions,
+    slug: &SlugOptions,
+) -> Result<DocumentInfo> {
+    let ast = process(
+        content,
+        ProcessorOptions {
+            position: true,
+            ..options
+        },
+        slug,
+    )?;
+    Ok(fmd_core::analyze(&ast))
+}
+
+fn format_stats(info: &DocumentInfo) -> String {
+    let mut out = format!(
+        "Words: {}\nCharacters: {}\nReading time: {} min\n",
+        info.word_count, info.char_count, info.reading_time
+    );
+    out.push_str(&format!(
+        "Links: {} internal, {} external\nImages: {} internal, {} external\n",
+        info.links.internal.len(),
+        info.links.external.len(),
+        info.images.internal.len(),
+        info.images.external.len()
+    ));
+    if !info.code_languages.is_empty() {
+        out.push_str(&format!("Code: {}\n", info.code_languages.join(", ")));
+    }
+    if !info.headings.is_empty() {
+        out.push_str("Outline:\n");
+        for heading in &info.headings {
+            let indent = "  ".repeat(heading.depth.saturating_sub(1) as usize);
+            out.push_str(&format!("{}- {}\n", indent, heading.text));
+        }
+    }
+    out
 }
 
 fn benchmark(content: &str, iterations: usize) -> Result<()> {
@@ -0,0 +1,261 @@
+// Accessibility audit over the AST
+
+use crate::ast::{Node, NodeType};
+use crate::position::Position;
+use crate::visit::{visit, Control};
+use serde::{Deserialize, Serialize};
+
+/// WCAG 2.1 success criterion an issue violates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wcag {
+    /// Criterion number, e.g. `1.1.1`
+    pub criterion: String,
+    pub title: String,
+    /// Conformance level: `A`, `AA` or `AAA`
+    pub level: String,
+    pub url: String,
+}
+
+impl Wcag {
+    fn new(criterion: &str, title: &str, level: &str, slug: &str) -> Self {
+        Self {
+            criterion: criterion.to_string(),
+            title: title.to_string(),
+            level: level.to_string(),
+            url: format!("https://www.w3.org/WAI/WCAG21/Understanding/{}", slug),
+        }
+    }
+
+    fn non_text_content() -> Self {
+        Self::new("1.1.1", "Non-text Content", "A", "non-text-content")
+    }
+
+    fn info_and_relationships() -> Self {
+        Self::new(
+            "1.3.1",
+            "Info and Relationships",
+            "A",
+            "info-and-relationships",
+        )
+    }
+
+    fn link_purpose() -> Self {
+        Self::new(
+            "2.4.4",
+            "Link Purpose (In Context)",
+            "A",
+            "link-purpose-in-context",
+        )
+    }
+}
+
+/// An accessibility problem found in a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct A11yIssue {
+    /// `image-alt`, `image-alt-filename`, `link-text`, `link-bare-url`, `empty-link`,
+    /// `heading-order` or `table-header`
+    pub rule_id: String,
+    pub message: String,
+    /// Present when the AST was parsed with positions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    pub wcag: Wcag,
+}
+
+/// Link texts that say nothing about the target
+const VAGUE_LINK_TEXT: &[&str] = &[
+    "click here",
+    "here",
+    "click",
+    "link",
+    "this link",
+    "more",
+    "read more",
+    "this",
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &[
+    ".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp", ".avif", ".bmp",
+];
+
+/// Audit a document tree; parse with `position: true` to get locations
+pub fn audit(root: &Node) -> Vec<A11yIssue> {
+    let mut issues = Vec::new();
+    let mut previous_depth: Option<u8> = None;
+
+    let mut report = |rule_id: &str, message: String, node: &Node, wcag: Wcag| {
+        issues.push(A11yIssue {
+            rule_id: rule_id.to_string(),
+            message,
+            position: node.position.clone(),
+            wcag,
+        });
+    };
+
+    visit(root, |node, _| {
+        match node.node_type {
+            NodeType::Image | NodeType::ImageReference => {
+                let alt = image_alt(node);
+                if alt.trim().is_empty() {
+                    report(
+                        "image-alt",
+                        "Image has no alternative text".to_string(),
+                        node,
+                        Wcag::non_text_content(),
+                    );
+                } else if is_filename_like(&alt, node.url.as_deref()) {
+                    report(
+                        "image-alt-filename",
+                        format!("Image alternative text \"{}\" looks like a file name", alt),
+                        node,
+                        Wcag::non_text_content(),
+                    );
+                }
+            }
+            NodeType::Link | NodeType::LinkReference => {
+                let text = accessible_name(node);
+                let normalized = text.trim().trim_end_matches(['.', '!', ':']).to_lowercase();
+
+                if normalized.is_empty() {
+                    report(
+                        "empty-link",
+                        "Link has no text".to_string(),
+                        node,
+                        Wcag::link_purpose(),
+                    );
+                } else if VAGUE_LINK_TEXT.contains(&normalized.as_str()) {
+                    report(
+                        "link-text",
+                        format!("Link text \"{}\" does not describe its target", text.trim()),
+                        node,
+                        Wcag::link_purpose(),
+                    );
+                } else if is_url(&normalized) {
+                    report(
+                        "link-bare-url",
+                        format!("Link text is a bare URL: {}", text.trim()),
+                        node,
+                        Wcag::link_purpose(),
+                    );
+                }
+                return Control::Skip;
+            }
+            NodeType::Heading => {
+                let depth = node.depth.unwrap_or(1);
+                if let Some(previous) = previous_depth {
+                    if depth > previous + 1 {
+                        report(
+                            "heading-order",
+                            format!("Heading level {} skips level {}", depth, previous + 1),
+                            node,
+                            Wcag::info_and_relationships(),
+                        );
+                    }
+                }
+                previous_depth = Some(depth);
+            }
+            NodeType::Table => {
+                let has_header = node.children.first().is_some_and(|row| {
+                    row.children
+                        .iter()
+                        .any(|cell| !cell.text_content().trim().is_empty())
+                });
+                if !has_header {
+                    report(
+                        "table-header",
+                        "Table has no header row".to_string(),
+                        node,
+                        Wcag::info_and_relationships(),
+                    );
+                }
+            }
+            _ => {}
+        }
+        Control::Continue
+    });
+
+    issues
+}
+
+fn image_alt(node: &Node) -> String {
+    node.alt.clone().unwrap_or_else(|| node.text_content())
+}
+
+/// Text a screen reader announces for a link, including alt text of images inside it
+fn accessible_name(node: &Node) -> String {
+    node.children
+        .iter()
+        .map(|child| match child.node_type {
+            NodeType::Image | NodeType::ImageReference => image_alt(child),
+            _ if child.children.is_empty() => child.text_content(),
+            _ => accessible_name(child),
+        })
+        .collect()
+}
+
+fn is_url(text: &str) -> bool {
+    text.starts_with("http://") || text.starts_with("https://") || text.starts_with("www.")
+}
+
+fn is_filename_like(alt: &str, url: Option<&str>) -> bool {
+    let alt = alt.trim().to_lowercase();
+    if IMAGE_EXTENSIONS.iter().any(|ext| alt.ends_with(ext)) {
+        return true;
+    }
+    url.and_then(|u| u.rsplit('/').next())
+        .is_some_and(|file| file.to_lowercase() == alt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Document, ProcessorOptions};
+
+    fn audit_str(input: &str) -> Vec<A11yIssue> {
+        let options = ProcessorOptions {
+            gfm: true,
+            position: true,
+            ..Default::default()
+        };
+        audit(&parse(&Document::new(input), options).ast)
+    }
+
+    fn ids(issues: &[A11yIssue]) -> Vec<&str> {
+        issues.iter().map(|i| i.rule_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_audit_findings() {
+        let input = "# Title\n\n![](a.png) ![IMG_0042.JPG](x/IMG_0042.JPG) ![A cat](cat.png)\n\n\
+                     [Click here](/a), [https://example.com](https://example.com), [](/empty), \
+                     [![Logo](logo.png)](/home) and [the guide](/guide).\n\n### Deep\n\n| | |\n|-|-|\n| a | b |\n";
+        let issues = audit_str(input);
+        assert_eq!(
+            ids(&issues),
+            vec![
+                "image-alt",
+                "image-alt-filename",
+                "link-text",
+                "link-bare-url",
+                "empty-link",
+                "heading-order",
+                "table-header",
+            ]
+        );
+
+        let first = &issues[0];
+        assert_eq!(first.wcag.criterion, "1.1.1");
+        assert_eq!(first.position.as_ref().unwrap().start.line, 3);
+        assert_eq!(issues[5].wcag.criterion, "1.3.1");
+        assert_eq!(issues[2].wcag.criterion, "2.4.4");
+    }
+
+    #[test]
+    fn test_clean_document() {
+        assert!(audit_str(
+            "# A\n\n## B\n\n[Read the guide](/guide) ![Diagram of the flow](f.svg)\n"
+        )
+        .is_empty());
+    }
+}
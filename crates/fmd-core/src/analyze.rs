@@ -0,0 +1,289 @@
+// Document metadata: outline, links, images, code languages and reading stats
+
+use crate::ast::{Node, NodeType};
+use crate::position::Position;
+use crate::visit::{visit, Control};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Words per minute used for the reading time estimate
+pub const WORDS_PER_MINUTE: usize = 200;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentInfo {
+    /// Headings in document order
+    pub headings: Vec<HeadingInfo>,
+    pub links: References,
+    pub images: References,
+    /// Distinct code block languages, in order of first use
+    pub code_languages: Vec<String>,
+    /// Words of prose; code, HTML and frontmatter are not counted
+    pub word_count: usize,
+    /// Non-whitespace characters of prose
+    pub char_count: usize,
+    /// Minutes at [`WORDS_PER_MINUTE`], rounded up
+    pub reading_time: usize,
+    /// Text of the first paragraph
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadingInfo {
+    pub depth: u8,
+    pub text: String,
+    /// `data.id` set by the heading-ids transform
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+}
+
+/// Links or images split by whether the URL points off-site
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct References {
+    pub internal: Vec<Reference>,
+    pub external: Vec<Reference>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    pub url: String,
+    /// Link text or image alt text
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+}
+
+impl References {
+    fn push(&mut self, reference: Reference) {
+        if is_external(&reference.url) {
+            self.external.push(reference);
+        } else {
+            self.internal.push(reference);
+        }
+    }
+}
+
+/// Collect metadata for a whole document
+///
+/// Reference-style links and images are resolved against the document's
+/// definitions. Parse with `position: true` to get heading and link positions.
+pub fn analyze(root: &Node) -> DocumentInfo {
+    let mut info = DocumentInfo::default();
+
+    let mut definitions = HashMap::new();
+    visit(root, |node, _| {
+        if node.node_type == NodeType::Definition {
+            if let (Some(id), Some(url)) = (&node.identifier, &node.url) {
+                definitions
+                    .entry(id.to_lowercase())
+                    .or_insert((url.clone(), node.title.clone()));
+            }
+        }
+        Control::Continue
+    });
+
+    visit(root, |node, _| {
+        match node.node_type {
+            NodeType::Heading => info.headings.push(HeadingInfo {
+                depth: node.depth.unwrap_or(1),
+                text: node.text_content(),
+                id: node
+                    .data
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                position: node.position.clone(),
+            }),
+            NodeType::Paragraph if info.summary.is_none() => {
+                let text = node.text_content().replace('\n', " ");
+                if !text.trim().is_empty() {
+                    info.summary = Some(text.trim().to_string());
+                }
+            }
+            NodeType::Code => {
+                if let Some(lang) = &node.lang {
+                    if !info.code_languages.contains(lang) {
+                        info.code_languages.push(lang.clone());
+                    }
+                }
+            }
+            NodeType::Link
+            | NodeType::Image
+            | NodeType::LinkReference
+            | NodeType::ImageReference => {
+                let resolved = match &node.url {
+                    Some(url) => Some((url.clone(), node.title.clone())),
+                    None => node
+                        .identifier
+                        .as_ref()
+                        .and_then(|id| definitions.get(&id.to_lowercase()).cloned()),
+                };
+                if let Some((url, title)) = resolved {
+                    let is_image =
+                        matches!(node.node_type, NodeType::Image | NodeType::ImageReference);
+                    let reference = Reference {
+                        url,
+                        text: node.alt.clone().unwrap_or_else(|| node.text_content()),
+                        title,
+                        position: node.position.clone(),
+                    };
+                    if is_image {
+                        info.images.push(reference);
+                    } else {
+                        info.links.push(reference);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Control::Continue
+    });
+
+    let mut prose = String::new();
+    collect_prose(root, &mut prose);
+    info.word_count = count_words(&prose);
+    info.char_count = prose.chars().filter(|c| !c.is_whitespace()).count();
+    info.reading_time = info.word_count.div_ceil(WORDS_PER_MINUTE);
+    info
+}
+
+/// Anything with a scheme (`https:`, `mailto:`) or protocol-relative `//host`
+pub fn is_external(url: &str) -> bool {
+    if url.starts_with("//") {
+        return true;
+    }
+    match url.find(':') {
+        Some(colon) => {
+            let scheme = &url[..colon];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+fn collect_prose(node: &Node, out: &mut String) {
+    match node.node_type {
+        NodeType::Code
+        | NodeType::Html
+        | NodeType::Math
+        | NodeType::Yaml
+        | NodeType::FrontMatter
+        | NodeType::Definition
+        | NodeType::MdxjsEsm
+        | NodeType::MdxFlowExpression
+        | NodeType::MdxTextExpression => {}
+        NodeType::Text | NodeType::InlineCode | NodeType::InlineMath => {
+            out.push_str(node.value.as_deref().unwrap_or_default());
+        }
+        NodeType::Image | NodeType::ImageReference => {}
+        _ => {
+            for child in &node.children {
+                collect_prose(child, out);
+            }
+            // Keep words in neighbouring blocks or cells apart
+            if !matches!(
+                node.node_type,
+                NodeType::Emphasis | NodeType::Strong | NodeType::Delete | NodeType::Link
+            ) {
+                out.push('\n');
+            }
+        }
+    }
+}
+
+/// Whitespace-separated words; CJK characters count as one word each
+fn count_words(text: &str) -> usize {
+    text.split_whitespace()
+        .map(|token| {
+            let cjk = token.chars().filter(|&c| is_cjk(c)).count();
+            let rest = token.chars().any(|c| c.is_alphanumeric() && !is_cjk(c));
+            cjk + usize::from(rest)
+        })
+        .sum()
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}' // Hiragana, Katakana
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{ac00}'..='\u{d7af}' // Hangul syllables
+        | '\u{f900}'..='\u{faff}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Document, ProcessorOptions};
+
+    fn analyze_str(input: &str) -> DocumentInfo {
+        let options = ProcessorOptions {
+            gfm: true,
+            frontmatter: true,
+            position: true,
+            ..Default::default()
+        };
+        analyze(&parse(&Document::new(input), options).ast)
+    }
+
+    #[test]
+    fn test_analyze() {
+        let info = analyze_str(
+            "---\ntitle: Doc\n---\n\n# Intro\n\nThe *quick* brown fox\njumps.\n\n\
+             ## Links\n\nSee [guide](./guide.md), [site](https://example.com) and [ref][r].\n\n\
+             ![Logo](/logo.png) ![Badge](https://img.shields.io/x.svg)\n\n\
+             ```rust\nfn main() {}\n```\n\n```js\nx\n```\n\n```rust\ny\n```\n\n[r]: mailto:a@b.c\n",
+        );
+
+        let outline: Vec<_> = info
+            .headings
+            .iter()
+            .map(|h| (h.depth, h.text.as_str()))
+            .collect();
+        assert_eq!(outline, vec![(1, "Intro"), (2, "Links")]);
+        assert_eq!(info.headings[1].position.as_ref().unwrap().start.line, 10);
+
+        let urls = |refs: &[Reference]| refs.iter().map(|r| r.url.clone()).collect::<Vec<_>>();
+        assert_eq!(urls(&info.links.internal), vec!["./guide.md"]);
+        assert_eq!(
+            urls(&info.links.external),
+            vec!["https://example.com", "mailto:a@b.c"]
+        );
+        assert_eq!(urls(&info.images.internal), vec!["/logo.png"]);
+        assert_eq!(info.images.external[0].text, "Badge");
+
+        assert_eq!(info.code_languages, vec!["rust", "js"]);
+        assert_eq!(info.summary.as_deref(), Some("The quick brown fox jumps."));
+        // Intro / The quick brown fox jumps / Links / See guide, site and ref
+        assert_eq!(info.word_count, 12);
+        assert_eq!(info.reading_time, 1);
+    }
+
+    #[test]
+    fn test_counts() {
+        let info = analyze_str("日本語 text, `code`.\n\n| a | b |\n|---|---|\n| c | d |\n");
+        assert_eq!(info.word_count, 3 + 2 + 4);
+        assert_eq!(info.char_count, "日本語text,code.abcd".chars().count());
+        assert_eq!(analyze_str("").reading_time, 0);
+    }
+
+    #[test]
+    fn test_is_external() {
+        assert!(is_external("https://a.b"));
+        assert!(is_external("//cdn.example.com/x.js"));
+        assert!(!is_external("/docs"));
+        assert!(!is_external("#top"));
+        assert!(!is_external("./a:b"));
+    }
+}
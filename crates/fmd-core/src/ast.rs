@@ -5,12 +5,12 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Node {
     #[serde(rename = "type")]
     pub node_type: NodeType,
 
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub children: Vec<Node>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -57,6 +57,33 @@ pub struct Node {
     pub align: Option<Vec<String>>,
 }
 
+impl Node {
+    /// Concatenated text of all literal descendants (mdast-util-to-string)
+    pub fn text_content(&self) -> String {
+        let mut out = String::new();
+        collect_text(self, &mut out);
+        out
+    }
+}
+
+fn collect_text(node: &Node, out: &mut String) {
+    match node.node_type {
+        NodeType::Text | NodeType::InlineCode | NodeType::InlineMath => {
+            if let Some(value) = &node.value {
+                out.push_str(value);
+            }
+        }
+        NodeType::Image | NodeType::ImageReference if node.alt.is_some() => {
+            out.push_str(node.alt.as_deref().unwrap_or_default());
+        }
+        _ => {
+            for child in &node.children {
+                collect_text(child, out);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub enum NodeType {
@@ -114,3 +141,94 @@ pub enum NodeType {
     // YAML
     Yaml,
 }
+
+impl NodeType {
+    /// Every node type, in declaration order
+    pub const ALL: [NodeType; 37] = [
+        NodeType::Root,
+        NodeType::Paragraph,
+        NodeType::Heading,
+        NodeType::ThematicBreak,
+        NodeType::Blockquote,
+        NodeType::List,
+        NodeType::ListItem,
+        NodeType::Code,
+        NodeType::Html,
+        NodeType::Definition,
+        NodeType::FrontMatter,
+        NodeType::Text,
+        NodeType::Emphasis,
+        NodeType::Strong,
+        NodeType::InlineCode,
+        NodeType::Break,
+        NodeType::Link,
+        NodeType::Image,
+        NodeType::LinkReference,
+        NodeType::ImageReference,
+        NodeType::Table,
+        NodeType::TableRow,
+        NodeType::TableCell,
+        NodeType::Delete,
+        NodeType::FootnoteDefinition,
+        NodeType::FootnoteReference,
+        NodeType::MdxjsEsm,
+        NodeType::MdxJsxFlowElement,
+        NodeType::MdxJsxTextElement,
+        NodeType::MdxFlowExpression,
+        NodeType::MdxTextExpression,
+        NodeType::ContainerDirective,
+        NodeType::LeafDirective,
+        NodeType::TextDirective,
+        NodeType::Math,
+        NodeType::InlineMath,
+        NodeType::Yaml,
+    ];
+
+    /// mdast name of the type, as used in serialized ASTs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeType::Root => "root",
+            NodeType::Paragraph => "paragraph",
+            NodeType::Heading => "heading",
+            NodeType::ThematicBreak => "thematicBreak",
+            NodeType::Blockquote => "blockquote",
+            NodeType::List => "list",
+            NodeType::ListItem => "listItem",
+            NodeType::Code => "code",
+            NodeType::Html => "html",
+            NodeType::Definition => "definition",
+            NodeType::FrontMatter => "frontMatter",
+            NodeType::Text => "text",
+            NodeType::Emphasis => "emphasis",
+            NodeType::Strong => "strong",
+            NodeType::InlineCode => "inlineCode",
+            NodeType::Break => "break",
+            NodeType::Link => "link",
+            NodeType::Image => "image",
+            NodeType::LinkReference => "linkReference",
+            NodeType::ImageReference => "imageReference",
+            NodeType::Table => "table",
+            NodeType::TableRow => "tableRow",
+            NodeType::TableCell => "tableCell",
+            NodeType::Delete => "delete",
+            NodeType::FootnoteDefinition => "footnoteDefinition",
+            NodeType::FootnoteReference => "footnoteReference",
+            NodeType::MdxjsEsm => "mdxjsEsm",
+            NodeType::MdxJsxFlowElement => "mdxJsxFlowElement",
+            NodeType::MdxJsxTextElement => "mdxJsxTextElement",
+            NodeType::MdxFlowExpression => "mdxFlowExpression",
+            NodeType::MdxTextExpression => "mdxTextExpression",
+            NodeType::ContainerDirective => "containerDirective",
+            NodeType::LeafDirective => "leafDirective",
+            NodeType::TextDirective => "textDirective",
+            NodeType::Math => "math",
+            NodeType::InlineMath => "inlineMath",
+            NodeType::Yaml => "yaml",
+        }
+    }
+
+    /// Look up a type by its mdast name
+    pub fn from_name(name: &str) -> Option<NodeType> {
+        Self::ALL.iter().find(|t| t.as_str() == name).cloned()
+    }
+}
@@ -0,0 +1,551 @@
+// Compact binary encoding of the AST
+//
+// Layout (version 1):
+//   magic "FMDB", version byte
+//   string table: varint count, then (varint length, UTF-8 bytes) per string
+//   root node, pre-order:
+//     type id (byte, index into `TYPE_IDS`), varint field flags,
+//     present fields in flag order (strings as table indices),
+//     varint child count, children
+// Positions are stored relative to the previous node's start so typical
+// documents need one or two bytes per coordinate.
+
+use crate::ast::{Node, NodeType};
+use crate::position::{Point, Position};
+use std::collections::HashMap;
+use std::fmt;
+
+pub const MAGIC: &[u8; 4] = b"FMDB";
+/// Bumped whenever the layout changes; decoders reject other versions
+pub const VERSION: u8 = 1;
+
+/// Node types by their encoded id. Append new types at the end; reordering or
+/// removing entries changes the meaning of existing caches and needs a new `VERSION`
+const TYPE_IDS: [NodeType; 37] = [
+    NodeType::Root,
+    NodeType::Paragraph,
+    NodeType::Heading,
+    NodeType::ThematicBreak,
+    NodeType::Blockquote,
+    NodeType::List,
+    NodeType::ListItem,
+    NodeType::Code,
+    NodeType::Html,
+    NodeType::Definition,
+    NodeType::FrontMatter,
+    NodeType::Text,
+    NodeType::Emphasis,
+    NodeType::Strong,
+    NodeType::InlineCode,
+    NodeType::Break,
+    NodeType::Link,
+    NodeType::Image,
+    NodeType::LinkReference,
+    NodeType::ImageReference,
+    NodeType::Table,
+    NodeType::TableRow,
+    NodeType::TableCell,
+    NodeType::Delete,
+    NodeType::FootnoteDefinition,
+    NodeType::FootnoteReference,
+    NodeType::MdxjsEsm,
+    NodeType::MdxJsxFlowElement,
+    NodeType::MdxJsxTextElement,
+    NodeType::MdxFlowExpression,
+    NodeType::MdxTextExpression,
+    NodeType::ContainerDirective,
+    NodeType::LeafDirective,
+    NodeType::TextDirective,
+    NodeType::Math,
+    NodeType::InlineMath,
+    NodeType::Yaml,
+];
+
+const VALUE: u32 = 1 << 0;
+const POSITION: u32 = 1 << 1;
+const POSITION_SOURCE: u32 = 1 << 2;
+const DATA: u32 = 1 << 3;
+const DEPTH: u32 = 1 << 4;
+const ORDERED: u32 = 1 << 5;
+const ORDERED_TRUE: u32 = 1 << 6;
+const CHECKED: u32 = 1 << 7;
+const CHECKED_TRUE: u32 = 1 << 8;
+const LANG: u32 = 1 << 9;
+const META: u32 = 1 << 10;
+const URL: u32 = 1 << 11;
+const TITLE: u32 = 1 << 12;
+const ALT: u32 = 1 << 13;
+const IDENTIFIER: u32 = 1 << 14;
+const START: u32 = 1 << 15;
+const ALIGN: u32 = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    pub message: String,
+    /// Byte offset where decoding failed
+    pub offset: usize,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid binary AST at byte {}: {}",
+            self.offset, self.message
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Encode a tree in the binary format
+pub fn encode(root: &Node) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    let mut tree = Vec::new();
+    encoder.node(root, &mut tree);
+
+    let mut out = Vec::with_capacity(tree.len() + encoder.table_bytes + 16);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    write_varint(&mut out, encoder.strings.len() as u64);
+    for s in &encoder.strings {
+        write_varint(&mut out, s.len() as u64);
+        out.extend_from_slice(s.as_bytes());
+    }
+    out.extend_from_slice(&tree);
+    out
+}
+
+/// Decode bytes produced by [`encode`]
+pub fn decode(bytes: &[u8]) -> Result<Node, DecodeError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        strings: Vec::new(),
+        last_start: Point {
+            line: 1,
+            column: 1,
+            offset: 0,
+        },
+    };
+
+    if bytes.len() < 5 || &bytes[..4] != MAGIC {
+        return Err(decoder.error("missing FMDB header"));
+    }
+    if bytes[4] != VERSION {
+        return Err(decoder.error(&format!("unsupported version {}", bytes[4])));
+    }
+    decoder.pos = 5;
+
+    let count = decoder.varint()? as usize;
+    decoder.strings.reserve(count.min(bytes.len()));
+    for _ in 0..count {
+        let len = decoder.varint()? as usize;
+        let raw = decoder.take(len)?;
+        let s = std::str::from_utf8(raw).map_err(|_| decoder.error("string is not UTF-8"))?;
+        decoder.strings.push(s.to_string());
+    }
+
+    let root = decoder.node(0)?;
+    if decoder.pos != bytes.len() {
+        return Err(decoder.error("trailing bytes"));
+    }
+    Ok(root)
+}
+
+#[derive(Default)]
+struct Encoder {
+    strings: Vec<String>,
+    index: HashMap<String, u64>,
+    table_bytes: usize,
+    last_start: Option<Point>,
+}
+
+impl Encoder {
+    fn string(&mut self, out: &mut Vec<u8>, s: &str) {
+        let id = match self.index.get(s) {
+            Some(&id) => id,
+            None => {
+                let id = self.strings.len() as u64;
+                self.strings.push(s.to_string());
+                self.index.insert(s.to_string(), id);
+                self.table_bytes += s.len() + 2;
+                id
+            }
+        };
+        write_varint(out, id);
+    }
+
+    fn node(&mut self, node: &Node, out: &mut Vec<u8>) {
+        let type_id = TYPE_IDS
+            .iter()
+            .position(|t| *t == node.node_type)
+            .unwrap_or(0);
+        out.push(type_id as u8);
+
+        let mut flags = 0;
+        let mut set = |present: bool, bit: u32| {
+            if present {
+                flags |= bit;
+            }
+        };
+        set(node.value.is_some(), VALUE);
+        set(node.position.is_some(), POSITION);
+        set(
+            node.position.as_ref().is_some_and(|p| p.source.is_some()),
+            POSITION_SOURCE,
+        );
+        set(!node.data.is_empty(), DATA);
+        set(node.depth.is_some(), DEPTH);
+        set(node.ordered.is_some(), ORDERED);
+        set(node.ordered == Some(true), ORDERED_TRUE);
+        set(node.checked.is_some(), CHECKED);
+        set(node.checked == Some(true), CHECKED_TRUE);
+        set(node.lang.is_some(), LANG);
+        set(node.meta.is_some(), META);
+        set(node.url.is_some(), URL);
+        set(node.title.is_some(), TITLE);
+        set(node.alt.is_some(), ALT);
+        set(node.identifier.is_some(), IDENTIFIER);
+        set(node.start.is_some(), START);
+        set(node.align.is_some(), ALIGN);
+        write_varint(out, flags as u64);
+
+        if let Some(value) = &node.value {
+            self.string(out, value);
+        }
+        if let Some(position) = &node.position {
+            let last = self.last_start.clone().unwrap_or(Point {
+                line: 1,
+                column: 1,
+                offset: 0,
+            });
+            let (start, end) = (&position.start, &position.end);
+            write_signed(out, start.offset as i64 - last.offset as i64);
+            write_signed(out, start.line as i64 - last.line as i64);
+            write_varint(out, start.column as u64);
+            write_signed(out, end.offset as i64 - start.offset as i64);
+            write_signed(out, end.line as i64 - start.line as i64);
+            write_varint(out, end.column as u64);
+            if let Some(source) = &position.source {
+                self.string(out, source);
+            }
+            self.last_start = Some(start.clone());
+        }
+        if !node.data.is_empty() {
+            let json = serde_json::to_string(&node.data).unwrap_or_default();
+            self.string(out, &json);
+        }
+        if let Some(depth) = node.depth {
+            out.push(depth);
+        }
+        for field in [
+            &node.lang,
+            &node.meta,
+            &node.url,
+            &node.title,
+            &node.alt,
+            &node.identifier,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.string(out, field);
+        }
+        if let Some(start) = node.start {
+            write_varint(out, start);
+        }
+        if let Some(align) = &node.align {
+            write_varint(out, align.len() as u64);
+            for a in align {
+                self.string(out, a);
+            }
+        }
+
+        write_varint(out, node.children.len() as u64);
+        for child in &node.children {
+            self.node(child, out);
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    strings: Vec<String>,
+    last_start: Point,
+}
+
+/// Deeper trees are rejected rather than risking stack exhaustion
+const MAX_DEPTH: usize = 1024;
+
+impl<'a> Decoder<'a> {
+    fn error(&self, message: &str) -> DecodeError {
+        DecodeError {
+            message: message.to_string(),
+            offset: self.pos,
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let b = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            value |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error("varint too long"))
+    }
+
+    fn signed(&mut self) -> Result<i64, DecodeError> {
+        let v = self.varint()?;
+        Ok(((v >> 1) as i64) ^ -((v & 1) as i64))
+    }
+
+    /// `base` plus a signed delta
+    fn relative(&mut self, base: i64) -> Result<i64, DecodeError> {
+        let delta = self.signed()?;
+        base.checked_add(delta)
+            .ok_or_else(|| self.error("coordinate out of range"))
+    }
+
+    fn usize_from(&self, v: i64) -> Result<usize, DecodeError> {
+        usize::try_from(v).map_err(|_| self.error("negative coordinate"))
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let id = self.varint()? as usize;
+        self.strings
+            .get(id)
+            .cloned()
+            .ok_or_else(|| self.error("string index out of range"))
+    }
+
+    fn node(&mut self, depth: usize) -> Result<Node, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("tree too deep"));
+        }
+        let type_id = self.byte()? as usize;
+        let node_type = TYPE_IDS
+            .get(type_id)
+            .cloned()
+            .ok_or_else(|| self.error("unknown node type"))?;
+        let flags = self.varint()? as u32;
+        let has = |bit: u32| flags & bit != 0;
+
+        let mut node = Node {
+            node_type,
+            ..Default::default()
+        };
+        if has(VALUE) {
+            node.value = Some(self.string()?);
+        }
+        if has(POSITION) {
+            let start_offset = self.relative(self.last_start.offset as i64)?;
+            let start_line = self.relative(self.last_start.line as i64)?;
+            let start_column = self.varint()? as usize;
+            let end_offset = self.relative(start_offset)?;
+            let end_line = self.relative(start_line)?;
+            let end_column = self.varint()? as usize;
+            let start = Point {
+                line: self.usize_from(start_line)?,
+                column: start_column,
+                offset: self.usize_from(start_offset)?,
+            };
+            let end = Point {
+                line: self.usize_from(end_line)?,
+                column: end_column,
+                offset: self.usize_from(end_offset)?,
+            };
+            let source = if has(POSITION_SOURCE) {
+                Some(self.string()?)
+            } else {
+                None
+            };
+            self.last_start = start.clone();
+            node.position = Some(Position { start, end, source });
+        }
+        if has(DATA) {
+            let json = self.string()?;
+            node.data = serde_json::from_str(&json).map_err(|_| self.error("invalid data"))?;
+        }
+        if has(DEPTH) {
+            node.depth = Some(self.byte()?);
+        }
+        if has(ORDERED) {
+            node.ordered = Some(has(ORDERED_TRUE));
+        }
+        if has(CHECKED) {
+            node.checked = Some(has(CHECKED_TRUE));
+        }
+        for (bit, field) in [
+            (LANG, &mut node.lang),
+            (META, &mut node.meta),
+            (URL, &mut node.url),
+            (TITLE, &mut node.title),
+            (ALT, &mut node.alt),
+            (IDENTIFIER, &mut node.identifier),
+        ] {
+            if has(bit) {
+                *field = Some(self.string()?);
+            }
+        }
+        if has(START) {
+            node.start = Some(self.varint()?);
+        }
+        if has(ALIGN) {
+            let count = self.varint()? as usize;
+            let mut align = Vec::with_capacity(count.min(64));
+            for _ in 0..count {
+                align.push(self.string()?);
+            }
+            node.align = Some(align);
+        }
+
+        let count = self.varint()? as usize;
+        // Every child takes at least two bytes
+        if count > self.bytes.len() - self.pos {
+            return Err(self.error("child count exceeds input"));
+        }
+        node.children.reserve(count);
+        for _ in 0..count {
+            node.children.push(self.node(depth + 1)?);
+        }
+        Ok(node)
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let b = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(b);
+            return;
+        }
+        out.push(b | 0x80);
+    }
+}
+
+/// Zigzag so small negative deltas stay small
+fn write_signed(out: &mut Vec<u8>, value: i64) {
+    write_varint(out, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Document, ProcessorOptions};
+
+    const SAMPLE: &str = "---\ntitle: x\n---\n\n# Heading\n\nSome *text* with [a link](https://e.com \"T\") and `code`.\n\n1. one\n2. two\n\n- [x] done\n\n| a | b |\n|:--|--:|\n| 1 | 2 |\n\n```rust meta\nfn main() {}\n```\n\nFoot[^n].\n\n[^n]: Note.\n";
+
+    fn sample(position: bool) -> Node {
+        let options = ProcessorOptions {
+            gfm: true,
+            frontmatter: true,
+            position,
+            ..Default::default()
+        };
+        parse(&Document::new(SAMPLE), options).ast
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for position in [false, true] {
+            let mut ast = sample(position);
+            ast.children[1]
+                .data
+                .insert("id".to_string(), serde_json::json!("heading"));
+            let bytes = encode(&ast);
+            assert_eq!(&bytes[..4], MAGIC);
+            assert_eq!(decode(&bytes).unwrap(), ast);
+        }
+    }
+
+    #[test]
+    fn test_smaller_than_json() {
+        let ast = sample(true);
+        let json = serde_json::to_vec(&ast).unwrap();
+        assert!(encode(&ast).len() * 3 < json.len());
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        let bytes = encode(&sample(false));
+        assert!(decode(b"nope").is_err());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = VERSION + 1;
+        assert!(decode(&wrong_version)
+            .unwrap_err()
+            .message
+            .contains("unsupported version"));
+
+        for len in [5, bytes.len() / 2, bytes.len() - 1] {
+            assert!(decode(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_rejects_overflowing_positions() {
+        // Root with a position whose deltas are all `i64::MAX`
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([VERSION, 0, 0]);
+        write_varint(&mut bytes, POSITION as u64);
+        for _ in 0..2 {
+            write_varint(&mut bytes, (i64::MAX as u64) << 1);
+        }
+        bytes.push(0);
+        for _ in 0..2 {
+            write_varint(&mut bytes, (i64::MAX as u64) << 1);
+        }
+        bytes.extend([0, 0]);
+        assert!(decode(&bytes)
+            .unwrap_err()
+            .message
+            .contains("coordinate out of range"));
+    }
+
+    #[test]
+    fn test_type_ids_are_stable() {
+        for node_type in NodeType::ALL {
+            assert!(
+                TYPE_IDS.contains(&node_type),
+                "{:?} has no type id",
+                node_type
+            );
+        }
+        let names: Vec<_> = TYPE_IDS.iter().map(NodeType::as_str).collect();
+        assert_eq!(
+            names.join(" "),
+            "root paragraph heading thematicBreak blockquote list listItem code html \
+             definition frontMatter text emphasis strong inlineCode break link image \
+             linkReference imageReference table tableRow tableCell delete \
+             footnoteDefinition footnoteReference mdxjsEsm mdxJsxFlowElement \
+             mdxJsxTextElement mdxFlowExpression mdxTextExpression containerDirective \
+             leafDirective textDirective math inlineMath yaml"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+// Structured fenced code meta: ```rust title="main.rs" {1,3-5} showLineNumbers
+
+use crate::ast::{Node, NodeType};
+use crate::processor::{Transformer, VFile};
+use crate::visit::visit_by_type_mut;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Parsed code fence meta, stored as `data.codeMeta` by [`CodeMetaParsing`]
+///
+/// Line numbers are 1-based and count lines of the block, independent of
+/// `start_line`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CodeMeta {
+    /// `title="file.rs"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// `{1,3-5}`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub highlight: Vec<usize>,
+    /// `ins={2}`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ins: Vec<usize>,
+    /// `del={3}`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub del: Vec<usize>,
+    /// `showLineNumbers` or `showLineNumbers{10}`
+    pub show_line_numbers: bool,
+    /// Number shown for the first line: `startLine=10`
+    pub start_line: usize,
+    /// Any other `key=value` or bare `key` (value `""`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl Default for CodeMeta {
+    fn default() -> Self {
+        Self {
+            title: None,
+            highlight: Vec::new(),
+            ins: Vec::new(),
+            del: Vec::new(),
+            show_line_numbers: false,
+            start_line: 1,
+            attributes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Upper bound for line numbers in ranges when the block's length is unknown
+const MAX_LINES: usize = 100_000;
+
+impl CodeMeta {
+    pub fn parse(meta: &str) -> Self {
+        Self::parse_for(meta, MAX_LINES)
+    }
+
+    /// Parse meta for a block of `line_count` lines; range parts past the end are dropped
+    pub fn parse_for(meta: &str, line_count: usize) -> Self {
+        let mut result = Self::default();
+        let mut rest = meta.trim_start();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('{') {
+                let (ranges, after) = braced(after);
+                result.highlight.extend(line_ranges(ranges, line_count));
+                rest = after;
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '=' || c == '{')
+                    .unwrap_or(rest.len());
+                let (key, after) = rest.split_at(end);
+                let (value, after) = value(after);
+                rest = after;
+                result.set(key, value, line_count);
+            }
+            rest = rest.trim_start();
+        }
+        result.highlight.sort_unstable();
+        result.highlight.dedup();
+        result
+    }
+
+    /// Read `data.codeMeta`, or parse `node.meta` when the transform has not run
+    pub fn from_node(node: &Node) -> Self {
+        node.data
+            .get("codeMeta")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_else(|| {
+                Self::parse_for(node.meta.as_deref().unwrap_or_default(), line_count(node))
+            })
+    }
+
+    /// Whether rendering needs one wrapper per line
+    pub fn has_line_options(&self) -> bool {
+        self.show_line_numbers
+            || self.start_line != 1
+            || !self.highlight.is_empty()
+            || !self.ins.is_empty()
+            || !self.del.is_empty()
+    }
+
+    fn set(&mut self, key: &str, value: Option<Value<'_>>, line_count: usize) {
+        match (key, value) {
+            ("title", Some(Value::Text(title))) => self.title = Some(title.to_string()),
+            ("ins", Some(Value::Braced(ranges))) => self.ins = line_ranges(ranges, line_count),
+            ("del", Some(Value::Braced(ranges))) => self.del = line_ranges(ranges, line_count),
+            ("showLineNumbers", value) => {
+                self.show_line_numbers = true;
+                if let Some(start) = value.and_then(|v| v.as_str().trim().parse().ok()) {
+                    self.start_line = start;
+                }
+            }
+            ("startLine", Some(value)) => {
+                if let Ok(start) = value.as_str().trim().parse() {
+                    self.start_line = start;
+                }
+            }
+            ("", _) => {}
+            (key, value) => {
+                let value = value.map_or("", |v| v.as_str());
+                self.attributes.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+}
+
+enum Value<'a> {
+    Text(&'a str),
+    Braced(&'a str),
+}
+
+impl<'a> Value<'a> {
+    fn as_str(&self) -> &'a str {
+        match self {
+            Value::Text(s) | Value::Braced(s) => s,
+        }
+    }
+}
+
+/// `="quoted"`, `='quoted'`, `={1-3}`, `=bare` or `{10}` right after a key
+fn value(input: &str) -> (Option<Value<'_>>, &str) {
+    if let Some(after) = input.strip_prefix('{') {
+        let (inner, rest) = braced(after);
+        return (Some(Value::Braced(inner)), rest);
+    }
+    let Some(after) = input.strip_prefix('=') else {
+        return (None, input);
+    };
+    if let Some(after) = after.strip_prefix('{') {
+        let (inner, rest) = braced(after);
+        return (Some(Value::Braced(inner)), rest);
+    }
+    for quote in ['"', '\''] {
+        if let Some(after) = after.strip_prefix(quote) {
+            let end = after.find(quote).unwrap_or(after.len());
+            let rest = after.get(end + 1..).unwrap_or_default();
+            return (Some(Value::Text(&after[..end])), rest);
+        }
+    }
+    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+    (Some(Value::Text(&after[..end])), &after[end..])
+}
+
+/// Split at the closing `}`; an unclosed brace takes the rest of the input
+fn braced(input: &str) -> (&str, &str) {
+    match input.find('}') {
+        Some(end) => (&input[..end], &input[end + 1..]),
+        None => (input, ""),
+    }
+}
+
+/// Lines of a code node, not counting an empty one after a final newline
+fn line_count(node: &Node) -> usize {
+    let value = node.value.as_deref().unwrap_or_default();
+    value
+        .strip_suffix('\n')
+        .unwrap_or(value)
+        .split('\n')
+        .count()
+}
+
+/// `1,3-5` → `[1, 3, 4, 5]`; malformed parts and line 0 are skipped and ranges end at `last`
+fn line_ranges(input: &str, last: usize) -> Vec<usize> {
+    let mut lines = Vec::new();
+    for part in input.split(',').map(str::trim) {
+        let bounds: Option<(usize, usize)> = match part.split_once('-') {
+            Some((start, end)) => start.trim().parse().ok().zip(end.trim().parse().ok()),
+            None => part.parse().ok().map(|line| (line, line)),
+        };
+        if let Some((start, end)) = bounds {
+            lines.extend(start.max(1)..=end.min(last));
+        }
+    }
+    lines.sort_unstable();
+    lines.dedup();
+    lines
+}
+
+/// Parses `meta` on fenced code into `data.codeMeta`
+#[derive(Debug, Default)]
+pub struct CodeMetaParsing;
+
+impl Transformer for CodeMetaParsing {
+    fn name(&self) -> &str {
+        "code-meta"
+    }
+
+    fn transform(&mut self, root: &mut Node, _file: &mut VFile) {
+        visit_by_type_mut(root, NodeType::Code, |node, _| {
+            let Some(meta) = node.meta.as_deref() else {
+                return;
+            };
+            let meta = CodeMeta::parse_for(meta, line_count(node));
+            if let Ok(value) = serde_json::to_value(meta) {
+                node.data.insert("codeMeta".to_string(), value);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let meta = CodeMeta::parse(
+            r#"title="src/main.rs" {1,3-5} showLineNumbers startLine=10 ins={6} del={7-8} copy lang=rs"#,
+        );
+        assert_eq!(meta.title.as_deref(), Some("src/main.rs"));
+        assert_eq!(meta.highlight, vec![1, 3, 4, 5]);
+        assert_eq!(meta.ins, vec![6]);
+        assert_eq!(meta.del, vec![7, 8]);
+        assert!(meta.show_line_numbers);
+        assert_eq!(meta.start_line, 10);
+        assert_eq!(meta.attributes["copy"], "");
+        assert_eq!(meta.attributes["lang"], "rs");
+
+        let meta = CodeMeta::parse("{3} {1, x, 2-} showLineNumbers{5} title='a b'");
+        assert_eq!(meta.highlight, vec![1, 3]);
+        assert_eq!(meta.start_line, 5);
+        assert_eq!(meta.title.as_deref(), Some("a b"));
+
+        assert!(!CodeMeta::parse("").has_line_options());
+        assert!(!CodeMeta::parse("title=x").has_line_options());
+    }
+
+    #[test]
+    fn test_transform() {
+        use crate::{Processor, ProcessorOptions};
+
+        let mut file =
+            VFile::new("```rust {2} title=\"a.rs\"\nfn a() {}\nfn b() {}\n```\n\n```\nx\n```\n");
+        let root = Processor::with_defaults(ProcessorOptions::default()).process(&mut file);
+        assert_eq!(
+            root.children[0].data["codeMeta"],
+            serde_json::json!({ "title": "a.rs", "highlight": [2], "showLineNumbers": false, "startLine": 1 })
+        );
+        assert!(!root.children[1].data.contains_key("codeMeta"));
+        assert_eq!(CodeMeta::from_node(&root.children[0]).highlight, vec![2]);
+    }
+
+    #[test]
+    fn test_huge_ranges_are_clamped() {
+        use crate::{Processor, ProcessorOptions};
+
+        let mut file =
+            VFile::new("```rust {1-18446744073709551615} ins={2-4000000000}\na\nb\n```\n");
+        let root = Processor::with_defaults(ProcessorOptions::default()).process(&mut file);
+        let meta = CodeMeta::from_node(&root.children[0]);
+        assert_eq!(meta.highlight, vec![1, 2]);
+        assert_eq!(meta.ins, vec![2]);
+
+        // Without the block, ranges stop at a fixed bound
+        let meta = CodeMeta::parse("{18446744073709551614-18446744073709551615} del={7-9}");
+        assert!(meta.highlight.is_empty());
+        assert_eq!(meta.del, vec![7, 8, 9]);
+        assert_eq!(CodeMeta::parse("{1-4000000000}").highlight.len(), MAX_LINES);
+
+        // Lines are 1-based
+        let meta = CodeMeta::parse("{0-3} ins={0} del={0,2}");
+        assert_eq!(meta.highlight, vec![1, 2, 3]);
+        assert!(meta.ins.is_empty());
+        assert_eq!(meta.del, vec![2]);
+    }
+}
@@ -0,0 +1,420 @@
+// Structural AST diff producing child-index-path patches
+
+use crate::ast::Node;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// One edit to a tree; paths are child indices from the root
+///
+/// Patches are applied in order and each path refers to the tree as left by
+/// the previous patches. `Insert` and `Remove` address the child slot itself:
+/// the last index is the position within the parent at the rest of the path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum AstPatch {
+    Insert {
+        path: Vec<usize>,
+        node: Node,
+    },
+    Remove {
+        path: Vec<usize>,
+    },
+    Replace {
+        path: Vec<usize>,
+        node: Node,
+    },
+    /// Set fields other than `children`; `null` clears a field
+    UpdateProps {
+        path: Vec<usize>,
+        props: Map<String, Value>,
+    },
+}
+
+impl AstPatch {
+    pub fn path(&self) -> &[usize] {
+        match self {
+            AstPatch::Insert { path, .. }
+            | AstPatch::Remove { path }
+            | AstPatch::Replace { path, .. }
+            | AstPatch::UpdateProps { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchError {
+    pub message: String,
+    pub path: Vec<usize>,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cannot apply patch at {:?}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Subtree hashes computed once per tree
+struct Hashed<'a> {
+    node: &'a Node,
+    hash: u64,
+    props: u64,
+    children: Vec<Hashed<'a>>,
+}
+
+impl<'a> Hashed<'a> {
+    fn new(node: &'a Node) -> Self {
+        let children: Vec<_> = node.children.iter().map(Hashed::new).collect();
+        let props = hash_props(node);
+        let mut hasher = DefaultHasher::new();
+        props.hash(&mut hasher);
+        for child in &children {
+            child.hash.hash(&mut hasher);
+        }
+        Self {
+            node,
+            hash: hasher.finish(),
+            props,
+            children,
+        }
+    }
+}
+
+/// Hash of a node's own fields (everything but `children`), like
+/// `rope::IncrementalTree::hash_node` but covering every field
+fn hash_props(node: &Node) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    node.node_type.as_str().hash(&mut hasher);
+    node.value.hash(&mut hasher);
+    node.depth.hash(&mut hasher);
+    node.ordered.hash(&mut hasher);
+    node.checked.hash(&mut hasher);
+    node.lang.hash(&mut hasher);
+    node.meta.hash(&mut hasher);
+    node.url.hash(&mut hasher);
+    node.title.hash(&mut hasher);
+    node.alt.hash(&mut hasher);
+    node.identifier.hash(&mut hasher);
+    node.start.hash(&mut hasher);
+    node.align.hash(&mut hasher);
+    if let Some(pos) = &node.position {
+        (
+            pos.start.offset,
+            pos.end.offset,
+            pos.start.line,
+            pos.start.column,
+        )
+            .hash(&mut hasher);
+        (pos.end.line, pos.end.column).hash(&mut hasher);
+    }
+    let mut keys: Vec<_> = node.data.keys().collect();
+    keys.sort();
+    for key in keys {
+        key.hash(&mut hasher);
+        node.data[key].to_string().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Patches that turn `old` into `new`
+pub fn diff(old: &Node, new: &Node) -> Vec<AstPatch> {
+    let mut patches = Vec::new();
+    diff_node(
+        &Hashed::new(old),
+        &Hashed::new(new),
+        &mut Vec::new(),
+        &mut patches,
+    );
+    patches
+}
+
+fn diff_node(old: &Hashed, new: &Hashed, path: &mut Vec<usize>, patches: &mut Vec<AstPatch>) {
+    if old.hash == new.hash {
+        return;
+    }
+    if old.node.node_type != new.node.node_type {
+        patches.push(AstPatch::Replace {
+            path: path.clone(),
+            node: new.node.clone(),
+        });
+        return;
+    }
+    if old.props != new.props {
+        let props = changed_props(old.node, new.node);
+        if !props.is_empty() {
+            patches.push(AstPatch::UpdateProps {
+                path: path.clone(),
+                props,
+            });
+        }
+    }
+    diff_children(&old.children, &new.children, path, patches);
+}
+
+enum Step {
+    Keep,
+    Delete,
+    Add(usize),
+}
+
+fn diff_children(
+    old: &[Hashed],
+    new: &[Hashed],
+    path: &mut Vec<usize>,
+    patches: &mut Vec<AstPatch>,
+) {
+    // Skip identical prefix and suffix, then align the middle on subtree hashes
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(a, b)| a.hash == b.hash)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a.hash == b.hash)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lcs = vec![vec![0u32; new_mid.len() + 1]; old_mid.len() + 1];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i][j] = if old_mid[i].hash == new_mid[j].hash {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut steps = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i].hash == new_mid[j].hash {
+            steps.push(Step::Keep);
+            i += 1;
+            j += 1;
+        } else if i < old_mid.len() && (j == new_mid.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            steps.push(Step::Delete);
+            i += 1;
+        } else {
+            steps.push(Step::Add(j));
+            j += 1;
+        }
+    }
+
+    // Walk the steps, pairing deletions with additions between kept children
+    // so that an edited child becomes a nested diff rather than remove + insert
+    let mut index = prefix;
+    let mut old_index = 0;
+    let mut s = 0;
+    while s < steps.len() {
+        if let Step::Keep = steps[s] {
+            index += 1;
+            old_index += 1;
+            s += 1;
+            continue;
+        }
+
+        let run_end = steps[s..]
+            .iter()
+            .position(|step| matches!(step, Step::Keep))
+            .map_or(steps.len(), |p| s + p);
+        let deleted = steps[s..run_end]
+            .iter()
+            .filter(|step| matches!(step, Step::Delete))
+            .count();
+        let added: Vec<usize> = steps[s..run_end]
+            .iter()
+            .filter_map(|step| match step {
+                Step::Add(j) => Some(*j),
+                _ => None,
+            })
+            .collect();
+
+        let paired = deleted.min(added.len());
+        for &j in &added[..paired] {
+            path.push(index);
+            diff_node(&old_mid[old_index], &new_mid[j], path, patches);
+            path.pop();
+            index += 1;
+            old_index += 1;
+        }
+        for _ in paired..deleted {
+            path.push(index);
+            patches.push(AstPatch::Remove { path: path.clone() });
+            path.pop();
+            old_index += 1;
+        }
+        for &j in &added[paired..] {
+            path.push(index);
+            patches.push(AstPatch::Insert {
+                path: path.clone(),
+                node: new_mid[j].node.clone(),
+            });
+            path.pop();
+            index += 1;
+        }
+        s = run_end;
+    }
+}
+
+fn props_of(node: &Node) -> Map<String, Value> {
+    let mut map = match serde_json::to_value(Node {
+        children: Vec::new(),
+        ..node.clone()
+    }) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    map.remove("children");
+    map
+}
+
+fn changed_props(old: &Node, new: &Node) -> Map<String, Value> {
+    let old_props = props_of(old);
+    let new_props = props_of(new);
+    let mut changed: Map<String, Value> = new_props
+        .iter()
+        .filter(|(key, value)| old_props.get(*key) != Some(value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    for key in old_props.keys() {
+        if !new_props.contains_key(key) {
+            changed.insert(key.clone(), Value::Null);
+        }
+    }
+    changed
+}
+
+/// Apply patches produced by [`diff`] in order
+pub fn apply_patches(root: &mut Node, patches: &[AstPatch]) -> Result<(), PatchError> {
+    for patch in patches {
+        apply_patch(root, patch)?;
+    }
+    Ok(())
+}
+
+/// Parent of the slot a path addresses, with the index within it
+fn child_slot<'a>(root: &'a mut Node, path: &[usize]) -> Option<(usize, &'a mut Node)> {
+    let (&index, parent_path) = path.split_last()?;
+    crate::visit::node_at_mut(root, parent_path).map(|parent| (index, parent))
+}
+
+fn apply_patch(root: &mut Node, patch: &AstPatch) -> Result<(), PatchError> {
+    let error = |message: &str| PatchError {
+        message: message.to_string(),
+        path: patch.path().to_vec(),
+    };
+    let path = patch.path();
+
+    match patch {
+        AstPatch::Replace { node, .. } => {
+            let target = crate::visit::node_at_mut(root, path).ok_or_else(|| error("no node"))?;
+            *target = node.clone();
+        }
+        AstPatch::UpdateProps { props, .. } => {
+            let target = crate::visit::node_at_mut(root, path).ok_or_else(|| error("no node"))?;
+            let mut value = serde_json::to_value(&*target).map_err(|e| error(&e.to_string()))?;
+            if let Value::Object(map) = &mut value {
+                for (key, prop) in props {
+                    if key == "children" {
+                        continue;
+                    }
+                    if prop.is_null() {
+                        map.remove(key);
+                    } else {
+                        map.insert(key.clone(), prop.clone());
+                    }
+                }
+            }
+            *target = serde_json::from_value(value).map_err(|e| error(&e.to_string()))?;
+        }
+        AstPatch::Insert { node, .. } => {
+            let (index, parent) = child_slot(root, path).ok_or_else(|| error("no parent"))?;
+            if index > parent.children.len() {
+                return Err(error("index out of range"));
+            }
+            parent.children.insert(index, node.clone());
+        }
+        AstPatch::Remove { .. } => {
+            let (index, parent) = child_slot(root, path).ok_or_else(|| error("no parent"))?;
+            if index >= parent.children.len() {
+                return Err(error("index out of range"));
+            }
+            parent.children.remove(index);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Document, ProcessorOptions};
+
+    fn ast(input: &str) -> Node {
+        parse(&Document::new(input), ProcessorOptions::default()).ast
+    }
+
+    fn assert_patches_apply(old: &str, new: &str) -> Vec<AstPatch> {
+        let (a, b) = (ast(old), ast(new));
+        let patches = diff(&a, &b);
+        let mut patched = a.clone();
+        apply_patches(&mut patched, &patches).unwrap();
+        assert_eq!(patched, b, "patches: {:#?}", patches);
+        patches
+    }
+
+    #[test]
+    fn test_identical_trees() {
+        assert!(assert_patches_apply("# A\n\nb\n", "# A\n\nb\n").is_empty());
+    }
+
+    #[test]
+    fn test_text_edit_updates_props() {
+        let patches = assert_patches_apply("# A\n\none\n\nthree\n", "# A\n\ntwo\n\nthree\n");
+        assert_eq!(patches.len(), 1);
+        match &patches[0] {
+            AstPatch::UpdateProps { path, props } => {
+                assert_eq!(path, &vec![1, 0]);
+                assert_eq!(props["value"], "two");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_insert_remove_replace() {
+        let patches = assert_patches_apply("a\n\nb\n\nc\n", "a\n\nnew\n\n# heading\n\nb\n");
+        assert!(patches
+            .iter()
+            .any(|p| matches!(p, AstPatch::Insert { path, .. } if path == &vec![1])));
+
+        let patches = assert_patches_apply("a\n\nb\n\nc\n", "a\n\nc\n");
+        assert_eq!(patches, vec![AstPatch::Remove { path: vec![1] }]);
+
+        let patches = assert_patches_apply("a\n\n- b\n", "a\n\n> b\n");
+        assert!(matches!(&patches[0], AstPatch::Replace { path, .. } if path == &vec![1]));
+
+        assert_patches_apply(
+            "*a* b `c`\n\n1. x\n2. y\n",
+            "a **b** `c`\n\n1. x\n3. z\n4. y\n",
+        );
+    }
+
+    #[test]
+    fn test_patch_serialization() {
+        let patch = AstPatch::Remove { path: vec![0, 2] };
+        assert_eq!(
+            serde_json::to_string(&patch).unwrap(),
+            r#"{"op":"remove","path":[0,2]}"#
+        );
+    }
+}
@@ -3,19 +3,36 @@
 use serde::{Deserialize, Serialize};
 
 // Re-export main types
+pub use analyze::{analyze, DocumentInfo};
 pub use ast::*;
+pub use code_meta::CodeMeta;
+pub use diff::{apply_patches, diff, AstPatch, PatchError};
 pub use error::{ErrorCollector, ParseError, ParseErrorKind};
 pub use incremental::*;
 pub use position::*;
+pub use processor::{Processor, Severity, Transformer, VFile, VFileMessage};
+pub use select::{select, select_all, SelectMatch, Selector, SelectorError};
+pub use toc::{insert_toc, toc, Toc, TocEntry, TocOptions};
 
+pub mod a11y;
+pub mod analyze;
 pub mod ast;
+pub mod binary;
+pub mod code_meta;
+pub mod diff;
 pub mod error;
 pub mod incremental;
 pub mod inline;
+pub mod lint;
 pub mod parser_impl;
 pub mod position;
+pub mod processor;
 pub mod rope;
 pub mod scanner;
+pub mod select;
+pub mod toc;
+pub mod transforms;
+pub mod visit;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
@@ -52,6 +69,10 @@ pub struct ProcessorOptions {
     pub incremental: bool,
     #[serde(default)]
     pub track_positions: bool,
+    /// Parse `# Title {#id .class data-key=value}`; sets `data.id` and `data.hProperties`.
+    /// Keys other than `data-*` are ignored
+    #[serde(default)]
+    pub heading_attributes: bool,
     #[serde(default)]
     pub gfm_options: GfmOptions,
 }
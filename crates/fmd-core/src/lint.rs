@@ -0,0 +1,719 @@
+// Markdown lint engine: rules over the AST and source lines
+
+use crate::ast::{Node, NodeType};
+use crate::position::{Point, Position};
+use crate::processor::Severity;
+use crate::visit::{visit, Control};
+use crate::{parse, Document, ProcessorOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Replace `source[start..end]` (byte offsets) with `text`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// A rule violation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    pub position: Position,
+    /// Edits that resolve the violation, if it can be fixed automatically
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fix: Vec<TextEdit>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}:{}: {}: {} [{}]",
+            self.position.start.line, self.position.start.column, label, self.message, self.rule_id
+        )
+    }
+}
+
+/// Severity override for a rule in [`LintOptions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LintOptions {
+    /// Per-rule severity overrides keyed by rule id
+    pub rules: BTreeMap<String, RuleLevel>,
+    /// Maximum line length for `line-length`
+    pub line_length: usize,
+    /// Required unordered list marker for `list-marker-style`; the first marker used wins if unset
+    pub list_marker: Option<char>,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+            line_length: 80,
+            list_marker: None,
+        }
+    }
+}
+
+/// Parsed document handed to every rule
+pub struct LintContext<'a> {
+    pub source: &'a str,
+    pub ast: &'a Node,
+    pub options: &'a LintOptions,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn new(source: &'a str, ast: &'a Node, options: &'a LintOptions) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            source,
+            ast,
+            options,
+            line_starts,
+        }
+    }
+
+    /// Point for a byte offset
+    pub fn point(&self, offset: usize) -> Point {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        Point {
+            line,
+            column: self.source[line_start..offset].chars().count() + 1,
+            offset,
+        }
+    }
+
+    /// Position spanning two byte offsets
+    pub fn position(&self, start: usize, end: usize) -> Position {
+        Position {
+            start: self.point(start),
+            end: self.point(end),
+            source: None,
+        }
+    }
+
+    /// Source lines with their starting byte offsets (without the newline)
+    pub fn lines(&self) -> impl Iterator<Item = (usize, &'a str)> + '_ {
+        self.line_starts.iter().map(|&start| {
+            let rest = &self.source[start.min(self.source.len())..];
+            let line = rest.split('\n').next().unwrap_or_default();
+            (start, line.strip_suffix('\r').unwrap_or(line))
+        })
+    }
+
+    /// 1-based lines covered by nodes of the given types
+    pub fn lines_covered_by(&self, types: &[NodeType]) -> Vec<(usize, usize)> {
+        let mut ranges = Vec::new();
+        visit(self.ast, |node, _| {
+            if types.contains(&node.node_type) {
+                if let Some(pos) = &node.position {
+                    ranges.push((pos.start.line, pos.end.line));
+                }
+                return Control::Skip;
+            }
+            Control::Continue
+        });
+        ranges
+    }
+}
+
+/// Violation reported by a rule; the engine fills in the rule id and severity
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub message: String,
+    pub position: Position,
+    pub fix: Vec<TextEdit>,
+}
+
+impl Finding {
+    pub fn new(message: impl Into<String>, position: Position) -> Self {
+        Self {
+            message: message.into(),
+            position,
+            fix: Vec::new(),
+        }
+    }
+
+    pub fn with_fix(mut self, edit: TextEdit) -> Self {
+        self.fix.push(edit);
+        self
+    }
+}
+
+pub trait Rule: Send + Sync {
+    /// Stable id used in config, disable comments and output
+    fn id(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn check(&self, ctx: &LintContext) -> Vec<Finding>;
+}
+
+/// Runs a set of rules over a document
+pub struct Linter {
+    options: LintOptions,
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Linter {
+    /// Linter with the built-in rules
+    pub fn new(options: LintOptions) -> Self {
+        Self {
+            options,
+            rules: vec![
+                Box::new(HeadingIncrement),
+                Box::new(NoDuplicateHeadings),
+                Box::new(NoTrailingSpaces),
+                Box::new(NoBareUrls),
+                Box::new(ListMarkerStyle),
+                Box::new(FencedCodeLanguage),
+                Box::new(LineLength),
+            ],
+        }
+    }
+
+    pub fn with_rule<R: Rule + 'static>(mut self, rule: R) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|r| r.as_ref())
+    }
+
+    /// Lint `source`, sorted by position
+    pub fn check(&self, source: &str) -> Vec<Diagnostic> {
+        let options = ProcessorOptions {
+            gfm: true,
+            frontmatter: true,
+            position: true,
+            ..Default::default()
+        };
+        let ast = parse(&Document::new(source), options).ast;
+        let ctx = LintContext::new(source, &ast, &self.options);
+        let disabled = disabled_rules(&ast);
+
+        let mut diagnostics = Vec::new();
+        for rule in &self.rules {
+            let severity = match self.options.rules.get(rule.id()) {
+                Some(RuleLevel::Off) => continue,
+                Some(RuleLevel::Info) => Severity::Info,
+                Some(RuleLevel::Warning) => Severity::Warning,
+                Some(RuleLevel::Error) => Severity::Error,
+                None => rule.default_severity(),
+            };
+            if disabled.all || disabled.rules.iter().any(|id| id == rule.id()) {
+                continue;
+            }
+
+            diagnostics.extend(rule.check(&ctx).into_iter().map(|finding| Diagnostic {
+                rule_id: rule.id().to_string(),
+                severity,
+                message: finding.message,
+                position: finding.position,
+                fix: finding.fix,
+            }));
+        }
+
+        diagnostics.sort_by_key(|d| (d.position.start.offset, d.rule_id.clone()));
+        diagnostics
+    }
+}
+
+/// Lint with the built-in rules
+pub fn lint(source: &str, options: &LintOptions) -> Vec<Diagnostic> {
+    Linter::new(options.clone()).check(source)
+}
+
+/// Apply the fixes of `diagnostics` to `source`; overlapping edits after the first are skipped
+pub fn apply_fixes(source: &str, diagnostics: &[Diagnostic]) -> String {
+    let mut edits: Vec<&TextEdit> = diagnostics.iter().flat_map(|d| &d.fix).collect();
+    edits.sort_by_key(|e| (e.start, e.end));
+
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    for edit in edits {
+        if edit.start < cursor || edit.end > source.len() {
+            continue;
+        }
+        output.push_str(&source[cursor..edit.start]);
+        output.push_str(&edit.text);
+        cursor = edit.end;
+    }
+    output.push_str(&source[cursor..]);
+    output
+}
+
+#[derive(Default)]
+struct Disabled {
+    all: bool,
+    rules: Vec<String>,
+}
+
+/// `<!-- fmd-lint-disable -->` or `<!-- fmd-lint-disable rule-a rule-b -->` anywhere in the file
+fn disabled_rules(ast: &Node) -> Disabled {
+    let mut disabled = Disabled::default();
+    visit(ast, |node, _| {
+        if node.node_type != NodeType::Html {
+            return Control::Continue;
+        }
+        let value = node.value.as_deref().unwrap_or_default();
+        for comment in value.split("<!--").skip(1) {
+            let body = comment.split("-->").next().unwrap_or_default().trim();
+            if let Some(rest) = body.strip_prefix("fmd-lint-disable") {
+                if rest.trim().is_empty() {
+                    disabled.all = true;
+                } else if rest.starts_with(char::is_whitespace) {
+                    disabled
+                        .rules
+                        .extend(rest.split_whitespace().map(str::to_string));
+                }
+            }
+        }
+        Control::Continue
+    });
+    disabled
+}
+
+fn node_offsets(node: &Node) -> Option<(usize, usize)> {
+    node.position
+        .as_ref()
+        .map(|p| (p.start.offset, p.end.offset))
+}
+
+/// Heading levels should only increase one at a time
+pub struct HeadingIncrement;
+
+impl Rule for HeadingIncrement {
+    fn id(&self) -> &'static str {
+        "heading-increment"
+    }
+
+    fn description(&self) -> &'static str {
+        "Heading levels should only increment by one level at a time"
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut previous: Option<u8> = None;
+        visit(ctx.ast, |node, _| {
+            if node.node_type != NodeType::Heading {
+                return Control::Continue;
+            }
+            let depth = node.depth.unwrap_or(1);
+            if let (Some(prev), Some((start, end))) = (previous, node_offsets(node)) {
+                if depth > prev + 1 {
+                    let mut finding = Finding::new(
+                        format!("Expected heading level {} but found {}", prev + 1, depth),
+                        ctx.position(start, end),
+                    );
+                    let hashes = ctx.source[start..end]
+                        .chars()
+                        .take_while(|&c| c == '#')
+                        .count();
+                    if hashes == depth as usize {
+                        finding = finding.with_fix(TextEdit {
+                            start,
+                            end: start + hashes,
+                            text: "#".repeat(prev as usize + 1),
+                        });
+                    }
+                    findings.push(finding);
+                }
+            }
+            previous = Some(depth);
+            Control::Skip
+        });
+        findings
+    }
+}
+
+/// Headings should have unique text
+pub struct NoDuplicateHeadings;
+
+impl Rule for NoDuplicateHeadings {
+    fn id(&self) -> &'static str {
+        "no-duplicate-headings"
+    }
+
+    fn description(&self) -> &'static str {
+        "Multiple headings with the same content"
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        visit(ctx.ast, |node, _| {
+            if node.node_type != NodeType::Heading {
+                return Control::Continue;
+            }
+            let text = node.text_content().trim().to_string();
+            if let Some((start, end)) = node_offsets(node) {
+                let line = ctx.point(start).line;
+                if let Some(first) = seen.get(&text) {
+                    findings.push(Finding::new(
+                        format!("Duplicate heading \"{}\" (first on line {})", text, first),
+                        ctx.position(start, end),
+                    ));
+                } else {
+                    seen.insert(text, line);
+                }
+            }
+            Control::Skip
+        });
+        findings
+    }
+}
+
+/// Lines should not end in whitespace (two spaces before text are a hard break and allowed)
+pub struct NoTrailingSpaces;
+
+impl Rule for NoTrailingSpaces {
+    fn id(&self) -> &'static str {
+        "no-trailing-spaces"
+    }
+
+    fn description(&self) -> &'static str {
+        "Trailing whitespace"
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Finding> {
+        let code = ctx.lines_covered_by(&[NodeType::Code]);
+        let lines: Vec<_> = ctx.lines().collect();
+        let mut findings = Vec::new();
+
+        for (index, &(start, line)) in lines.iter().enumerate() {
+            let number = index + 1;
+            let trimmed = line.trim_end_matches([' ', '\t']);
+            if trimmed.len() == line.len() || in_ranges(&code, number) {
+                continue;
+            }
+
+            let hard_break = &line[trimmed.len()..] == "  "
+                && !trimmed.is_empty()
+                && lines
+                    .get(index + 1)
+                    .is_some_and(|(_, next)| !next.trim().is_empty());
+            if hard_break {
+                continue;
+            }
+
+            let from = start + trimmed.len();
+            let to = start + line.len();
+            findings.push(
+                Finding::new("Trailing whitespace", ctx.position(from, to)).with_fix(TextEdit {
+                    start: from,
+                    end: to,
+                    text: String::new(),
+                }),
+            );
+        }
+        findings
+    }
+}
+
+/// URLs should be links or autolinks (`<https://…>`), not bare text
+pub struct NoBareUrls;
+
+impl Rule for NoBareUrls {
+    fn id(&self) -> &'static str {
+        "no-bare-urls"
+    }
+
+    fn description(&self) -> &'static str {
+        "Bare URL used"
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        visit(ctx.ast, |node, _| {
+            match node.node_type {
+                NodeType::Link | NodeType::LinkReference | NodeType::Definition => {
+                    return Control::Skip
+                }
+                NodeType::Text => {}
+                _ => return Control::Continue,
+            }
+            let (Some(value), Some((start, end))) = (node.value.as_deref(), node_offsets(node))
+            else {
+                return Control::Continue;
+            };
+
+            let mut search_from = start;
+            for url in bare_urls(value) {
+                // Text values are unescaped, so locate the URL in the source span
+                let Some(found) = ctx.source[search_from..end].find(url) else {
+                    continue;
+                };
+                let url_start = search_from + found;
+                let url_end = url_start + url.len();
+                search_from = url_end;
+                findings.push(
+                    Finding::new(
+                        format!("Bare URL \"{}\"; wrap it in <> or a link", url),
+                        ctx.position(url_start, url_end),
+                    )
+                    .with_fix(TextEdit {
+                        start: url_start,
+                        end: url_end,
+                        text: format!("<{}>", url),
+                    }),
+                );
+            }
+            Control::Continue
+        });
+        findings
+    }
+}
+
+fn bare_urls(text: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+    let mut consumed = 0;
+    while let Some(found) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let start = consumed + found;
+        let tail = &text[start..];
+        let len = tail
+            .find(|c: char| c.is_whitespace() || c == '<' || c == '>')
+            .unwrap_or(tail.len());
+        let url = tail[..len].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"']);
+        if url.len() > "https://".len() {
+            urls.push(url);
+        }
+        consumed = start + len.max(1);
+        rest = &text[consumed..];
+    }
+    urls
+}
+
+/// Unordered lists should use one bullet character
+pub struct ListMarkerStyle;
+
+impl Rule for ListMarkerStyle {
+    fn id(&self) -> &'static str {
+        "list-marker-style"
+    }
+
+    fn description(&self) -> &'static str {
+        "Inconsistent unordered list marker"
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut expected = ctx.options.list_marker;
+        visit(ctx.ast, |node, walk_ctx| {
+            let in_bullet_list = walk_ctx
+                .parent()
+                .is_some_and(|p| p.node_type == NodeType::List && p.ordered != Some(true));
+            if node.node_type != NodeType::ListItem || !in_bullet_list {
+                return Control::Continue;
+            }
+            let Some((start, _)) = node_offsets(node) else {
+                return Control::Continue;
+            };
+            let Some(marker) = ctx.source[start..]
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '-' | '*' | '+'))
+            else {
+                return Control::Continue;
+            };
+
+            match expected {
+                None => expected = Some(marker),
+                Some(style) if style != marker => findings.push(
+                    Finding::new(
+                        format!(
+                            "Expected list marker \"{}\" but found \"{}\"",
+                            style, marker
+                        ),
+                        ctx.position(start, start + 1),
+                    )
+                    .with_fix(TextEdit {
+                        start,
+                        end: start + 1,
+                        text: style.to_string(),
+                    }),
+                ),
+                Some(_) => {}
+            }
+            Control::Continue
+        });
+        findings
+    }
+}
+
+/// Fenced code blocks should declare a language
+pub struct FencedCodeLanguage;
+
+impl Rule for FencedCodeLanguage {
+    fn id(&self) -> &'static str {
+        "fenced-code-language"
+    }
+
+    fn description(&self) -> &'static str {
+        "Fenced code block without a language"
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        visit(ctx.ast, |node, _| {
+            if node.node_type != NodeType::Code || node.lang.is_some() {
+                return Control::Continue;
+            }
+            if let Some((start, end)) = node_offsets(node) {
+                let fenced = ctx.source[start..end].trim_start().starts_with(['`', '~']);
+                if fenced {
+                    findings.push(Finding::new(
+                        "Fenced code block should specify a language",
+                        ctx.position(start, end),
+                    ));
+                }
+            }
+            Control::Continue
+        });
+        findings
+    }
+}
+
+/// Lines should not exceed `LintOptions::line_length`
+///
+/// Code blocks, tables, HTML and lines whose overflow has no whitespace
+/// (long URLs) are exempt.
+pub struct LineLength;
+
+impl Rule for LineLength {
+    fn id(&self) -> &'static str {
+        "line-length"
+    }
+
+    fn description(&self) -> &'static str {
+        "Line too long"
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Finding> {
+        let limit = ctx.options.line_length;
+        let exempt = ctx.lines_covered_by(&[NodeType::Code, NodeType::Table, NodeType::Html]);
+        let mut findings = Vec::new();
+
+        for (index, (start, line)) in ctx.lines().enumerate() {
+            let length = line.chars().count();
+            if length <= limit || in_ranges(&exempt, index + 1) {
+                continue;
+            }
+            let overflow_at = line
+                .char_indices()
+                .nth(limit)
+                .map_or(line.len(), |(i, _)| i);
+            if !line[overflow_at..].contains(char::is_whitespace) {
+                continue;
+            }
+            findings.push(Finding::new(
+                format!("Line length {} exceeds {}", length, limit),
+                ctx.position(start + overflow_at, start + line.len()),
+            ));
+        }
+        findings
+    }
+}
+
+fn in_ranges(ranges: &[(usize, usize)], line: usize) -> bool {
+    ranges
+        .iter()
+        .any(|&(start, end)| start <= line && line <= end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.rule_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let source = "# Title\n\n### Skipped\n\n## Title\n\nSee https://example.com. \n\n- a\n* b\n\n```\ncode  \n```\n";
+        let diagnostics = lint(source, &LintOptions::default());
+        assert_eq!(
+            ids(&diagnostics),
+            vec![
+                "heading-increment",
+                "no-duplicate-headings",
+                "no-bare-urls",
+                "no-trailing-spaces",
+                "list-marker-style",
+                "fenced-code-language",
+            ]
+        );
+        assert_eq!(diagnostics[0].position.start.line, 3);
+        assert_eq!(diagnostics[2].position.start.column, 5);
+        assert_eq!(
+            diagnostics[2].to_string(),
+            "7:5: warning: Bare URL \"https://example.com\"; wrap it in <> or a link [no-bare-urls]"
+        );
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let source = "# A\n\n### B\n\nhttps://a.example x \n\n* a\n- b\n";
+        let fixed = apply_fixes(source, &lint(source, &LintOptions::default()));
+        assert_eq!(fixed, "# A\n\n## B\n\n<https://a.example> x\n\n* a\n* b\n");
+        assert!(lint(&fixed, &LintOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_config_and_disable_comments() {
+        let source = "# A\n\n### B\n\nword word word word\n";
+        let options = LintOptions {
+            line_length: 10,
+            rules: BTreeMap::from([("heading-increment".to_string(), RuleLevel::Off)]),
+            ..Default::default()
+        };
+        let diagnostics = lint(source, &options);
+        assert_eq!(ids(&diagnostics), vec!["line-length"]);
+        assert_eq!(diagnostics[0].position.start.column, 11);
+
+        let disabled = format!("<!-- fmd-lint-disable line-length -->\n\n{}", source);
+        assert!(lint(&disabled, &options).is_empty());
+        let all = format!("<!-- fmd-lint-disable -->\n\n{}", source);
+        assert!(lint(&all, &LintOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_hard_break_spaces_allowed() {
+        let source = "line one  \nline two\n";
+        assert!(lint(source, &LintOptions::default()).is_empty());
+    }
+}
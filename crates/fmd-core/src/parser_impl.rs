@@ -27,6 +27,12 @@ pub fn parse_with_pulldown(doc: &Document, options: ProcessorOptions) -> Node {
     if options.math {
         pulldown_options.insert(Options::ENABLE_MATH);
     }
+    if options.gfm {
+        pulldown_options.insert(Options::ENABLE_FOOTNOTES);
+    }
+    if options.heading_attributes {
+        pulldown_options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+    }
 
     let parser = Parser::new_ext(&doc.content, pulldown_options);
     let mut builder = AstBuilder::new(options);
@@ -81,14 +87,14 @@ impl AstBuilder {
             Event::End(tag) => self.handle_end_tag(tag),
             Event::Text(text) => self.handle_text(text, position),
             Event::Code(code) => self.handle_inline_code(code, position),
-            Event::Html(html) => self.handle_html(html, position),
+            Event::Html(html) | Event::InlineHtml(html) => self.handle_html(html, position),
             Event::SoftBreak => self.handle_soft_break(position),
             Event::HardBreak => self.handle_hard_break(position),
             Event::Rule => self.handle_rule(position),
             Event::TaskListMarker(checked) => self.handle_task_list_marker(checked),
             Event::InlineMath(math) => self.handle_inline_math(math, position),
             Event::DisplayMath(math) => self.handle_display_math(math, position),
-            _ => {} // Ignore other events for now
+            Event::FootnoteReference(label) => self.handle_footnote_reference(label, position),
         }
     }
 
@@ -99,12 +105,40 @@ impl AstBuilder {
                 position,
                 ..Default::default()
             },
-            Tag::Heading { level, .. } => Node {
-                node_type: NodeType::Heading,
-                depth: Some(self.heading_level_to_depth(level)),
-                position,
-                ..Default::default()
-            },
+            Tag::Heading {
+                level,
+                id,
+                classes,
+                attrs,
+            } => {
+                let mut node = Node {
+                    node_type: NodeType::Heading,
+                    depth: Some(self.heading_level_to_depth(level)),
+                    position,
+                    ..Default::default()
+                };
+                if let Some(id) = id {
+                    node.data.insert("id".to_string(), id.to_string().into());
+                }
+                let mut properties = serde_json::Map::new();
+                if !classes.is_empty() {
+                    let classes: Vec<String> = classes.iter().map(|c| c.to_string()).collect();
+                    properties.insert("className".to_string(), classes.into());
+                }
+                // Only `data-*`: arbitrary keys would allow `onclick`, `style` and the like
+                let data_attrs = attrs.iter().filter(|(key, _)| is_data_attribute(key));
+                for (key, value) in data_attrs {
+                    let value = value
+                        .as_ref()
+                        .map_or(serde_json::Value::Bool(true), |v| v.to_string().into());
+                    properties.insert(key.to_string(), value);
+                }
+                if !properties.is_empty() {
+                    node.data
+                        .insert("hProperties".to_string(), properties.into());
+                }
+                node
+            }
             Tag::BlockQuote(_) => Node {
                 node_type: NodeType::Blockquote,
                 position,
@@ -228,11 +262,15 @@ impl AstBuilder {
                 position,
                 ..Default::default()
             },
-            Tag::TableHead => Node {
-                node_type: NodeType::TableRow,
-                position,
-                ..Default::default()
-            },
+            Tag::TableHead => {
+                let mut row = Node {
+                    node_type: NodeType::TableRow,
+                    position,
+                    ..Default::default()
+                };
+                row.data.insert("header".to_string(), true.into());
+                row
+            }
             Tag::TableRow => Node {
                 node_type: NodeType::TableRow,
                 position,
@@ -248,6 +286,12 @@ impl AstBuilder {
                 position,
                 ..Default::default()
             },
+            Tag::FootnoteDefinition(label) => Node {
+                node_type: NodeType::FootnoteDefinition,
+                identifier: Some(label.to_string()),
+                position,
+                ..Default::default()
+            },
             Tag::MetadataBlock(_) => Node {
                 node_type: NodeType::Yaml,
                 position,
@@ -263,29 +307,23 @@ impl AstBuilder {
         if let Some(mut node) = self.stack.pop() {
             // Handle special cases
             match tag {
-                TagEnd::CodeBlock => {
-                    // Combine collected content for code blocks
-                    if !self.content.is_empty() {
-                        node.value = Some(self.content.join(""));
-                        self.content.clear();
-                    }
+                // Combine collected content for code blocks
+                TagEnd::CodeBlock if !self.content.is_empty() => {
+                    node.value = Some(self.content.join(""));
+                    self.content.clear();
                 }
                 TagEnd::List(_) => {
                     self.list_stack.pop();
                 }
-                TagEnd::MetadataBlock(_) => {
-                    // Store frontmatter content
-                    if !self.content.is_empty() {
-                        node.value = Some(self.content.join(""));
-                        self.content.clear();
-                    }
+                // Store frontmatter content
+                TagEnd::MetadataBlock(_) if !self.content.is_empty() => {
+                    node.value = Some(self.content.join(""));
+                    self.content.clear();
                 }
-                TagEnd::HtmlBlock => {
-                    // Store HTML content
-                    if !self.content.is_empty() {
-                        node.value = Some(self.content.join(""));
-                        self.content.clear();
-                    }
+                // Store HTML content
+                TagEnd::HtmlBlock if !self.content.is_empty() => {
+                    node.value = Some(self.content.join(""));
+                    self.content.clear();
                 }
                 _ => {}
             }
@@ -311,18 +349,25 @@ impl AstBuilder {
             }
         }
 
-        // Otherwise create a text node
-        let node = Node {
-            node_type: NodeType::Text,
-            value: Some(text.to_string()),
-            position,
-            ..Default::default()
+        // Otherwise extend the preceding text node (pulldown splits text at
+        // escapes and soft breaks) or create a new one
+        let siblings = match self.stack.last_mut() {
+            Some(parent) => &mut parent.children,
+            None => &mut self.root.children,
         };
-
-        if let Some(parent) = self.stack.last_mut() {
-            parent.children.push(node);
-        } else {
-            self.root.children.push(node);
+        match siblings.last_mut() {
+            Some(last) if last.node_type == NodeType::Text => {
+                last.value.get_or_insert_with(String::new).push_str(&text);
+                if let (Some(last_pos), Some(pos)) = (last.position.as_mut(), position) {
+                    last_pos.end = pos.end;
+                }
+            }
+            _ => siblings.push(Node {
+                node_type: NodeType::Text,
+                value: Some(text.to_string()),
+                position,
+                ..Default::default()
+            }),
         }
     }
 
@@ -366,14 +411,22 @@ impl AstBuilder {
     }
 
     fn handle_soft_break(&mut self, position: Option<Position>) {
-        let node = Node {
-            node_type: NodeType::Break,
-            position,
-            ..Default::default()
-        };
-
+        // Soft line endings are plain text in mdast; only hard breaks are `Break` nodes
         if let Some(parent) = self.stack.last_mut() {
-            parent.children.push(node);
+            match parent.children.last_mut() {
+                Some(last) if last.node_type == NodeType::Text => {
+                    last.value.get_or_insert_with(String::new).push('\n');
+                    if let (Some(last_pos), Some(pos)) = (last.position.as_mut(), position) {
+                        last_pos.end = pos.end;
+                    }
+                }
+                _ => parent.children.push(Node {
+                    node_type: NodeType::Text,
+                    value: Some("\n".to_string()),
+                    position,
+                    ..Default::default()
+                }),
+            }
         }
     }
 
@@ -433,6 +486,21 @@ impl AstBuilder {
         self.root.children.push(node);
     }
 
+    fn handle_footnote_reference(&mut self, label: CowStr, position: Option<Position>) {
+        let node = Node {
+            node_type: NodeType::FootnoteReference,
+            identifier: Some(label.to_string()),
+            position,
+            ..Default::default()
+        };
+
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children.push(node);
+        } else {
+            self.root.children.push(node);
+        }
+    }
+
     fn heading_level_to_depth(&self, level: HeadingLevel) -> u8 {
         match level {
             HeadingLevel::H1 => 1,
@@ -446,7 +514,8 @@ impl AstBuilder {
 
     fn range_to_position(&self, range: std::ops::Range<usize>, source: &str) -> Position {
         let start_offset = range.start;
-        let end_offset = range.end;
+        // Block ranges include the trailing line ending; mdast positions end before it
+        let end_offset = start_offset + source[range.clone()].trim_end_matches(['\n', '\r']).len();
 
         // Calculate line and column for start
         let mut line = 1;
@@ -502,3 +571,13 @@ impl AstBuilder {
         self.root
     }
 }
+
+/// `data-` followed by a valid custom data attribute name
+fn is_data_attribute(key: &str) -> bool {
+    key.strip_prefix("data-").is_some_and(|name| {
+        !name.is_empty()
+            && name.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.')
+            })
+    })
+}
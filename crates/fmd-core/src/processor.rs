@@ -0,0 +1,343 @@
+// Transform pipeline: parse -> transformers -> (render)
+
+use crate::ast::Node;
+use crate::code_meta::CodeMetaParsing;
+use crate::position::Position;
+use crate::transforms::{FootnoteNumbering, HeadingIds};
+use crate::{parse, Document, ProcessorOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Severity of a diagnostic attached to a [`VFile`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Diagnostic reported while processing a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VFileMessage {
+    pub reason: String,
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    /// Name of the transformer (or `parse`) that reported the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+}
+
+impl fmt::Display for VFileMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(pos) = &self.position {
+            write!(f, "{}:{}: ", pos.start.line, pos.start.column)?;
+        }
+        let label = match self.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}", label, self.reason)?;
+        match (&self.source, &self.rule_id) {
+            (Some(source), Some(rule)) => write!(f, " [{}:{}]", source, rule),
+            (Some(source), None) => write!(f, " [{}]", source),
+            (None, Some(rule)) => write!(f, " [{}]", rule),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Virtual file: source text plus diagnostics and data shared between transformers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub value: String,
+    pub messages: Vec<VFileMessage>,
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub data: HashMap<String, Value>,
+}
+
+impl VFile {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Add a diagnostic and return it for further customisation
+    pub fn message(
+        &mut self,
+        severity: Severity,
+        reason: impl Into<String>,
+        position: Option<Position>,
+    ) -> &mut VFileMessage {
+        self.messages.push(VFileMessage {
+            reason: reason.into(),
+            severity,
+            position,
+            source: None,
+            rule_id: None,
+        });
+        self.messages.last_mut().unwrap()
+    }
+
+    pub fn info(&mut self, reason: impl Into<String>, position: Option<Position>) {
+        self.message(Severity::Info, reason, position);
+    }
+
+    pub fn warn(&mut self, reason: impl Into<String>, position: Option<Position>) {
+        self.message(Severity::Warning, reason, position);
+    }
+
+    pub fn error(&mut self, reason: impl Into<String>, position: Option<Position>) {
+        self.message(Severity::Error, reason, position);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.messages.iter().any(|m| m.severity == Severity::Error)
+    }
+}
+
+/// AST plugin run between parsing and rendering
+pub trait Transformer {
+    /// Unique name, used for ordering and as the source of diagnostics
+    fn name(&self) -> &str;
+
+    fn transform(&mut self, root: &mut Node, file: &mut VFile);
+}
+
+/// Parses a file and runs an ordered chain of transformers over the AST
+pub struct Processor {
+    options: ProcessorOptions,
+    transformers: Vec<Box<dyn Transformer>>,
+}
+
+impl Processor {
+    pub fn new(options: ProcessorOptions) -> Self {
+        Self {
+            options,
+            transformers: Vec::new(),
+        }
+    }
+
+    /// Processor with the built-in core transforms enabled by `options`
+    pub fn with_defaults(options: ProcessorOptions) -> Self {
+        let processor = Self::new(options)
+            .use_transformer(HeadingIds::new())
+            .use_transformer(CodeMetaParsing);
+        if options.gfm {
+            processor.use_transformer(FootnoteNumbering)
+        } else {
+            processor
+        }
+    }
+
+    pub fn options(&self) -> ProcessorOptions {
+        self.options
+    }
+
+    /// Append a transformer (builder style)
+    pub fn use_transformer<T: Transformer + 'static>(mut self, transformer: T) -> Self {
+        self.push(Box::new(transformer));
+        self
+    }
+
+    /// Append a transformer, replacing any existing one with the same name in place
+    pub fn push(&mut self, transformer: Box<dyn Transformer>) {
+        match self.index_of(transformer.name()) {
+            Some(index) => self.transformers[index] = transformer,
+            None => self.transformers.push(transformer),
+        }
+    }
+
+    /// Insert a transformer before the one named `anchor`; returns false if absent
+    pub fn insert_before(&mut self, anchor: &str, transformer: Box<dyn Transformer>) -> bool {
+        self.insert_at(anchor, 0, transformer)
+    }
+
+    /// Insert a transformer after the one named `anchor`; returns false if absent
+    pub fn insert_after(&mut self, anchor: &str, transformer: Box<dyn Transformer>) -> bool {
+        self.insert_at(anchor, 1, transformer)
+    }
+
+    fn insert_at(
+        &mut self,
+        anchor: &str,
+        offset: usize,
+        transformer: Box<dyn Transformer>,
+    ) -> bool {
+        let Some(index) = self.index_of(anchor) else {
+            return false;
+        };
+        if transformer.name() == anchor {
+            self.transformers[index] = transformer;
+            return true;
+        }
+        // Removing the old entry may shift the anchor
+        self.remove(transformer.name());
+        let index = self.index_of(anchor).unwrap_or(index);
+        self.transformers.insert(index + offset, transformer);
+        true
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn Transformer>> {
+        let index = self.index_of(name)?;
+        Some(self.transformers.remove(index))
+    }
+
+    /// Names of the registered transformers in run order
+    pub fn names(&self) -> Vec<&str> {
+        self.transformers.iter().map(|t| t.name()).collect()
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.transformers.iter().position(|t| t.name() == name)
+    }
+
+    /// Parse the file contents, recording parse errors and warnings on the file
+    pub fn parse(&self, file: &mut VFile) -> Node {
+        let doc = Document {
+            content: file.value.clone(),
+            source: file.path.clone(),
+        };
+        let result = parse(&doc, self.options);
+
+        for error in result.errors {
+            file.message(Severity::Error, error, None).source = Some("parse".to_string());
+        }
+        for warning in result.warnings {
+            file.message(Severity::Warning, warning, None).source = Some("parse".to_string());
+        }
+        if let Some(frontmatter) = result.frontmatter {
+            file.data.insert("frontmatter".to_string(), frontmatter);
+        }
+
+        result.ast
+    }
+
+    /// Run every transformer over `root` in order
+    pub fn run(&mut self, root: &mut Node, file: &mut VFile) {
+        for transformer in &mut self.transformers {
+            let first_new = file.messages.len();
+            transformer.transform(root, file);
+
+            let name = transformer.name();
+            for message in &mut file.messages[first_new..] {
+                if message.source.is_none() {
+                    message.source = Some(name.to_string());
+                }
+            }
+        }
+    }
+
+    /// Parse and transform
+    pub fn process(&mut self, file: &mut VFile) -> Node {
+        let mut root = self.parse(file);
+        if !file.has_errors() {
+            self.run(&mut root, file);
+        }
+        root
+    }
+}
+
+impl fmt::Debug for Processor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Processor")
+            .field("options", &self.options)
+            .field("transformers", &self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NodeType;
+
+    struct Uppercase;
+
+    impl Transformer for Uppercase {
+        fn name(&self) -> &str {
+            "uppercase"
+        }
+
+        fn transform(&mut self, root: &mut Node, file: &mut VFile) {
+            fn walk(node: &mut Node) {
+                if node.node_type == NodeType::Text {
+                    node.value = node.value.as_ref().map(|v| v.to_uppercase());
+                }
+                node.children.iter_mut().for_each(walk);
+            }
+            walk(root);
+            file.info("uppercased", None);
+        }
+    }
+
+    struct Tag(&'static str);
+
+    impl Transformer for Tag {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn transform(&mut self, root: &mut Node, _file: &mut VFile) {
+            let mut order = root
+                .data
+                .get("order")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            order.push_str(self.0);
+            root.data.insert("order".to_string(), Value::from(order));
+        }
+    }
+
+    #[test]
+    fn test_process_runs_transformers() {
+        let mut processor = Processor::new(ProcessorOptions::default()).use_transformer(Uppercase);
+        let mut file = VFile::new("hello");
+        let root = processor.process(&mut file);
+
+        assert_eq!(root.text_content(), "HELLO");
+        assert_eq!(file.messages.len(), 1);
+        assert_eq!(file.messages[0].source.as_deref(), Some("uppercase"));
+    }
+
+    #[test]
+    fn test_transformer_ordering() {
+        let mut processor = Processor::new(ProcessorOptions::default())
+            .use_transformer(Tag("a"))
+            .use_transformer(Tag("c"));
+        assert!(processor.insert_before("c", Box::new(Tag("b"))));
+        assert!(processor.insert_after("c", Box::new(Tag("d"))));
+        assert!(!processor.insert_after("missing", Box::new(Tag("e"))));
+        assert_eq!(processor.names(), vec!["a", "b", "c", "d"]);
+
+        // A missing anchor leaves an existing entry of the same name alone
+        assert!(!processor.insert_before("missing", Box::new(Tag("b"))));
+        assert_eq!(processor.names(), vec!["a", "b", "c", "d"]);
+        // Moving an entry, and replacing the anchor itself
+        assert!(processor.insert_after("d", Box::new(Tag("b"))));
+        assert!(processor.insert_before("c", Box::new(Tag("c"))));
+        assert_eq!(processor.names(), vec!["a", "c", "d", "b"]);
+
+        processor.remove("a");
+        let mut file = VFile::new("text");
+        let root = processor.process(&mut file);
+        assert_eq!(root.data["order"], "cdb");
+    }
+}
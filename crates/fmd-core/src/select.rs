@@ -0,0 +1,606 @@
+// CSS-like selector queries over the AST (modelled on unist-util-select)
+//
+// Supported syntax:
+//   type selectors      heading, listItem, *
+//   attributes          [lang], [depth=2], [url^="https:"], [url$=.md], [url*=x], [meta~=title]
+//   combinators         a b, a > b, a + b, a ~ b
+//   pseudo-classes      :first-child, :last-child, :only-child, :empty, :root, :not(sel)
+//   selector lists      a, b
+
+use crate::ast::{Node, NodeType};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// Error produced when a selector cannot be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorError {
+    pub message: String,
+    /// Byte offset into the selector where parsing failed
+    pub offset: usize,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid selector at offset {}: {}",
+            self.offset, self.message
+        )
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+/// A node matched by a selector together with its child-index path from the root
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectMatch<'a> {
+    pub path: Vec<usize>,
+    pub node: &'a Node,
+}
+
+/// Parsed selector list, reusable across trees
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    alternatives: Vec<Complex>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Complex {
+    /// Compounds left to right; `combinators[i]` sits between `compounds[i]` and `compounds[i + 1]`
+    compounds: Vec<Compound>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+    Adjacent,
+    Sibling,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Compound {
+    node_type: Option<NodeType>,
+    attributes: Vec<Attribute>,
+    pseudos: Vec<Pseudo>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Attribute {
+    name: String,
+    op: Option<(AttrOp, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttrOp {
+    Equals,
+    Prefix,
+    Suffix,
+    Contains,
+    Word,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pseudo {
+    FirstChild,
+    LastChild,
+    OnlyChild,
+    Empty,
+    Root,
+    Not(Selector),
+}
+
+/// Position of a candidate node: its ancestors (root first) and index path
+#[derive(Clone, Copy)]
+struct Scope<'s, 'a> {
+    ancestors: &'s [&'a Node],
+    path: &'s [usize],
+}
+
+impl<'s, 'a> Scope<'s, 'a> {
+    fn parent(&self) -> Option<(&'a Node, Scope<'s, 'a>)> {
+        let (&parent, ancestors) = self.ancestors.split_last()?;
+        let path = &self.path[..self.path.len() - 1];
+        Some((parent, Scope { ancestors, path }))
+    }
+
+    fn index(&self) -> Option<usize> {
+        self.path.last().copied()
+    }
+
+    fn siblings(&self) -> &'a [Node] {
+        self.ancestors
+            .last()
+            .map(|p| p.children.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+impl Selector {
+    pub fn parse(input: &str) -> Result<Self, SelectorError> {
+        let mut parser = SelectorParser { input, pos: 0 };
+        let selector = parser.parse_list()?;
+        parser.skip_ws();
+        if parser.pos < input.len() {
+            return Err(parser.error("Unexpected character"));
+        }
+        Ok(selector)
+    }
+
+    /// First matching node in document order
+    pub fn select<'a>(&self, root: &'a Node) -> Option<SelectMatch<'a>> {
+        let mut matches = Vec::new();
+        self.collect(root, &mut Vec::new(), &mut Vec::new(), &mut matches, true);
+        matches.pop()
+    }
+
+    /// All matching nodes in document order
+    pub fn select_all<'a>(&self, root: &'a Node) -> Vec<SelectMatch<'a>> {
+        let mut matches = Vec::new();
+        self.collect(root, &mut Vec::new(), &mut Vec::new(), &mut matches, false);
+        matches
+    }
+
+    /// Whether the node at `path` in `root` matches
+    pub fn matches_at(&self, root: &Node, path: &[usize]) -> bool {
+        let mut ancestors = Vec::with_capacity(path.len());
+        let mut node = root;
+        for &index in path {
+            ancestors.push(node);
+            match node.children.get(index) {
+                Some(child) => node = child,
+                None => return false,
+            }
+        }
+        self.matches(
+            node,
+            Scope {
+                ancestors: &ancestors,
+                path,
+            },
+        )
+    }
+
+    fn collect<'a>(
+        &self,
+        node: &'a Node,
+        ancestors: &mut Vec<&'a Node>,
+        path: &mut Vec<usize>,
+        matches: &mut Vec<SelectMatch<'a>>,
+        first_only: bool,
+    ) -> bool {
+        let scope = Scope { ancestors, path };
+        if self.matches(node, scope) {
+            matches.push(SelectMatch {
+                path: path.clone(),
+                node,
+            });
+            if first_only {
+                return true;
+            }
+        }
+
+        ancestors.push(node);
+        for (index, child) in node.children.iter().enumerate() {
+            path.push(index);
+            let done = self.collect(child, ancestors, path, matches, first_only);
+            path.pop();
+            if done {
+                ancestors.pop();
+                return true;
+            }
+        }
+        ancestors.pop();
+        false
+    }
+
+    fn matches(&self, node: &Node, scope: Scope) -> bool {
+        self.alternatives
+            .iter()
+            .any(|complex| complex.matches(complex.compounds.len() - 1, node, scope))
+    }
+}
+
+impl Complex {
+    fn matches(&self, k: usize, node: &Node, scope: Scope) -> bool {
+        if !self.compounds[k].matches(node, scope) {
+            return false;
+        }
+        if k == 0 {
+            return true;
+        }
+
+        match self.combinators[k - 1] {
+            Combinator::Child => scope
+                .parent()
+                .is_some_and(|(parent, outer)| self.matches(k - 1, parent, outer)),
+            Combinator::Descendant => {
+                let mut current = scope;
+                while let Some((ancestor, outer)) = current.parent() {
+                    if self.matches(k - 1, ancestor, outer) {
+                        return true;
+                    }
+                    current = outer;
+                }
+                false
+            }
+            Combinator::Adjacent => match scope.index() {
+                Some(index) if index > 0 => {
+                    let path = [&scope.path[..scope.path.len() - 1], &[index - 1][..]].concat();
+                    let sibling_scope = Scope {
+                        ancestors: scope.ancestors,
+                        path: &path,
+                    };
+                    self.matches(k - 1, &scope.siblings()[index - 1], sibling_scope)
+                }
+                _ => false,
+            },
+            Combinator::Sibling => {
+                let index = scope.index().unwrap_or(0);
+                let siblings = scope.siblings();
+                (0..index).any(|i| {
+                    let path = [&scope.path[..scope.path.len() - 1], &[i][..]].concat();
+                    let sibling_scope = Scope {
+                        ancestors: scope.ancestors,
+                        path: &path,
+                    };
+                    self.matches(k - 1, &siblings[i], sibling_scope)
+                })
+            }
+        }
+    }
+}
+
+impl Compound {
+    fn matches(&self, node: &Node, scope: Scope) -> bool {
+        if let Some(node_type) = &self.node_type {
+            if node.node_type != *node_type {
+                return false;
+            }
+        }
+        self.attributes.iter().all(|attr| attr.matches(node))
+            && self.pseudos.iter().all(|pseudo| match pseudo {
+                Pseudo::FirstChild => scope.index() == Some(0),
+                Pseudo::LastChild => scope
+                    .index()
+                    .is_some_and(|i| i + 1 == scope.siblings().len()),
+                Pseudo::OnlyChild => scope.index().is_some() && scope.siblings().len() == 1,
+                Pseudo::Empty => node.children.is_empty() && node.value.is_none(),
+                Pseudo::Root => scope.ancestors.is_empty(),
+                Pseudo::Not(inner) => !inner.matches(node, scope),
+            })
+    }
+}
+
+impl Attribute {
+    fn matches(&self, node: &Node) -> bool {
+        let Some(actual) = attribute_value(node, &self.name) else {
+            return false;
+        };
+        match &self.op {
+            None => true,
+            Some((AttrOp::Equals, expected)) => actual == *expected,
+            Some((AttrOp::Prefix, expected)) => actual.starts_with(expected.as_str()),
+            Some((AttrOp::Suffix, expected)) => actual.ends_with(expected.as_str()),
+            Some((AttrOp::Contains, expected)) => actual.contains(expected.as_str()),
+            Some((AttrOp::Word, expected)) => actual.split_whitespace().any(|w| w == expected),
+        }
+    }
+}
+
+/// Stringified value of a node field, falling back to `data` (`data.key` or `key`)
+fn attribute_value(node: &Node, name: &str) -> Option<String> {
+    match name {
+        "type" => Some(node.node_type.as_str().to_string()),
+        "value" => node.value.clone(),
+        "depth" => node.depth.map(|v| v.to_string()),
+        "ordered" => node.ordered.map(|v| v.to_string()),
+        "checked" => node.checked.map(|v| v.to_string()),
+        "lang" => node.lang.clone(),
+        "meta" => node.meta.clone(),
+        "url" => node.url.clone(),
+        "title" => node.title.clone(),
+        "alt" => node.alt.clone(),
+        "identifier" => node.identifier.clone(),
+        "start" => node.start.map(|v| v.to_string()),
+        "align" => node.align.as_ref().map(|a| a.join(" ")),
+        _ => {
+            let key = name.strip_prefix("data.").unwrap_or(name);
+            node.data.get(key).map(|v| match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+        }
+    }
+}
+
+struct SelectorParser<'i> {
+    input: &'i str,
+    pos: usize,
+}
+
+impl<'i> SelectorParser<'i> {
+    fn error(&self, message: impl Into<String>) -> SelectorError {
+        SelectorError {
+            message: message.into(),
+            offset: self.pos,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_ws(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+        self.pos > start
+    }
+
+    fn ident(&mut self) -> Option<&'i str> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            self.bump();
+        }
+        (self.pos > start).then(|| &self.input[start..self.pos])
+    }
+
+    fn parse_list(&mut self) -> Result<Selector, SelectorError> {
+        let mut alternatives = vec![self.parse_complex()?];
+        loop {
+            self.skip_ws();
+            if !self.eat(',') {
+                break;
+            }
+            alternatives.push(self.parse_complex()?);
+        }
+        Ok(Selector { alternatives })
+    }
+
+    fn parse_complex(&mut self) -> Result<Complex, SelectorError> {
+        self.skip_ws();
+        let mut compounds = vec![self.parse_compound()?];
+        let mut combinators = Vec::new();
+
+        loop {
+            let had_ws = self.skip_ws();
+            let combinator = match self.peek() {
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::Adjacent,
+                Some('~') => Combinator::Sibling,
+                Some(',') | Some(')') | None => break,
+                Some(_) if had_ws => Combinator::Descendant,
+                Some(_) => return Err(self.error("Expected combinator")),
+            };
+            if combinator != Combinator::Descendant {
+                self.bump();
+                self.skip_ws();
+            }
+            combinators.push(combinator);
+            compounds.push(self.parse_compound()?);
+        }
+
+        Ok(Complex {
+            compounds,
+            combinators,
+        })
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound, SelectorError> {
+        let start = self.pos;
+        let mut compound = Compound::default();
+
+        if self.eat('*') {
+            // Universal selector: no type constraint
+        } else if let Some(name) = self.ident() {
+            compound.node_type = Some(NodeType::from_name(name).ok_or_else(|| SelectorError {
+                message: format!("Unknown node type `{}`", name),
+                offset: start,
+            })?);
+        }
+
+        loop {
+            match self.peek() {
+                Some('[') => {
+                    self.bump();
+                    compound.attributes.push(self.parse_attribute()?);
+                }
+                Some(':') => {
+                    self.bump();
+                    compound.pseudos.push(self.parse_pseudo()?);
+                }
+                _ => break,
+            }
+        }
+
+        if self.pos == start {
+            return Err(self.error("Expected selector"));
+        }
+        Ok(compound)
+    }
+
+    fn parse_attribute(&mut self) -> Result<Attribute, SelectorError> {
+        self.skip_ws();
+        let name = self
+            .ident()
+            .ok_or_else(|| self.error("Expected attribute name"))?
+            .to_string();
+        self.skip_ws();
+
+        let op = match self.peek() {
+            Some(']') => None,
+            Some(c) => {
+                let op = match c {
+                    '=' => AttrOp::Equals,
+                    '^' => AttrOp::Prefix,
+                    '$' => AttrOp::Suffix,
+                    '*' => AttrOp::Contains,
+                    '~' => AttrOp::Word,
+                    _ => return Err(self.error("Expected attribute operator")),
+                };
+                self.bump();
+                if op != AttrOp::Equals && !self.eat('=') {
+                    return Err(self.error("Expected `=`"));
+                }
+                self.skip_ws();
+                let value = self.parse_value()?;
+                self.skip_ws();
+                Some((op, value))
+            }
+            None => return Err(self.error("Unterminated attribute")),
+        };
+
+        if !self.eat(']') {
+            return Err(self.error("Expected `]`"));
+        }
+        Ok(Attribute { name, op })
+    }
+
+    fn parse_value(&mut self) -> Result<String, SelectorError> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.bump();
+                let mut value = String::new();
+                loop {
+                    match self.bump() {
+                        Some('\\') => value.extend(self.bump()),
+                        Some(c) if c == quote => return Ok(value),
+                        Some(c) => value.push(c),
+                        None => return Err(self.error("Unterminated string")),
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| !c.is_whitespace() && c != ']') {
+                    self.bump();
+                }
+                if self.pos == start {
+                    return Err(self.error("Expected attribute value"));
+                }
+                Ok(self.input[start..self.pos].to_string())
+            }
+        }
+    }
+
+    fn parse_pseudo(&mut self) -> Result<Pseudo, SelectorError> {
+        let start = self.pos;
+        let name = self
+            .ident()
+            .ok_or_else(|| self.error("Expected pseudo-class name"))?;
+        let pseudo = match name {
+            "first-child" => Pseudo::FirstChild,
+            "last-child" => Pseudo::LastChild,
+            "only-child" => Pseudo::OnlyChild,
+            "empty" => Pseudo::Empty,
+            "root" => Pseudo::Root,
+            "not" => {
+                if !self.eat('(') {
+                    return Err(self.error("Expected `(` after :not"));
+                }
+                let inner = self.parse_list()?;
+                self.skip_ws();
+                if !self.eat(')') {
+                    return Err(self.error("Expected `)`"));
+                }
+                Pseudo::Not(inner)
+            }
+            other => {
+                return Err(SelectorError {
+                    message: format!("Unknown pseudo-class `:{}`", other),
+                    offset: start,
+                })
+            }
+        };
+        Ok(pseudo)
+    }
+}
+
+/// First node in `root` matching `selector`
+pub fn select<'a>(
+    root: &'a Node,
+    selector: &str,
+) -> Result<Option<SelectMatch<'a>>, SelectorError> {
+    Ok(Selector::parse(selector)?.select(root))
+}
+
+/// All nodes in `root` matching `selector`, in document order
+pub fn select_all<'a>(
+    root: &'a Node,
+    selector: &str,
+) -> Result<Vec<SelectMatch<'a>>, SelectorError> {
+    Ok(Selector::parse(selector)?.select_all(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Document, ProcessorOptions};
+
+    fn ast(input: &str) -> Node {
+        parse(&Document::new(input), ProcessorOptions::default()).ast
+    }
+
+    const DOC: &str = "# Title\n\n## Install\n\nRun `cargo`.\n\n## Usage\n\n```rust\nfn main() {}\n```\n\n```\nplain\n```\n\n[docs](https://example.com/docs.md)\n";
+
+    #[test]
+    fn test_type_and_attribute() {
+        let root = ast(DOC);
+        let found = select_all(&root, "heading[depth=2] > text").unwrap();
+        let texts: Vec<_> = found
+            .iter()
+            .filter_map(|m| m.node.value.as_deref())
+            .collect();
+        assert_eq!(texts, vec!["Install", "Usage"]);
+        assert_eq!(found[0].path, vec![1, 0]);
+
+        assert_eq!(select_all(&root, "code[lang]").unwrap().len(), 1);
+        assert_eq!(select_all(&root, "code:not([lang])").unwrap().len(), 1);
+        assert_eq!(select_all(&root, "link[url$=\".md\"]").unwrap().len(), 1);
+        assert_eq!(select_all(&root, "link[url^=https]").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_combinators_and_pseudos() {
+        let root = ast(DOC);
+        let first = select(&root, "heading:first-child").unwrap().unwrap();
+        assert_eq!(first.node.depth, Some(1));
+        assert_eq!(first.path, vec![0]);
+
+        assert_eq!(select_all(&root, "heading + paragraph").unwrap().len(), 1);
+        assert_eq!(select_all(&root, "heading ~ code").unwrap().len(), 2);
+        assert_eq!(select_all(&root, "root inlineCode").unwrap().len(), 1);
+        assert_eq!(select_all(&root, "heading, code").unwrap().len(), 5);
+        assert!(select(&root, ":root").unwrap().unwrap().path.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_selectors() {
+        assert!(Selector::parse("heading[").is_err());
+        assert!(Selector::parse("nope").is_err());
+        assert!(Selector::parse("heading:hover").is_err());
+        assert_eq!(Selector::parse("heading >").unwrap_err().offset, 9);
+    }
+}
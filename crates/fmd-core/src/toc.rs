@@ -0,0 +1,263 @@
+// Table of contents built from headings
+
+use crate::ast::{Node, NodeType};
+use crate::visit::visit_by_type;
+use fmd_slug::{GithubSlugger, Slugger};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TocOptions {
+    /// Shallowest heading depth to include
+    pub min_depth: u8,
+    /// Deepest heading depth to include
+    pub max_depth: u8,
+    /// Render the TOC as an ordered list
+    pub ordered: bool,
+    /// Prepended to ids in the map's links, matching a rendered id prefix like `user-content-`
+    pub prefix: String,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            min_depth: 1,
+            max_depth: 6,
+            ordered: false,
+            prefix: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TocEntry {
+    pub depth: u8,
+    pub text: String,
+    /// Heading id the entry links to
+    pub id: String,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub children: Vec<TocEntry>,
+}
+
+/// A nested table of contents
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Toc {
+    pub entries: Vec<TocEntry>,
+    /// The entries as a nested list of links, `None` when there are no headings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map: Option<Node>,
+}
+
+/// Build a table of contents from the headings in `root`
+///
+/// Entries link to the heading's `data.id` (set by the heading-ids
+/// transform) or to a [`GithubSlugger`] slug. A heading deeper than its
+/// predecessor nests under it.
+pub fn toc(root: &Node, options: &TocOptions) -> Toc {
+    let mut headings = Vec::new();
+    let mut slugger = GithubSlugger::new();
+    visit_by_type(root, NodeType::Heading, |node, _| {
+        let text = node.text_content();
+        let id = match node.data.get("id").and_then(Value::as_str) {
+            Some(id) => id.to_string(),
+            None => slugger.slug(&text),
+        };
+        let depth = node.depth.unwrap_or(1);
+        if (options.min_depth..=options.max_depth).contains(&depth) {
+            headings.push(TocEntry {
+                depth,
+                text,
+                id,
+                children: Vec::new(),
+            });
+        }
+    });
+
+    let entries = nest(headings);
+    let map = (!entries.is_empty()).then(|| list(&entries, options));
+    Toc { entries, map }
+}
+
+fn nest(headings: Vec<TocEntry>) -> Vec<TocEntry> {
+    let mut roots: Vec<TocEntry> = Vec::new();
+    // Depths of the entries currently open for nesting, outermost first
+    let mut open: Vec<u8> = Vec::new();
+    for heading in headings {
+        while open.last().is_some_and(|&depth| depth >= heading.depth) {
+            open.pop();
+        }
+        let mut siblings = &mut roots;
+        for _ in 0..open.len() {
+            siblings = &mut siblings.last_mut().expect("open entry").children;
+        }
+        open.push(heading.depth);
+        siblings.push(heading);
+    }
+    roots
+}
+
+fn list(entries: &[TocEntry], options: &TocOptions) -> Node {
+    let items = entries
+        .iter()
+        .map(|entry| {
+            let link = Node {
+                node_type: NodeType::Link,
+                url: Some(format!("#{}{}", options.prefix, entry.id)),
+                children: vec![Node {
+                    node_type: NodeType::Text,
+                    value: Some(entry.text.clone()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            let mut children = vec![link];
+            if !entry.children.is_empty() {
+                children.push(list(&entry.children, options));
+            }
+            Node {
+                node_type: NodeType::ListItem,
+                children,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Node {
+        node_type: NodeType::List,
+        ordered: Some(options.ordered),
+        children: items,
+        ..Default::default()
+    }
+}
+
+/// Whether a block is a `[[toc]]` or `<!-- toc -->` placeholder
+pub fn is_toc_marker(node: &Node) -> bool {
+    match node.node_type {
+        NodeType::Paragraph => node.text_content().trim().eq_ignore_ascii_case("[[toc]]"),
+        NodeType::Html => node.value.as_deref().is_some_and(|value| {
+            value
+                .trim()
+                .strip_prefix("<!--")
+                .and_then(|rest| rest.strip_suffix("-->"))
+                .is_some_and(|inner| inner.trim().eq_ignore_ascii_case("toc"))
+        }),
+        _ => false,
+    }
+}
+
+/// Replace every TOC marker with the table of contents; returns whether any was found
+///
+/// The list gets `data.hProperties.className = ["toc"]` for styling. Markers
+/// in a document without headings are removed.
+pub fn insert_toc(root: &mut Node, options: &TocOptions) -> bool {
+    let map = toc(root, options).map.map(|mut list| {
+        list.data.insert(
+            "hProperties".to_string(),
+            serde_json::json!({ "className": ["toc"] }),
+        );
+        list
+    });
+
+    replace_markers(root, map.as_ref())
+}
+
+fn replace_markers(node: &mut Node, map: Option<&Node>) -> bool {
+    let mut found = false;
+    for mut child in std::mem::take(&mut node.children) {
+        if is_toc_marker(&child) {
+            found = true;
+            node.children.extend(map.cloned());
+        } else {
+            found |= replace_markers(&mut child, map);
+            node.children.push(child);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Document, ProcessorOptions};
+
+    const DOC: &str = "# Guide\n\n[[toc]]\n\n## Install\n\n### From source\n\n## Usage\n\n#### Deep\n\n## Usage\n";
+
+    fn parse_str(input: &str) -> Node {
+        parse(&Document::new(input), ProcessorOptions::default()).ast
+    }
+
+    fn outline(entries: &[TocEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .flat_map(|e| {
+                std::iter::once(format!("{}:{}", e.depth, e.id))
+                    .chain(outline(&e.children).into_iter().map(|c| format!("  {}", c)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nesting_and_slugs() {
+        let toc = toc(&parse_str(DOC), &TocOptions::default());
+        assert_eq!(
+            outline(&toc.entries),
+            vec![
+                "1:guide",
+                "  2:install",
+                "    3:from-source",
+                "  2:usage",
+                "    4:deep",
+                "  2:usage-1",
+            ]
+        );
+
+        let json = serde_json::to_value(&toc).unwrap();
+        assert_eq!(json["entries"][0]["children"][1]["text"], "Usage");
+        assert_eq!(json["map"]["type"], "list");
+    }
+
+    #[test]
+    fn test_depth_range() {
+        let options = TocOptions {
+            min_depth: 2,
+            max_depth: 3,
+            ..Default::default()
+        };
+        let toc = toc(&parse_str(DOC), &options);
+        assert_eq!(
+            outline(&toc.entries),
+            vec!["2:install", "  3:from-source", "2:usage", "2:usage-1"]
+        );
+
+        let map = toc.map.unwrap();
+        let first = &map.children[0];
+        assert_eq!(first.children[0].url.as_deref(), Some("#install"));
+
+        let prefixed = TocOptions {
+            prefix: "user-content-".to_string(),
+            ..Default::default()
+        };
+        let map = super::toc(&parse_str(DOC), &prefixed).map.unwrap();
+        assert_eq!(
+            map.children[0].children[0].url.as_deref(),
+            Some("#user-content-guide")
+        );
+        assert_eq!(first.children[1].node_type, NodeType::List);
+    }
+
+    #[test]
+    fn test_insert_toc() {
+        let mut root = parse_str(DOC);
+        assert!(insert_toc(&mut root, &TocOptions::default()));
+        assert_eq!(root.children[1].node_type, NodeType::List);
+        assert!(root.children.iter().all(|c| !is_toc_marker(c)));
+
+        let mut root = parse_str("Intro\n\n<!-- TOC -->\n");
+        assert!(insert_toc(&mut root, &TocOptions::default()));
+        assert_eq!(root.children.len(), 1);
+
+        let mut root = parse_str("# Title\n");
+        assert!(!insert_toc(&mut root, &TocOptions::default()));
+    }
+}
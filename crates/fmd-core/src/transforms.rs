@@ -0,0 +1,174 @@
+// Built-in AST transforms run by the processor
+
+use crate::ast::{Node, NodeType};
+use crate::processor::{Transformer, VFile};
+use crate::visit::{visit_by_type, visit_by_type_mut};
+use fmd_slug::{GithubSlugger, Slugger};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Assigns a unique `data.id` slug to every heading that does not already have one
+///
+/// Slugs are GitHub-style unless another [`Slugger`] is given, e.g. from
+/// `fmd_slug::SlugOptions::slugger`.
+pub struct HeadingIds {
+    slugger: Box<dyn Slugger>,
+}
+
+impl Default for HeadingIds {
+    fn default() -> Self {
+        Self::with_slugger(Box::new(GithubSlugger::new()))
+    }
+}
+
+impl fmt::Debug for HeadingIds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeadingIds").finish_non_exhaustive()
+    }
+}
+
+impl HeadingIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_slugger(slugger: Box<dyn Slugger>) -> Self {
+        Self { slugger }
+    }
+}
+
+/// Set `data.id` on every heading; existing ids are kept but made unique
+pub fn assign_heading_ids(root: &mut Node, slugger: &mut dyn Slugger) {
+    visit_by_type_mut(root, NodeType::Heading, |node, _| {
+        let id = match node.data.get("id").and_then(Value::as_str) {
+            Some(id) => slugger.unique(id),
+            None => slugger.slug(&node.text_content()),
+        };
+        node.data.insert("id".to_string(), Value::from(id));
+    });
+}
+
+impl Transformer for HeadingIds {
+    fn name(&self) -> &str {
+        "heading-ids"
+    }
+
+    fn transform(&mut self, root: &mut Node, _file: &mut VFile) {
+        self.slugger.reset();
+        assign_heading_ids(root, self.slugger.as_mut());
+    }
+}
+
+/// Numbers footnotes in order of first reference and reports dangling ones
+///
+/// Sets `data.number` on references and definitions, plus `data.referenceIndex`
+/// (1-based) on each reference so renderers can build unique back-links.
+#[derive(Debug, Default)]
+pub struct FootnoteNumbering;
+
+fn footnote_key(node: &Node) -> Option<String> {
+    node.identifier.as_ref().map(|id| id.to_lowercase())
+}
+
+impl Transformer for FootnoteNumbering {
+    fn name(&self) -> &str {
+        "footnote-numbering"
+    }
+
+    fn transform(&mut self, root: &mut Node, file: &mut VFile) {
+        let mut defined = HashSet::new();
+        visit_by_type(root, NodeType::FootnoteDefinition, |node, _| {
+            defined.extend(footnote_key(node));
+        });
+
+        // Number in order of first reference
+        let mut numbers: HashMap<String, (usize, usize)> = HashMap::new();
+        visit_by_type_mut(root, NodeType::FootnoteReference, |node, _| {
+            let Some(key) = footnote_key(node) else {
+                return;
+            };
+            if !defined.contains(&key) {
+                file.warn(
+                    format!("Footnote `{}` is referenced but never defined", key),
+                    node.position.clone(),
+                );
+                return;
+            }
+            let next = numbers.len() + 1;
+            let entry = numbers.entry(key).or_insert((next, 0));
+            entry.1 += 1;
+            node.data.insert("number".to_string(), Value::from(entry.0));
+            node.data
+                .insert("referenceIndex".to_string(), Value::from(entry.1));
+        });
+
+        visit_by_type_mut(root, NodeType::FootnoteDefinition, |node, _| {
+            let Some(key) = footnote_key(node) else {
+                return;
+            };
+            match numbers.get(&key) {
+                Some(&(number, count)) => {
+                    node.data.insert("number".to_string(), Value::from(number));
+                    node.data
+                        .insert("referenceCount".to_string(), Value::from(count));
+                }
+                None => file.warn(
+                    format!("Footnote `{}` is defined but never referenced", key),
+                    node.position.clone(),
+                ),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::Processor;
+    use crate::ProcessorOptions;
+
+    fn process(input: &str) -> (Node, VFile) {
+        let options = ProcessorOptions {
+            gfm: true,
+            ..Default::default()
+        };
+        let mut file = VFile::new(input);
+        let root = Processor::with_defaults(options).process(&mut file);
+        (root, file)
+    }
+
+    #[test]
+    fn test_heading_ids_are_unique() {
+        let (root, _) = process("# Usage\n\n## Usage\n\n## Other\n\n## C++ & Rust!");
+        let ids: Vec<_> = root
+            .children
+            .iter()
+            .map(|h| h.data["id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(ids, vec!["usage", "usage-1", "other", "c--rust"]);
+    }
+
+    #[test]
+    fn test_footnote_numbering() {
+        let (root, file) =
+            process("B[^b] then A[^a] and B[^b].\n\n[^a]: A\n\n[^b]: B\n\n[^c]: C\n");
+        let para = &root.children[0];
+        let refs: Vec<_> = para
+            .children
+            .iter()
+            .filter(|n| n.node_type == NodeType::FootnoteReference)
+            .map(|n| (n.data["number"].clone(), n.data["referenceIndex"].clone()))
+            .collect();
+        assert_eq!(
+            refs,
+            vec![
+                (Value::from(1), Value::from(1)),
+                (Value::from(2), Value::from(1)),
+                (Value::from(1), Value::from(2)),
+            ]
+        );
+        assert_eq!(file.messages.len(), 1);
+        assert!(file.messages[0].reason.contains("`c`"));
+    }
+}
@@ -0,0 +1,408 @@
+// Generic AST traversal: visitors with enter/exit hooks and tree utilities
+
+use crate::ast::{Node, NodeType};
+
+/// What the walker should do after a visitor hook returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Control {
+    /// Keep walking (descend into children after `enter`)
+    #[default]
+    Continue,
+    /// Do not descend into this node's children; `exit` is still called
+    Skip,
+    /// Abort the whole walk immediately
+    Stop,
+}
+
+/// Where a node sits in the tree during an immutable walk
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    /// Child indices from the root to the current node
+    pub path: &'a [usize],
+    /// Ancestors from the root down to the parent
+    pub ancestors: &'a [&'a Node],
+}
+
+impl<'a> Context<'a> {
+    pub fn parent(&self) -> Option<&'a Node> {
+        self.ancestors.last().copied()
+    }
+
+    /// Index of the current node within its parent
+    pub fn index(&self) -> Option<usize> {
+        self.path.last().copied()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+}
+
+/// Where a node sits in the tree during a mutable walk
+///
+/// Ancestors cannot be borrowed while a descendant is mutably borrowed,
+/// so only their types are exposed.
+#[derive(Debug, Clone, Copy)]
+pub struct ContextMut<'a> {
+    pub path: &'a [usize],
+    pub ancestor_types: &'a [NodeType],
+}
+
+impl<'a> ContextMut<'a> {
+    pub fn parent_type(&self) -> Option<&'a NodeType> {
+        self.ancestor_types.last()
+    }
+
+    pub fn index(&self) -> Option<usize> {
+        self.path.last().copied()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+}
+
+/// Read-only visitor; both hooks default to [`Control::Continue`]
+pub trait Visitor {
+    fn enter(&mut self, _node: &Node, _ctx: &Context) -> Control {
+        Control::Continue
+    }
+
+    fn exit(&mut self, _node: &Node, _ctx: &Context) -> Control {
+        Control::Continue
+    }
+}
+
+/// Mutating visitor; `enter` may rewrite the node (including its children)
+/// before they are visited
+pub trait VisitorMut {
+    fn enter(&mut self, _node: &mut Node, _ctx: &ContextMut) -> Control {
+        Control::Continue
+    }
+
+    fn exit(&mut self, _node: &mut Node, _ctx: &ContextMut) -> Control {
+        Control::Continue
+    }
+}
+
+/// Walk `root` depth-first in document order; returns `Control::Stop` if aborted
+pub fn walk<V: Visitor + ?Sized>(root: &Node, visitor: &mut V) -> Control {
+    let mut path = Vec::new();
+    let mut ancestors = Vec::new();
+    walk_node(root, visitor, &mut path, &mut ancestors)
+}
+
+fn walk_node<'t, V: Visitor + ?Sized>(
+    node: &'t Node,
+    visitor: &mut V,
+    path: &mut Vec<usize>,
+    ancestors: &mut Vec<&'t Node>,
+) -> Control {
+    let control = visitor.enter(node, &Context { path, ancestors });
+    if control == Control::Stop {
+        return Control::Stop;
+    }
+
+    if control == Control::Continue {
+        ancestors.push(node);
+        for (index, child) in node.children.iter().enumerate() {
+            path.push(index);
+            let control = walk_node(child, visitor, path, ancestors);
+            path.pop();
+            if control == Control::Stop {
+                ancestors.pop();
+                return Control::Stop;
+            }
+        }
+        ancestors.pop();
+    }
+
+    match visitor.exit(node, &Context { path, ancestors }) {
+        Control::Stop => Control::Stop,
+        _ => Control::Continue,
+    }
+}
+
+/// Mutable counterpart of [`walk`]
+pub fn walk_mut<V: VisitorMut + ?Sized>(root: &mut Node, visitor: &mut V) -> Control {
+    let mut path = Vec::new();
+    let mut ancestor_types = Vec::new();
+    walk_node_mut(root, visitor, &mut path, &mut ancestor_types)
+}
+
+fn walk_node_mut<V: VisitorMut + ?Sized>(
+    node: &mut Node,
+    visitor: &mut V,
+    path: &mut Vec<usize>,
+    ancestor_types: &mut Vec<NodeType>,
+) -> Control {
+    let control = visitor.enter(
+        node,
+        &ContextMut {
+            path,
+            ancestor_types,
+        },
+    );
+    if control == Control::Stop {
+        return Control::Stop;
+    }
+
+    if control == Control::Continue {
+        ancestor_types.push(node.node_type.clone());
+        for index in 0..node.children.len() {
+            path.push(index);
+            let control = walk_node_mut(&mut node.children[index], visitor, path, ancestor_types);
+            path.pop();
+            if control == Control::Stop {
+                ancestor_types.pop();
+                return Control::Stop;
+            }
+        }
+        ancestor_types.pop();
+    }
+
+    let ctx = ContextMut {
+        path,
+        ancestor_types,
+    };
+    match visitor.exit(node, &ctx) {
+        Control::Stop => Control::Stop,
+        _ => Control::Continue,
+    }
+}
+
+struct FnVisitor<F>(F);
+
+impl<F: FnMut(&Node, &Context) -> Control> Visitor for FnVisitor<F> {
+    fn enter(&mut self, node: &Node, ctx: &Context) -> Control {
+        (self.0)(node, ctx)
+    }
+}
+
+struct FnVisitorMut<F>(F);
+
+impl<F: FnMut(&mut Node, &ContextMut) -> Control> VisitorMut for FnVisitorMut<F> {
+    fn enter(&mut self, node: &mut Node, ctx: &ContextMut) -> Control {
+        (self.0)(node, ctx)
+    }
+}
+
+/// Call `f` on every node (pre-order); the closure controls the walk
+pub fn visit<F: FnMut(&Node, &Context) -> Control>(root: &Node, f: F) {
+    walk(root, &mut FnVisitor(f));
+}
+
+/// Call `f` on every node (pre-order) with mutable access
+pub fn visit_mut<F: FnMut(&mut Node, &ContextMut) -> Control>(root: &mut Node, f: F) {
+    walk_mut(root, &mut FnVisitorMut(f));
+}
+
+/// Call `f` on every node of the given type
+pub fn visit_by_type<F: FnMut(&Node, &Context)>(root: &Node, node_type: NodeType, mut f: F) {
+    visit(root, |node, ctx| {
+        if node.node_type == node_type {
+            f(node, ctx);
+        }
+        Control::Continue
+    });
+}
+
+/// Call `f` on every node of the given type with mutable access
+pub fn visit_by_type_mut<F: FnMut(&mut Node, &ContextMut)>(
+    root: &mut Node,
+    node_type: NodeType,
+    mut f: F,
+) {
+    visit_mut(root, |node, ctx| {
+        if node.node_type == node_type {
+            f(node, ctx);
+        }
+        Control::Continue
+    });
+}
+
+/// First node (pre-order) matching `predicate`
+pub fn find<F: FnMut(&Node) -> bool>(root: &Node, mut predicate: F) -> Option<&Node> {
+    find_path(root, |node| predicate(node)).and_then(|path| node_at(root, &path))
+}
+
+/// Child-index path of the first node (pre-order) matching `predicate`
+pub fn find_path<F: FnMut(&Node) -> bool>(root: &Node, mut predicate: F) -> Option<Vec<usize>> {
+    let mut found = None;
+    visit(root, |node, ctx| {
+        if predicate(node) {
+            found = Some(ctx.path.to_vec());
+            Control::Stop
+        } else {
+            Control::Continue
+        }
+    });
+    found
+}
+
+/// All nodes matching `predicate`, in document order
+pub fn find_all<F: FnMut(&Node) -> bool>(root: &Node, mut predicate: F) -> Vec<&Node> {
+    let mut paths = Vec::new();
+    visit(root, |node, ctx| {
+        if predicate(node) {
+            paths.push(ctx.path.to_vec());
+        }
+        Control::Continue
+    });
+    paths.iter().filter_map(|p| node_at(root, p)).collect()
+}
+
+/// Node at a child-index path (empty path is the root)
+pub fn node_at<'a>(root: &'a Node, path: &[usize]) -> Option<&'a Node> {
+    path.iter()
+        .try_fold(root, |node, &index| node.children.get(index))
+}
+
+/// Mutable node at a child-index path
+pub fn node_at_mut<'a>(root: &'a mut Node, path: &[usize]) -> Option<&'a mut Node> {
+    path.iter()
+        .try_fold(root, |node, &index| node.children.get_mut(index))
+}
+
+/// Rewrite the value of every `Text` node
+pub fn map_text<F: FnMut(&str) -> String>(root: &mut Node, mut f: F) {
+    visit_by_type_mut(root, NodeType::Text, |node, _| {
+        if let Some(value) = &node.value {
+            node.value = Some(f(value));
+        }
+    });
+}
+
+/// Remove every descendant matching `predicate` (with its subtree); returns how many were removed
+pub fn remove_if<F: FnMut(&Node) -> bool>(root: &mut Node, mut predicate: F) -> usize {
+    fn prune<F: FnMut(&Node) -> bool>(node: &mut Node, predicate: &mut F) -> usize {
+        let before = node.children.len();
+        node.children.retain(|child| !predicate(child));
+        let mut removed = before - node.children.len();
+        for child in &mut node.children {
+            removed += prune(child, predicate);
+        }
+        removed
+    }
+    prune(root, &mut predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Node {
+        Node {
+            node_type: NodeType::Text,
+            value: Some(value.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> Node {
+        Node {
+            node_type: NodeType::Root,
+            children: vec![
+                Node {
+                    node_type: NodeType::Heading,
+                    depth: Some(1),
+                    children: vec![text("Title")],
+                    ..Default::default()
+                },
+                Node {
+                    node_type: NodeType::Paragraph,
+                    children: vec![
+                        text("Hello "),
+                        Node {
+                            node_type: NodeType::Emphasis,
+                            children: vec![text("world")],
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Visitor for Recorder {
+        fn enter(&mut self, node: &Node, ctx: &Context) -> Control {
+            self.events
+                .push(format!("enter {:?} {:?}", node.node_type, ctx.path));
+            if node.node_type == NodeType::Heading {
+                Control::Skip
+            } else if node.node_type == NodeType::Emphasis {
+                Control::Stop
+            } else {
+                Control::Continue
+            }
+        }
+
+        fn exit(&mut self, node: &Node, _ctx: &Context) -> Control {
+            self.events.push(format!("exit {:?}", node.node_type));
+            Control::Continue
+        }
+    }
+
+    #[test]
+    fn test_walk_skip_and_stop() {
+        let root = sample();
+        let mut recorder = Recorder::default();
+        assert_eq!(walk(&root, &mut recorder), Control::Stop);
+        assert_eq!(
+            recorder.events,
+            vec![
+                "enter Root []",
+                "enter Heading [0]",
+                "exit Heading",
+                "enter Paragraph [1]",
+                "enter Text [1, 0]",
+                "exit Text",
+                "enter Emphasis [1, 1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parent_access() {
+        let root = sample();
+        let mut parents = Vec::new();
+        visit_by_type(&root, NodeType::Text, |_, ctx| {
+            parents.push(ctx.parent().unwrap().node_type.clone());
+        });
+        assert_eq!(
+            parents,
+            vec![NodeType::Heading, NodeType::Paragraph, NodeType::Emphasis]
+        );
+    }
+
+    #[test]
+    fn test_find_and_paths() {
+        let root = sample();
+        let path = find_path(&root, |n| n.node_type == NodeType::Emphasis).unwrap();
+        assert_eq!(path, vec![1, 1]);
+        assert_eq!(
+            find(&root, |n| n.depth == Some(1)).unwrap().node_type,
+            NodeType::Heading
+        );
+        assert_eq!(find_all(&root, |n| n.node_type == NodeType::Text).len(), 3);
+        assert!(node_at(&root, &[5]).is_none());
+    }
+
+    #[test]
+    fn test_map_text_and_remove_if() {
+        let mut root = sample();
+        map_text(&mut root, |s| s.to_uppercase());
+        assert_eq!(root.text_content(), "TITLEHELLO WORLD");
+
+        let removed = remove_if(&mut root, |n| n.node_type == NodeType::Emphasis);
+        assert_eq!(removed, 1);
+        assert_eq!(root.text_content(), "TITLEHELLO ");
+    }
+}
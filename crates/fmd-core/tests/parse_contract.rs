@@ -125,3 +125,47 @@ fn test_parse_error_handling() {
     assert!(result.success);
     assert!(!result.warnings.is_empty());
 }
+
+#[test]
+fn test_soft_breaks_are_text() {
+    let result = parse(
+        &Document::new("one\ntwo \\* three  \nfour\n"),
+        Default::default(),
+    );
+    let paragraph = &result.ast.children[0];
+
+    // A soft line ending stays in the text; escapes do not split it
+    let kinds: Vec<_> = paragraph
+        .children
+        .iter()
+        .map(|c| c.node_type.clone())
+        .collect();
+    assert_eq!(kinds, vec![NodeType::Text, NodeType::Break, NodeType::Text]);
+    assert_eq!(
+        paragraph.children[0].value.as_deref(),
+        Some("one\ntwo * three")
+    );
+}
+
+#[test]
+fn test_inline_html_is_kept() {
+    let result = parse(&Document::new("a <b>x</b> c\n"), Default::default());
+    let paragraph = &result.ast.children[0];
+
+    // Inline tags become html nodes between the text, like block HTML
+    let nodes: Vec<_> = paragraph
+        .children
+        .iter()
+        .map(|c| (c.node_type.clone(), c.value.as_deref().unwrap_or_default()))
+        .collect();
+    assert_eq!(
+        nodes,
+        vec![
+            (NodeType::Text, "a "),
+            (NodeType::Html, "<b>"),
+            (NodeType::Text, "x"),
+            (NodeType::Html, "</b>"),
+            (NodeType::Text, " c"),
+        ]
+    );
+}
@@ -183,7 +183,7 @@ impl AutolinkParser {
             }
 
             // Regular character
-            if let Some(ch) = text.chars().nth(pos) {
+            if let Some(ch) = text[pos..].chars().next() {
                 plain_text.push(ch);
                 pos += ch.len_utf8();
             } else {
@@ -3,10 +3,13 @@
 pub mod autolink;
 pub mod strikethrough;
 pub mod table;
+pub mod transform;
 
 use fmd_core::Node;
 use serde::{Deserialize, Serialize};
 
+pub use transform::processor;
+
 /// GFM configuration options
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct GfmOptions {
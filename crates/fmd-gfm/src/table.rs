@@ -28,7 +28,7 @@ impl TableParser {
         }
 
         // Create header row node
-        let header_row = Node {
+        let mut header_row = Node {
             node_type: NodeType::TableRow,
             children: header_cells
                 .into_iter()
@@ -37,6 +37,9 @@ impl TableParser {
                 .collect(),
             ..Default::default()
         };
+        header_row
+            .data
+            .insert("header".to_string(), serde_json::Value::Bool(true));
 
         let mut rows = vec![header_row];
 
@@ -67,6 +70,7 @@ impl TableParser {
         Some(Node {
             node_type: NodeType::Table,
             children: rows,
+            align: Some(alignments.iter().map(|a| a.as_str().to_string()).collect()),
             ..Default::default()
         })
     }
@@ -183,15 +187,9 @@ impl TableParser {
         }
 
         if let Some(align) = alignment {
-            let align_str = match align {
-                Alignment::Left => "left",
-                Alignment::Right => "right",
-                Alignment::Center => "center",
-                Alignment::None => "none",
-            };
             node.data.insert(
                 "align".to_string(),
-                serde_json::Value::String(align_str.to_string()),
+                serde_json::Value::String(align.as_str().to_string()),
             );
         }
 
@@ -207,6 +205,18 @@ enum Alignment {
     Center,
 }
 
+impl Alignment {
+    /// mdast `align` value
+    fn as_str(self) -> &'static str {
+        match self {
+            Alignment::Left => "left",
+            Alignment::Right => "right",
+            Alignment::Center => "center",
+            Alignment::None => "none",
+        }
+    }
+}
+
 /// Check if lines form a valid GFM table
 pub fn is_table(lines: &[&str]) -> bool {
     if lines.len() < 2 {
@@ -274,6 +284,14 @@ mod tests {
 
         let table = parser.parse_table(&lines, 0);
         assert!(table.is_some());
+
+        let table = table.unwrap();
+        assert_eq!(
+            table.align,
+            Some(vec!["left".into(), "center".into(), "right".into()])
+        );
+        assert_eq!(table.children[0].data["header"], true);
+        assert!(!table.children[1].data.contains_key("header"));
     }
 
     #[test]
@@ -0,0 +1,165 @@
+// GFM transforms for the fmd-core processor
+
+use crate::autolink::{contains_email, contains_url, AutolinkParser};
+use fmd_core::visit::{visit_mut, Control};
+use fmd_core::{Node, NodeType, Position, Processor, ProcessorOptions, Transformer, VFile};
+
+/// Turns bare URLs and email addresses in text into links (GFM autolink literals)
+#[derive(Debug, Default)]
+pub struct Autolinks;
+
+impl Autolinks {
+    fn is_candidate(node: &Node) -> bool {
+        node.node_type == NodeType::Text
+            && node
+                .value
+                .as_deref()
+                .is_some_and(|v| contains_url(v) || contains_email(v))
+    }
+}
+
+/// Give the pieces a split text node became their own positions: exact when
+/// the text appears verbatim in `source`, else the whole text's position
+fn place(pieces: &mut [Node], text: &str, position: &Position, source: &str) {
+    let verbatim = source.get(position.start.offset..position.end.offset) == Some(text);
+    let mut point = position.start.clone();
+    for piece in pieces {
+        let piece_position = if verbatim {
+            let start = point.clone();
+            let from = start.offset - position.start.offset;
+            let length = piece.text_content().len();
+            for ch in text[from..from + length].chars() {
+                if ch == '\n' {
+                    point.line += 1;
+                    point.column = 1;
+                } else {
+                    point.column += 1;
+                }
+                point.offset += ch.len_utf8();
+            }
+            Position {
+                start,
+                end: point.clone(),
+                source: position.source.clone(),
+            }
+        } else {
+            position.clone()
+        };
+        for child in &mut piece.children {
+            child.position = Some(piece_position.clone());
+        }
+        piece.position = Some(piece_position);
+    }
+}
+
+impl Transformer for Autolinks {
+    fn name(&self) -> &str {
+        "gfm-autolinks"
+    }
+
+    fn transform(&mut self, root: &mut Node, file: &mut VFile) {
+        visit_mut(root, |node, _| {
+            // Never nest links or touch literal content
+            if matches!(
+                node.node_type,
+                NodeType::Link
+                    | NodeType::LinkReference
+                    | NodeType::Definition
+                    | NodeType::Code
+                    | NodeType::Html
+            ) {
+                return Control::Skip;
+            }
+
+            if node.children.iter().any(Self::is_candidate) {
+                let children = std::mem::take(&mut node.children);
+                for mut child in children {
+                    if Self::is_candidate(&child) {
+                        let text = child.value.take().unwrap_or_default();
+                        let mut pieces = AutolinkParser::process_autolinks(&text);
+                        if let Some(position) = &child.position {
+                            place(&mut pieces, &text, position, &file.value);
+                        }
+                        node.children.extend(pieces);
+                    } else {
+                        node.children.push(child);
+                    }
+                }
+            }
+            Control::Continue
+        });
+    }
+}
+
+/// Processor with the core and GFM built-in transforms enabled by `options`
+pub fn processor(options: ProcessorOptions) -> Processor {
+    let processor = Processor::with_defaults(options);
+    if options.gfm || options.gfm_options.autolinks {
+        processor.use_transformer(Autolinks)
+    } else {
+        processor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autolinks_transform() {
+        let options = ProcessorOptions {
+            gfm: true,
+            ..Default::default()
+        };
+        let mut file = VFile::new("See https://example.com and [x](https://a.b) `https://c.d`");
+        let root = processor(options).process(&mut file);
+
+        let para = &root.children[0];
+        let links: Vec<_> = para
+            .children
+            .iter()
+            .filter(|n| n.node_type == NodeType::Link)
+            .filter_map(|n| n.url.as_deref())
+            .collect();
+        assert_eq!(links, vec!["https://example.com", "https://a.b"]);
+        assert_eq!(
+            para.children.last().unwrap().node_type,
+            NodeType::InlineCode
+        );
+    }
+
+    #[test]
+    fn test_autolink_positions() {
+        use fmd_core::{parse, Document};
+
+        let options = ProcessorOptions {
+            gfm: true,
+            position: true,
+            ..Default::default()
+        };
+        let source = "# T\n\nSee https://example.com now,\nmail a@b.co\n";
+        let mut root = parse(&Document::new(source), options).ast;
+        processor(options).run(&mut root, &mut VFile::new(source));
+
+        let spans: Vec<_> = root.children[1]
+            .children
+            .iter()
+            .map(|n| {
+                let p = n.position.as_ref().unwrap();
+                let span = &source[p.start.offset..p.end.offset];
+                (span, p.start.line, p.start.column, p.end.column)
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("See ", 3, 1, 5),
+                ("https://example.com", 3, 5, 24),
+                (" now,\nmail ", 3, 24, 6),
+                ("a@b.co", 4, 6, 12),
+            ]
+        );
+        let link = &root.children[1].children[1];
+        assert_eq!(link.children[0].position, link.position);
+    }
+}
@@ -0,0 +1,567 @@
+// Fenced code blocks: language renderers, highlighting, per-line wrappers and titles
+
+use crate::hast::{stringify, Element, HastNode, PropertyValue};
+use crate::visitor::escape_html;
+use crate::HtmlOptions;
+use fmd_core::{CodeMeta, Node};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Custom HTML for code fences of one language, instead of `<pre><code>`
+pub trait CodeRenderer: Send + Sync {
+    /// `None` falls through to the default rendering
+    fn render(&self, node: &Node) -> Option<String>;
+
+    /// Tags and their attributes the output needs to survive sanitization
+    ///
+    /// They are allowed for the whole document, raw HTML included, so this
+    /// should only list markup that is harmless wherever it appears.
+    fn allowed(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        Vec::new()
+    }
+}
+
+impl<F> CodeRenderer for F
+where
+    F: Fn(&Node) -> Option<String> + Send + Sync,
+{
+    fn render(&self, node: &Node) -> Option<String> {
+        self(node)
+    }
+}
+
+/// Code renderers keyed by fence language (case-insensitive)
+#[derive(Clone, Default)]
+pub struct CodeRenderers {
+    renderers: HashMap<String, Arc<dyn CodeRenderer>>,
+}
+
+impl fmt::Debug for CodeRenderers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut langs: Vec<_> = self.renderers.keys().collect();
+        langs.sort();
+        f.debug_tuple("CodeRenderers").field(&langs).finish()
+    }
+}
+
+/// Languages with a built-in renderer
+pub const BUILTIN_RENDERERS: &[&str] = &["mermaid", "math", "csv", "svg"];
+
+impl CodeRenderers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `mermaid` → `<pre class="mermaid">`, `math` → display math, `csv` → a table
+    /// and `svg` → inline SVG sanitized to shapes, text and styling
+    pub fn builtin() -> Self {
+        let mut renderers = Self::new();
+        for lang in BUILTIN_RENDERERS {
+            renderers.register_builtin(lang);
+        }
+        renderers
+    }
+
+    /// Add one of [`BUILTIN_RENDERERS`]; returns false for other names
+    pub fn register_builtin(&mut self, lang: &str) -> bool {
+        let renderer: Arc<dyn CodeRenderer> = match lang {
+            "mermaid" => Arc::new(Mermaid),
+            "math" => Arc::new(Math),
+            "csv" => Arc::new(Csv),
+            "svg" => Arc::new(Svg),
+            _ => return false,
+        };
+        self.renderers.insert(lang.to_string(), renderer);
+        true
+    }
+
+    /// Add or replace the renderer for `lang`
+    pub fn register(&mut self, lang: &str, renderer: impl CodeRenderer + 'static) -> &mut Self {
+        self.renderers
+            .insert(lang.to_ascii_lowercase(), Arc::new(renderer));
+        self
+    }
+
+    pub fn remove(&mut self, lang: &str) -> bool {
+        self.renderers.remove(&lang.to_ascii_lowercase()).is_some()
+    }
+
+    pub fn get(&self, lang: &str) -> Option<&dyn CodeRenderer> {
+        self.renderers
+            .get(&lang.to_ascii_lowercase())
+            .map(|renderer| renderer.as_ref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renderers.is_empty()
+    }
+
+    /// Everything the registered renderers need from the sanitizer
+    pub(crate) fn allowed(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        self.renderers.values().flat_map(|r| r.allowed()).collect()
+    }
+
+    /// Output of the renderer registered for the node's language, if any
+    pub(crate) fn render(&self, node: &Node) -> Option<String> {
+        self.get(node.lang.as_deref()?)?.render(node)
+    }
+}
+
+struct Mermaid;
+
+impl CodeRenderer for Mermaid {
+    fn render(&self, node: &Node) -> Option<String> {
+        let value = node.value.as_deref().unwrap_or_default();
+        Some(format!(
+            "<pre class=\"mermaid\">{}</pre>",
+            escape_html(value)
+        ))
+    }
+
+    fn allowed(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        vec![("pre", vec!["class"])]
+    }
+}
+
+/// Same markup as `$$` math blocks, for a client-side math renderer
+struct Math;
+
+impl CodeRenderer for Math {
+    fn render(&self, node: &Node) -> Option<String> {
+        let value = node.value.as_deref().unwrap_or_default();
+        Some(stringify(&math_display(value).into()))
+    }
+
+    fn allowed(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        vec![("div", vec!["class"])]
+    }
+}
+
+pub(crate) fn math_display(value: &str) -> Element {
+    let mut div = Element::new("div").with_children(vec![HastNode::text(value)]);
+    div.add_class("math");
+    div.add_class("math-display");
+    div
+}
+
+/// First row as the header; quoted fields may contain commas, `""` and newlines
+struct Csv;
+
+impl CodeRenderer for Csv {
+    fn render(&self, node: &Node) -> Option<String> {
+        let mut rows = parse_csv(node.value.as_deref().unwrap_or_default()).into_iter();
+        let header = rows.next()?;
+        let row = |cells: Vec<String>, tag: &str| -> HastNode {
+            let cells = cells
+                .iter()
+                .map(|cell| {
+                    Element::new(tag)
+                        .with_children(vec![HastNode::text(cell)])
+                        .into()
+                })
+                .collect();
+            Element::new("tr").with_children(cells).into()
+        };
+        let mut table = Element::new("table").with_children(vec![Element::new("thead")
+            .with_children(vec![row(header, "th")])
+            .into()]);
+        let body: Vec<HastNode> = rows.map(|cells| row(cells, "td")).collect();
+        if !body.is_empty() {
+            table
+                .children
+                .push(Element::new("tbody").with_children(body).into());
+        }
+        Some(stringify(&table.into()))
+    }
+}
+
+fn parse_csv(input: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| !(row.len() == 1 && row[0].trim().is_empty()));
+    rows
+}
+
+// SVG names are case-sensitive (`viewBox`, `linearGradient`)
+const SVG_TAGS: &[&str] = &[
+    "svg",
+    "g",
+    "path",
+    "rect",
+    "circle",
+    "ellipse",
+    "line",
+    "polyline",
+    "polygon",
+    "text",
+    "tspan",
+    "title",
+    "desc",
+    "defs",
+    "linearGradient",
+    "radialGradient",
+    "stop",
+    "clipPath",
+];
+
+const SVG_ATTRIBUTES: &[&str] = &[
+    "viewBox",
+    "width",
+    "height",
+    "xmlns",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "stroke",
+    "stroke-width",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-dasharray",
+    "stroke-opacity",
+    "opacity",
+    "transform",
+    "d",
+    "x",
+    "y",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "r",
+    "rx",
+    "ry",
+    "points",
+    "offset",
+    "stop-color",
+    "stop-opacity",
+    "font-size",
+    "font-family",
+    "font-weight",
+    "text-anchor",
+    "dominant-baseline",
+    "id",
+    "class",
+    "clip-path",
+    "gradientUnits",
+    "gradientTransform",
+    "preserveAspectRatio",
+    "role",
+    "aria-label",
+];
+
+/// Prefix for ids in fenced SVG, so they cannot clobber the page's own ids
+const SVG_ID_PREFIX: &str = "user-content-";
+
+/// Inline SVG limited to shapes, text, gradients and presentation attributes
+///
+/// Ids get [`SVG_ID_PREFIX`] and `url(#id)` references follow them. Sanitized
+/// output drops ids and classes, as the allowlist also applies to raw `<svg>`.
+struct Svg;
+
+impl CodeRenderer for Svg {
+    fn render(&self, node: &Node) -> Option<String> {
+        let value = node.value.as_deref().unwrap_or_default();
+        let mut builder = ammonia::Builder::empty();
+        builder
+            .add_tags(SVG_TAGS)
+            .add_generic_attributes(SVG_ATTRIBUTES)
+            .id_prefix(Some(SVG_ID_PREFIX))
+            .attribute_filter(|_, _, value| {
+                if value.contains("url(#") {
+                    let prefixed = format!("url(#{}", SVG_ID_PREFIX);
+                    Some(value.replace("url(#", &prefixed).into())
+                } else {
+                    Some(value.into())
+                }
+            })
+            .strip_comments(true);
+        Some(builder.clean(value).to_string())
+    }
+
+    fn allowed(&self) -> Vec<(&'static str, Vec<&'static str>)> {
+        let attributes: Vec<&str> = SVG_ATTRIBUTES
+            .iter()
+            .copied()
+            .filter(|&name| name != "id" && name != "class")
+            .collect();
+        SVG_TAGS
+            .iter()
+            .map(|&tag| (tag, attributes.clone()))
+            .collect()
+    }
+}
+
+/// `<pre><code>` for a code node, inside a titled `<figure>` when the meta has a `title`
+///
+/// With line options in the meta (`{1,3}`, `ins=`, `del=`, `showLineNumbers`,
+/// `startLine=`) every line becomes `<span class="line">`, plus `highlighted`,
+/// `ins` or `del`, and `data-line` when line numbers are shown.
+pub(crate) fn code_block(node: &Node, options: &HtmlOptions) -> Element {
+    let value = node.value.as_deref().unwrap_or_default();
+    let lang = node.lang.as_deref();
+    let meta = CodeMeta::from_node(node);
+
+    let content = match &options.highlight {
+        Some(highlighter) => HastNode::Raw {
+            value: highlighter.highlight(value, lang),
+        },
+        None => HastNode::text(value),
+    };
+    let mut code = Element::new("code");
+    if let Some(lang) = lang {
+        code.add_class(&format!("language-{}", lang));
+    }
+    if meta.has_line_options() {
+        if meta.show_line_numbers {
+            code.properties
+                .insert("dataLineNumbers".to_string(), PropertyValue::Bool(true));
+        }
+        code.children = lines(content, &meta);
+    } else {
+        code.children = vec![content];
+    }
+
+    let pre = Element::new("pre").with_children(vec![code.into()]);
+    match &meta.title {
+        Some(title) => {
+            let mut caption = Element::new("figcaption").with_children(vec![HastNode::text(title)]);
+            caption.add_class("code-title");
+            let mut figure = Element::new("figure").with_children(vec![caption.into(), pre.into()]);
+            figure.add_class("code-block");
+            figure
+        }
+        None => pre,
+    }
+}
+
+/// Split text or (line-balanced) highlighted HTML into one wrapper per line
+fn lines(content: HastNode, meta: &CodeMeta) -> Vec<HastNode> {
+    let (source, raw) = match content {
+        HastNode::Raw { value } => (value, true),
+        HastNode::Text { value } => (value, false),
+        other => return vec![other],
+    };
+    let trailing_newline = source.ends_with('\n');
+    let source = source.strip_suffix('\n').unwrap_or(&source);
+
+    let mut out = Vec::new();
+    for (index, line) in source.split('\n').enumerate() {
+        let number = index + 1;
+        let mut span = Element::new("span");
+        span.add_class("line");
+        for (lines, class) in [
+            (&meta.highlight, "highlighted"),
+            (&meta.ins, "ins"),
+            (&meta.del, "del"),
+        ] {
+            if lines.contains(&number) {
+                span.add_class(class);
+            }
+        }
+        if meta.show_line_numbers {
+            let shown = meta.start_line.saturating_add(index);
+            span.properties
+                .insert("dataLine".to_string(), PropertyValue::Number(shown as f64));
+        }
+        span.children = vec![if raw {
+            HastNode::Raw {
+                value: line.to_string(),
+            }
+        } else {
+            HastNode::text(line)
+        }];
+        if index > 0 {
+            out.push(HastNode::text("\n"));
+        }
+        out.push(span.into());
+    }
+    if trailing_newline {
+        out.push(HastNode::text("\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hast::stringify;
+    use fmd_core::{Document, ProcessorOptions};
+
+    fn render(markdown: &str, options: &HtmlOptions) -> String {
+        let ast = fmd_core::parse(&Document::new(markdown), ProcessorOptions::default()).ast;
+        stringify(&code_block(&ast.children[0], options).into())
+    }
+
+    #[test]
+    fn test_line_wrappers() {
+        let html = render(
+            "```rust {1} showLineNumbers startLine=10 ins={2} del={3}\na\n<b>\nc\n```\n",
+            &HtmlOptions::default(),
+        );
+        assert_eq!(
+            html,
+            "<pre><code class=\"language-rust\" data-line-numbers>\
+             <span class=\"line highlighted\" data-line=\"10\">a</span>\n\
+             <span class=\"line ins\" data-line=\"11\">&lt;b&gt;</span>\n\
+             <span class=\"line del\" data-line=\"12\">c</span>\n</code></pre>"
+        );
+
+        // Line numbers stop growing instead of overflowing
+        let html = render(
+            "```rust showLineNumbers startLine=18446744073709551615\na\nb\n```\n",
+            &HtmlOptions::default(),
+        );
+        assert_eq!(
+            html.matches("data-line=\"18446744073709552000\"").count(),
+            2
+        );
+
+        // No line options: the code is left as one text node
+        assert_eq!(
+            render("```js copy\nx\n```\n", &HtmlOptions::default()),
+            "<pre><code class=\"language-js\">x\n</code></pre>"
+        );
+    }
+
+    #[test]
+    fn test_title_and_highlighter() {
+        struct Bold;
+        impl crate::Highlighter for Bold {
+            fn highlight(&self, code: &str, _lang: Option<&str>) -> String {
+                code.lines()
+                    .map(|line| format!("<b>{}</b>\n", line))
+                    .collect()
+            }
+        }
+
+        let options = HtmlOptions {
+            highlight: Some(std::sync::Arc::new(Bold)),
+            ..Default::default()
+        };
+        assert_eq!(
+            render("```sh title=\"install.sh\" {2}\na\nb\n```\n", &options),
+            "<figure class=\"code-block\"><figcaption class=\"code-title\">install.sh</figcaption>\
+             <pre><code class=\"language-sh\"><span class=\"line\"><b>a</b></span>\n\
+             <span class=\"line highlighted\"><b>b</b></span>\n</code></pre></figure>"
+        );
+    }
+
+    fn render_html(markdown: &str, options: HtmlOptions) -> String {
+        let ast = fmd_core::parse(&Document::new(markdown), ProcessorOptions::default()).ast;
+        crate::to_html(&ast, options)
+    }
+
+    #[test]
+    fn test_builtin_renderers() {
+        let options = HtmlOptions {
+            sanitize: true,
+            code_renderers: CodeRenderers::builtin(),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_html("```mermaid\ngraph TD; A-->B\n```\n", options.clone()),
+            "<pre class=\"mermaid\">graph TD; A--&gt;B\n</pre>\n"
+        );
+        assert_eq!(
+            render_html("```math\nx^2\n```\n", options.clone()),
+            "<div class=\"math math-display\">x^2\n</div>\n"
+        );
+        assert_eq!(
+            render_html(
+                "```csv\nname,\"a, b\"\nx,\"say \"\"hi\"\"\"\n```\n",
+                options.clone()
+            ),
+            "<table><thead><tr><th>name</th><th>a, b</th></tr></thead>\
+             <tbody><tr><td>x</td><td>say \"hi\"</td></tr></tbody></table>\n"
+        );
+
+        let svg = render_html(
+            "```svg\n<svg viewBox=\"0 0 10 10\" onload=\"alert(1)\"><script>alert(1)</script>\
+             <circle cx=\"5\" cy=\"5\" r=\"4\" fill=\"red\"/><foreignObject><p>x</p></foreignObject></svg>\n```\n",
+            options.clone(),
+        );
+        assert!(svg.starts_with("<svg viewBox=\"0 0 10 10\">"), "{}", svg);
+        assert!(svg.contains("<circle cx=\"5\" cy=\"5\" r=\"4\" fill=\"red\">"));
+        assert!(!svg.contains("script") && !svg.contains("onload") && !svg.contains("foreign"));
+
+        // Ids are prefixed, and dropped by the sanitizer along with raw SVG ids
+        let markdown = "```svg\n<svg><linearGradient id=\"g\"/><g id=\"login\" fill=\"url(#g)\"/></svg>\n```\n\n\
+                        <svg><g id=\"login\" class=\"x\"></g></svg>\n";
+        assert_eq!(
+            render_html(
+                markdown,
+                HtmlOptions {
+                    code_renderers: CodeRenderers::builtin(),
+                    ..Default::default()
+                }
+            ),
+            "<svg><linearGradient id=\"user-content-g\"></linearGradient>\
+             <g id=\"user-content-login\" fill=\"url(#user-content-g)\"></g></svg>\n\n\
+             <p><svg><g id=\"login\" class=\"x\"></g></svg></p>\n"
+        );
+        assert_eq!(
+            render_html(markdown, options),
+            "<svg><linearGradient></linearGradient><g fill=\"url(#user-content-g)\"></g></svg>\n\n\
+             <p><svg><g></g></svg></p>\n"
+        );
+
+        // Unregistered languages keep the default rendering
+        assert_eq!(
+            render_html("```svg\n<svg></svg>\n```\n", HtmlOptions::default()),
+            "<pre><code class=\"language-svg\">&lt;svg&gt;&lt;/svg&gt;\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_custom_renderer() {
+        let mut renderers = CodeRenderers::new();
+        renderers.register("Chart", |node: &Node| {
+            let value = node.value.as_deref()?;
+            (!value.trim().is_empty())
+                .then(|| format!("<canvas data-spec=\"{}\"></canvas>", value.trim()))
+        });
+        assert!(renderers.get("chart").is_some());
+        assert!(!renderers.register_builtin("plantuml"));
+
+        let options = HtmlOptions {
+            code_renderers: renderers,
+            ..Default::default()
+        };
+        assert_eq!(
+            render_html("```chart\nbar\n```\n", options.clone()),
+            "<canvas data-spec=\"bar\"></canvas>\n"
+        );
+        // `None` falls through to `<pre><code>`
+        assert_eq!(
+            render_html("```chart\n\n```\n", options),
+            "<pre><code class=\"language-chart\">\n</code></pre>\n"
+        );
+    }
+}
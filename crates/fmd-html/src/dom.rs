@@ -0,0 +1,347 @@
+// Read HTML into hast with html5ever's tree builder
+
+use crate::hast::{Element, HastNode, PropertyValue};
+use html5ever::interface::{ElementFlags, NodeOrText, QuirksMode, TreeSink};
+use html5ever::tendril::{StrTendril, TendrilSink};
+use html5ever::{local_name, ns, parse_fragment, Attribute, ExpandedName, ParseOpts, QualName};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// Elements whose text html5ever serializes unescaped
+const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "script",
+    "style",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes",
+    "noscript",
+    "plaintext",
+];
+
+/// Attributes read back as [`PropertyValue::Bool`]
+const BOOLEAN_ATTRIBUTES: &[&str] = &[
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "hidden",
+    "ismap",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
+
+/// Parse an HTML fragment the way the sanitizer does (in a `<div>`, scripting off)
+///
+/// Used to apply [`crate::hast::StringifyOptions`] to sanitized output: the
+/// tree is the one the sanitizer serialized, so no second HTML parser has to
+/// agree with it.
+pub(crate) fn parse_html(html: &str) -> HastNode {
+    let context = QualName::new(None, ns!(html), local_name!("div"));
+    let sink = parse_fragment(
+        Sink::default(),
+        ParseOpts::default(),
+        context,
+        vec![],
+        false,
+    )
+    .one(StrTendril::from(html));
+    // The fragment's nodes sit under the synthetic `<html>` element
+    let html = sink.document.children.borrow().first().cloned();
+    HastNode::Root {
+        children: html.map_or_else(Vec::new, |html| children(&html)),
+    }
+}
+
+type Handle = Rc<Node>;
+
+#[derive(Default)]
+struct Node {
+    data: Data,
+    parent: RefCell<Weak<Node>>,
+    children: RefCell<Vec<Handle>>,
+}
+
+#[derive(Default)]
+enum Data {
+    #[default]
+    Document,
+    Element {
+        name: QualName,
+        attrs: RefCell<Vec<Attribute>>,
+        template_contents: Option<Handle>,
+    },
+    Text(RefCell<StrTendril>),
+    Comment(StrTendril),
+    /// Processing instructions, which HTML has no use for
+    Ignored,
+}
+
+impl Node {
+    fn new(data: Data) -> Handle {
+        Rc::new(Node {
+            data,
+            ..Default::default()
+        })
+    }
+}
+
+fn children(node: &Handle) -> Vec<HastNode> {
+    let children = match &node.data {
+        Data::Element {
+            template_contents: Some(contents),
+            ..
+        } => contents,
+        _ => node,
+    };
+    let children = children.children.borrow();
+    children.iter().filter_map(convert).collect()
+}
+
+fn convert(node: &Handle) -> Option<HastNode> {
+    match &node.data {
+        Data::Element { name, attrs, .. } => {
+            let mut element = Element::new(&name.local);
+            for attr in attrs.borrow().iter() {
+                let name = match &attr.name.prefix {
+                    Some(prefix) => format!("{}:{}", prefix, attr.name.local),
+                    None => attr.name.local.to_string(),
+                };
+                let value = if BOOLEAN_ATTRIBUTES.contains(&name.as_str()) {
+                    PropertyValue::Bool(true)
+                } else {
+                    attr.value.to_string().into()
+                };
+                element.properties.insert(name, value);
+            }
+            element.children = children(node);
+            if name.ns == ns!(html) && RAW_TEXT_ELEMENTS.contains(&&*name.local) {
+                for child in &mut element.children {
+                    if let HastNode::Text { value } = child {
+                        let value = std::mem::take(value);
+                        *child = HastNode::Raw { value };
+                    }
+                }
+            }
+            Some(element.into())
+        }
+        Data::Text(text) => Some(HastNode::text(&text.borrow())),
+        Data::Comment(text) => Some(HastNode::Comment {
+            value: text.to_string(),
+        }),
+        Data::Document | Data::Ignored => None,
+    }
+}
+
+#[derive(Default)]
+struct Sink {
+    document: Handle,
+}
+
+impl Sink {
+    fn detach(&self, target: &Handle) {
+        let parent = target.parent.replace(Weak::new());
+        if let Some(parent) = parent.upgrade() {
+            parent
+                .children
+                .borrow_mut()
+                .retain(|child| !Rc::ptr_eq(child, target));
+        }
+    }
+
+    /// Insert `child` into `parent` at `index`, merging text into a preceding text node
+    fn insert(&self, parent: &Handle, index: usize, child: NodeOrText<Handle>) {
+        let node = match child {
+            NodeOrText::AppendText(text) => {
+                let children = parent.children.borrow();
+                if let Some(Data::Text(previous)) = index
+                    .checked_sub(1)
+                    .and_then(|previous| children.get(previous))
+                    .map(|previous| &previous.data)
+                {
+                    previous.borrow_mut().push_tendril(&text);
+                    return;
+                }
+                Node::new(Data::Text(RefCell::new(text)))
+            }
+            NodeOrText::AppendNode(node) => {
+                self.detach(&node);
+                node
+            }
+        };
+        *node.parent.borrow_mut() = Rc::downgrade(parent);
+        parent.children.borrow_mut().insert(index, node);
+    }
+}
+
+impl TreeSink for Sink {
+    type Handle = Handle;
+    type Output = Self;
+    type ElemName<'a> = ExpandedName<'a>;
+
+    fn finish(self) -> Self {
+        self
+    }
+
+    fn parse_error(&self, _msg: Cow<'static, str>) {}
+
+    fn get_document(&self) -> Handle {
+        self.document.clone()
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a Handle) -> ExpandedName<'a> {
+        match &target.data {
+            Data::Element { name, .. } => name.expanded(),
+            _ => panic!("not an element"),
+        }
+    }
+
+    fn create_element(&self, name: QualName, attrs: Vec<Attribute>, flags: ElementFlags) -> Handle {
+        Node::new(Data::Element {
+            name,
+            attrs: RefCell::new(attrs),
+            template_contents: flags.template.then(|| Node::new(Data::Document)),
+        })
+    }
+
+    fn create_comment(&self, text: StrTendril) -> Handle {
+        Node::new(Data::Comment(text))
+    }
+
+    fn create_pi(&self, _target: StrTendril, _data: StrTendril) -> Handle {
+        Node::new(Data::Ignored)
+    }
+
+    fn append(&self, parent: &Handle, child: NodeOrText<Handle>) {
+        let index = parent.children.borrow().len();
+        self.insert(parent, index, child);
+    }
+
+    fn append_based_on_parent_node(
+        &self,
+        element: &Handle,
+        prev_element: &Handle,
+        child: NodeOrText<Handle>,
+    ) {
+        if element.parent.borrow().upgrade().is_some() {
+            self.append_before_sibling(element, child);
+        } else {
+            self.append(prev_element, child);
+        }
+    }
+
+    fn append_doctype_to_document(&self, _: StrTendril, _: StrTendril, _: StrTendril) {}
+
+    fn get_template_contents(&self, target: &Handle) -> Handle {
+        match &target.data {
+            Data::Element {
+                template_contents: Some(contents),
+                ..
+            } => contents.clone(),
+            _ => panic!("not a template element"),
+        }
+    }
+
+    fn same_node(&self, x: &Handle, y: &Handle) -> bool {
+        Rc::ptr_eq(x, y)
+    }
+
+    fn set_quirks_mode(&self, _mode: QuirksMode) {}
+
+    fn append_before_sibling(&self, sibling: &Handle, child: NodeOrText<Handle>) {
+        let Some(parent) = sibling.parent.borrow().upgrade() else {
+            return;
+        };
+        let index = parent
+            .children
+            .borrow()
+            .iter()
+            .position(|node| Rc::ptr_eq(node, sibling))
+            .expect("sibling is a child of its parent");
+        self.insert(&parent, index, child);
+    }
+
+    fn add_attrs_if_missing(&self, target: &Handle, attrs: Vec<Attribute>) {
+        let Data::Element {
+            attrs: existing, ..
+        } = &target.data
+        else {
+            return;
+        };
+        let mut existing = existing.borrow_mut();
+        for attr in attrs {
+            if !existing.iter().any(|e| e.name == attr.name) {
+                existing.push(attr);
+            }
+        }
+    }
+
+    fn remove_from_parent(&self, target: &Handle) {
+        self.detach(target);
+    }
+
+    fn reparent_children(&self, node: &Handle, new_parent: &Handle) {
+        let children = std::mem::take(&mut *node.children.borrow_mut());
+        for child in &children {
+            *child.parent.borrow_mut() = Rc::downgrade(new_parent);
+        }
+        new_parent.children.borrow_mut().extend(children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hast::{stringify_with, StringifyOptions};
+
+    #[test]
+    fn test_parse_html() {
+        let html = "<p class=\"a\">x &amp; &lt;y&gt;&nbsp;<br><input disabled=\"\"></p>\n\
+                    <svg viewBox=\"0 0 1 1\"></svg><style>a > b {}</style><!--c-->";
+        let tree = parse_html(html);
+        assert_eq!(tree.children().len(), 5);
+        assert_eq!(tree.text_content(), "x & <y>\u{a0}\n");
+        assert_eq!(
+            stringify_with(
+                &tree,
+                &StringifyOptions {
+                    xhtml: true,
+                    ..Default::default()
+                }
+            ),
+            "<p class=\"a\">x &amp; &lt;y&gt;\u{a0}<br /><input disabled=\"disabled\" /></p>\n\
+             <svg viewBox=\"0 0 1 1\"></svg><style>a > b {}</style><!--c-->"
+        );
+    }
+
+    #[test]
+    fn test_parse_html_follows_the_spec() {
+        // Misnested and unclosed markup is fixed up like a browser would
+        let tree = parse_html("<b><i>x</b>y</i><p>a<p>b<table><td>c</table>");
+        assert_eq!(
+            stringify_with(&tree, &StringifyOptions::default()),
+            "<b><i>x</i></b><i>y</i><p>a</p><p>b</p>\
+             <table><tbody><tr><td>c</td></tr></tbody></table>"
+        );
+        // `</a>` inside an attribute value is not a tag
+        let tree = parse_html("<a title=\"</a><img src=x onerror=alert(1)>\">t</a>");
+        assert_eq!(
+            stringify_with(&tree, &StringifyOptions::default()),
+            "<a title=\"&lt;/a&gt;&lt;img src=x onerror=alert(1)&gt;\">t</a>"
+        );
+    }
+}
@@ -0,0 +1,629 @@
+// hast (HTML syntax tree) nodes and serialization to HTML
+
+use crate::visitor::VOID_ELEMENTS;
+use fmd_core::Position;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A hast node, serialized with a `type` tag like the JavaScript ecosystem's hast
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum HastNode {
+    Root {
+        #[serde(default)]
+        children: Vec<HastNode>,
+    },
+    Element(Element),
+    Text {
+        value: String,
+    },
+    Comment {
+        value: String,
+    },
+    Doctype,
+    /// HTML written out verbatim (raw HTML from Markdown)
+    Raw {
+        value: String,
+    },
+}
+
+/// Properties keyed by hast property name (`className`, `dataSourcepos`, …), in insertion order
+pub type Properties = IndexMap<String, PropertyValue>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    /// `true` renders a bare attribute, `false` omits it
+    Bool(bool),
+    Number(f64),
+    String(String),
+    /// Space-separated list, e.g. `className`
+    List(Vec<String>),
+}
+
+impl From<&str> for PropertyValue {
+    fn from(value: &str) -> Self {
+        PropertyValue::String(value.to_string())
+    }
+}
+
+impl From<String> for PropertyValue {
+    fn from(value: String) -> Self {
+        PropertyValue::String(value)
+    }
+}
+
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        PropertyValue::Bool(value)
+    }
+}
+
+impl PropertyValue {
+    /// Convert a JSON value from `data.hProperties`; `null` and objects have no equivalent
+    pub fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(PropertyValue::Bool(*b)),
+            Value::Number(n) => n.as_f64().map(PropertyValue::Number),
+            Value::String(s) => Some(PropertyValue::String(s.clone())),
+            Value::Array(items) => Some(PropertyValue::List(
+                items
+                    .iter()
+                    .map(|item| match item {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect(),
+            )),
+            Value::Null | Value::Object(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Element {
+    pub tag_name: String,
+    #[serde(default)]
+    pub properties: Properties,
+    #[serde(default)]
+    pub children: Vec<HastNode>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub position: Option<Position>,
+}
+
+impl Element {
+    pub fn new(tag_name: &str) -> Self {
+        Self {
+            tag_name: tag_name.to_string(),
+            properties: Properties::new(),
+            children: Vec::new(),
+            position: None,
+        }
+    }
+
+    pub fn with_property(mut self, name: &str, value: impl Into<PropertyValue>) -> Self {
+        self.properties.insert(name.to_string(), value.into());
+        self
+    }
+
+    pub fn with_children(mut self, children: Vec<HastNode>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Append to `className`, keeping existing classes
+    pub fn add_class(&mut self, class: &str) {
+        match self.properties.get_mut("className") {
+            Some(PropertyValue::List(classes)) => {
+                if !classes.iter().any(|c| c == class) {
+                    classes.push(class.to_string());
+                }
+            }
+            Some(PropertyValue::String(existing)) => {
+                let mut classes: Vec<String> =
+                    existing.split_whitespace().map(str::to_string).collect();
+                classes.push(class.to_string());
+                self.properties
+                    .insert("className".to_string(), PropertyValue::List(classes));
+            }
+            _ => {
+                self.properties.insert(
+                    "className".to_string(),
+                    PropertyValue::List(vec![class.to_string()]),
+                );
+            }
+        }
+    }
+
+    /// String value of a property, if it is one
+    pub fn property(&self, name: &str) -> Option<&str> {
+        match self.properties.get(name) {
+            Some(PropertyValue::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl From<Element> for HastNode {
+    fn from(element: Element) -> Self {
+        HastNode::Element(element)
+    }
+}
+
+impl HastNode {
+    pub fn text(value: &str) -> Self {
+        HastNode::Text {
+            value: value.to_string(),
+        }
+    }
+
+    pub fn children(&self) -> &[HastNode] {
+        match self {
+            HastNode::Root { children } => children,
+            HastNode::Element(element) => &element.children,
+            _ => &[],
+        }
+    }
+
+    pub fn children_mut(&mut self) -> Option<&mut Vec<HastNode>> {
+        match self {
+            HastNode::Root { children } => Some(children),
+            HastNode::Element(element) => Some(&mut element.children),
+            _ => None,
+        }
+    }
+
+    /// Concatenated text of all descendants (hast-util-to-string)
+    pub fn text_content(&self) -> String {
+        match self {
+            HastNode::Text { value } => value.clone(),
+            _ => self.children().iter().map(HastNode::text_content).collect(),
+        }
+    }
+}
+
+/// Call `f` on every element in document order, parents before children
+///
+/// The building block for rehype-style transforms: `f` can rewrite the
+/// element's tag, properties or children before they are visited.
+pub fn visit_elements_mut(node: &mut HastNode, f: &mut impl FnMut(&mut Element)) {
+    if let HastNode::Element(element) = node {
+        f(element);
+    }
+    if let Some(children) = node.children_mut() {
+        for child in children {
+            visit_elements_mut(child, f);
+        }
+    }
+}
+
+/// Quote character around attribute values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quote {
+    #[default]
+    Double,
+    Single,
+}
+
+/// Whitespace between block elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Line breaks between blocks as the tree has them
+    #[default]
+    Newlines,
+    /// No whitespace between blocks
+    Compact,
+    /// One block per line, nested blocks indented by two spaces
+    Pretty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StringifyOptions {
+    /// `<br />` and `checked="checked"` instead of `<br>` and `checked`
+    pub xhtml: bool,
+    pub quote: Quote,
+    /// Write `'` as `&#39;` in text and in double-quoted attributes too
+    pub escape_apostrophe: bool,
+    pub layout: Layout,
+}
+
+/// Elements whose whitespace is kept as is by the compact and pretty layouts
+const PREFORMATTED: &[&str] = &["pre", "textarea", "script", "style"];
+
+/// Elements the layouts put on their own line
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "caption",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "li",
+    "link",
+    "main",
+    "meta",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "ul",
+];
+
+/// Serialize a tree to HTML (hast-util-to-html)
+pub fn stringify(node: &HastNode) -> String {
+    stringify_with(node, &StringifyOptions::default())
+}
+
+/// Serialize a tree with explicit void element, quoting, escaping and layout settings
+pub fn stringify_with(node: &HastNode, options: &StringifyOptions) -> String {
+    let mut writer = Writer {
+        options,
+        out: String::with_capacity(1024),
+        sourcepos: None,
+    };
+    writer.node(node, 0, false);
+    writer.out
+}
+
+/// [`stringify_with`], plus the `(start, end, sourcepos)` output range of
+/// every element with a `data-sourcepos` property
+pub(crate) fn stringify_with_sourcepos(
+    node: &HastNode,
+    options: &StringifyOptions,
+) -> (String, Vec<(usize, usize, String)>) {
+    let mut writer = Writer {
+        options,
+        out: String::with_capacity(1024),
+        sourcepos: Some(Vec::new()),
+    };
+    writer.node(node, 0, false);
+    (writer.out, writer.sourcepos.unwrap_or_default())
+}
+
+struct Writer<'a> {
+    options: &'a StringifyOptions,
+    out: String,
+    /// Collected by [`stringify_with_sourcepos`]
+    sourcepos: Option<Vec<(usize, usize, String)>>,
+}
+
+impl Writer<'_> {
+    /// `level` counts the enclosing block elements; inside `verbatim` elements
+    /// whitespace is never touched
+    fn node(&mut self, node: &HastNode, level: usize, verbatim: bool) {
+        match node {
+            HastNode::Root { children } => self.children(children, level, verbatim),
+            HastNode::Element(element) => self.element(element, level, verbatim),
+            HastNode::Text { value } => {
+                let escaped = self.escape(value, None);
+                self.out.push_str(&escaped);
+            }
+            HastNode::Comment { value } => {
+                self.out.push_str("<!--");
+                self.out.push_str(value);
+                self.out.push_str("-->");
+            }
+            HastNode::Doctype if self.options.xhtml => self.out.push_str("<!DOCTYPE html>"),
+            HastNode::Doctype => self.out.push_str("<!doctype html>"),
+            HastNode::Raw { value } => self.out.push_str(value),
+        }
+    }
+
+    fn children(&mut self, children: &[HastNode], level: usize, verbatim: bool) {
+        let layout = self.options.layout;
+        for (index, child) in children.iter().enumerate() {
+            if layout == Layout::Newlines || verbatim || !is_block_whitespace(children, index) {
+                self.node(child, level, verbatim);
+                continue;
+            }
+            if layout == Layout::Pretty {
+                // The line before a closing tag lines up with its opening tag
+                let depth = if index + 1 == children.len() {
+                    level.saturating_sub(1)
+                } else {
+                    level
+                };
+                self.out.push('\n');
+                self.out.push_str(&"  ".repeat(depth));
+            }
+        }
+    }
+
+    fn element(&mut self, element: &Element, level: usize, verbatim: bool) {
+        let start = self.out.len();
+        self.element_markup(element, level, verbatim);
+        if let Some(ranges) = &mut self.sourcepos {
+            // Rendered trees use the property name, trees read from HTML the attribute name
+            let sourcepos = element
+                .property("dataSourcepos")
+                .or_else(|| element.property("data-sourcepos"));
+            if let Some(sourcepos) = sourcepos {
+                ranges.push((start, self.out.len(), sourcepos.to_string()));
+            }
+        }
+    }
+
+    fn element_markup(&mut self, element: &Element, level: usize, verbatim: bool) {
+        let tag = element.tag_name.as_str();
+        self.out.push('<');
+        self.out.push_str(tag);
+        for (name, value) in &element.properties {
+            self.attribute(name, value);
+        }
+        if VOID_ELEMENTS.contains(&tag) {
+            self.out
+                .push_str(if self.options.xhtml { " />" } else { ">" });
+            return;
+        }
+        self.out.push('>');
+        let level = if BLOCK_ELEMENTS.contains(&tag) {
+            level + 1
+        } else {
+            level
+        };
+        let verbatim = verbatim || PREFORMATTED.contains(&tag);
+        self.children(&element.children, level, verbatim);
+        self.out.push_str("</");
+        self.out.push_str(tag);
+        self.out.push('>');
+    }
+
+    fn attribute(&mut self, name: &str, value: &PropertyValue) {
+        let name = attribute_name(name);
+        let value = match value {
+            PropertyValue::Bool(false) => return,
+            // XHTML has no attribute minimization
+            PropertyValue::Bool(true) if self.options.xhtml => name.clone(),
+            PropertyValue::Bool(true) => {
+                self.out.push(' ');
+                self.out.push_str(&name);
+                return;
+            }
+            PropertyValue::Number(n) => n.to_string(),
+            PropertyValue::String(s) => s.clone(),
+            PropertyValue::List(items) => items.join(" "),
+        };
+        let quote = match self.options.quote {
+            Quote::Double => '"',
+            Quote::Single => '\'',
+        };
+        self.out.push(' ');
+        self.out.push_str(&name);
+        self.out.push('=');
+        self.out.push(quote);
+        let escaped = self.escape(&value, Some(quote));
+        self.out.push_str(&escaped);
+        self.out.push(quote);
+    }
+
+    /// Escape text, or an attribute value delimited by `quote`
+    fn escape(&self, text: &str, quote: Option<char>) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for ch in text.chars() {
+            match ch {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' if quote != Some('\'') => escaped.push_str("&quot;"),
+                '\'' if quote == Some('\'') || self.options.escape_apostrophe => {
+                    escaped.push_str("&#39;")
+                }
+                _ => escaped.push(ch),
+            }
+        }
+        escaped
+    }
+}
+
+/// Whether `children[index]` is a line break between two blocks (or the edge of its parent)
+fn is_block_whitespace(children: &[HastNode], index: usize) -> bool {
+    let HastNode::Text { value } = &children[index] else {
+        return false;
+    };
+    let is_block = |node: Option<&HastNode>| match node {
+        None | Some(HastNode::Doctype) => true,
+        Some(HastNode::Element(element)) => BLOCK_ELEMENTS.contains(&element.tag_name.as_str()),
+        Some(_) => false,
+    };
+    value.contains('\n')
+        && value.chars().all(char::is_whitespace)
+        && is_block(index.checked_sub(1).and_then(|i| children.get(i)))
+        && is_block(children.get(index + 1))
+}
+
+/// Case-sensitive SVG attributes that are written as is
+const SVG_CAMEL_CASE: &[&str] = &[
+    "viewBox",
+    "preserveAspectRatio",
+    "gradientUnits",
+    "gradientTransform",
+    "patternUnits",
+    "patternTransform",
+    "clipPathUnits",
+    "markerWidth",
+    "markerHeight",
+    "refX",
+    "refY",
+    "textLength",
+    "lengthAdjust",
+    "stdDeviation",
+];
+
+/// HTML attribute for a hast property name: `className` → `class`, `dataFooBar` → `data-foo-bar`
+pub fn attribute_name(property: &str) -> String {
+    match property {
+        "className" => return "class".to_string(),
+        "htmlFor" => return "for".to_string(),
+        "httpEquiv" => return "http-equiv".to_string(),
+        "acceptCharset" => return "accept-charset".to_string(),
+        _ => {}
+    }
+    // Already attribute names: `aria-hidden`, `data-line`, SVG's `viewBox`
+    if property.contains('-') || SVG_CAMEL_CASE.contains(&property) {
+        return property.to_string();
+    }
+    let prefixed = |prefix: &str| {
+        property
+            .strip_prefix(prefix)
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()))
+    };
+    if let Some(rest) = prefixed("data") {
+        let mut name = String::from("data");
+        for c in rest.chars() {
+            if c.is_ascii_uppercase() {
+                name.push('-');
+                name.push(c.to_ascii_lowercase());
+            } else {
+                name.push(c);
+            }
+        }
+        return name;
+    }
+    if let Some(rest) = prefixed("aria") {
+        return format!("aria-{}", rest.to_ascii_lowercase());
+    }
+    property.to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stringify() {
+        let mut link = Element::new("a")
+            .with_property("href", "/a?b&c")
+            .with_property("dataFootnoteRef", true)
+            .with_property("ariaDescribedBy", "label")
+            .with_children(vec![HastNode::text("<x>")]);
+        link.add_class("one");
+        link.add_class("two");
+        let tree = HastNode::Root {
+            children: vec![
+                link.into(),
+                Element::new("br").into(),
+                HastNode::Comment {
+                    value: " c ".to_string(),
+                },
+            ],
+        };
+        assert_eq!(
+            stringify(&tree),
+            "<a href=\"/a?b&amp;c\" data-footnote-ref aria-describedby=\"label\" \
+             class=\"one two\">&lt;x&gt;</a><br><!-- c -->"
+        );
+    }
+
+    #[test]
+    fn test_stringify_options() {
+        let item = |text: &str| Element::new("li").with_children(vec![HastNode::text(text)]);
+        let list = Element::new("ul").with_children(vec![
+            HastNode::text("\n"),
+            item("it's \"a\"").into(),
+            HastNode::text("\n"),
+            item("b").into(),
+            HastNode::text("\n"),
+        ]);
+        let code = Element::new("pre").with_children(vec![HastNode::text("x\n\ny")]);
+        let input = Element::new("input")
+            .with_property("title", "it's")
+            .with_property("checked", true);
+        let tree = HastNode::Root {
+            children: vec![
+                list.into(),
+                HastNode::text("\n"),
+                code.into(),
+                HastNode::text("\n"),
+                Element::new("p").with_children(vec![input.into()]).into(),
+                HastNode::text("\n"),
+            ],
+        };
+
+        let xhtml = StringifyOptions {
+            xhtml: true,
+            quote: Quote::Single,
+            ..Default::default()
+        };
+        assert_eq!(
+            stringify_with(&tree, &xhtml),
+            "<ul>\n<li>it's &quot;a&quot;</li>\n<li>b</li>\n</ul>\n<pre>x\n\ny</pre>\n\
+             <p><input title='it&#39;s' checked='checked' /></p>\n"
+        );
+        let compact = StringifyOptions {
+            escape_apostrophe: true,
+            layout: Layout::Compact,
+            ..Default::default()
+        };
+        assert_eq!(
+            stringify_with(&tree, &compact),
+            "<ul><li>it&#39;s &quot;a&quot;</li><li>b</li></ul><pre>x\n\ny</pre>\
+             <p><input title=\"it&#39;s\" checked></p>"
+        );
+        let pretty = StringifyOptions {
+            layout: Layout::Pretty,
+            ..Default::default()
+        };
+        assert_eq!(
+            stringify_with(&tree, &pretty),
+            "<ul>\n  <li>it's &quot;a&quot;</li>\n  <li>b</li>\n</ul>\n<pre>x\n\ny</pre>\n\
+             <p><input title=\"it's\" checked></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_hast_json_shape() {
+        let node: HastNode = Element::new("p")
+            .with_property("className", PropertyValue::List(vec!["x".to_string()]))
+            .with_children(vec![HastNode::text("hi")])
+            .into();
+        let json = serde_json::to_value(&node).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "element",
+                "tagName": "p",
+                "properties": { "className": ["x"] },
+                "children": [{ "type": "text", "value": "hi" }]
+            })
+        );
+        assert_eq!(serde_json::from_value::<HastNode>(json).unwrap(), node);
+    }
+}
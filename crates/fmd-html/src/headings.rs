@@ -0,0 +1,101 @@
+// Heading ids and self-link anchors (rehype-slug + rehype-autolink-headings)
+
+use crate::hast::{Element, HastNode, PropertyValue};
+use fmd_slug::Slugger;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HeadingIdOptions {
+    /// Prepended to every rendered id, e.g. `user-content-`
+    pub prefix: String,
+    /// Add a link to the heading itself
+    pub anchor: Option<AnchorOptions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnchorOptions {
+    pub placement: AnchorPlacement,
+    /// Class on the `<a>`; empty for none
+    pub class: String,
+    /// Text of a prepended or appended anchor; unused when wrapping
+    pub content: String,
+}
+
+impl Default for AnchorOptions {
+    fn default() -> Self {
+        Self {
+            placement: AnchorPlacement::Prepend,
+            class: "anchor".to_string(),
+            content: "#".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorPlacement {
+    /// `<h2 id="x"><a href="#x">#</a>Title</h2>`
+    #[default]
+    Prepend,
+    /// `<h2 id="x">Title<a href="#x">#</a></h2>`
+    Append,
+    /// `<h2 id="x"><a href="#x">Title</a></h2>`
+    Wrap,
+}
+
+/// Makes a fresh [`Slugger`] for each render, for id schemes `SlugOptions` cannot express
+#[derive(Clone)]
+pub struct SluggerFactory(Arc<dyn Fn() -> Box<dyn Slugger> + Send + Sync>);
+
+impl SluggerFactory {
+    pub fn new(factory: impl Fn() -> Box<dyn Slugger> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(factory))
+    }
+
+    pub fn slugger(&self) -> Box<dyn Slugger> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for SluggerFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SluggerFactory")
+    }
+}
+
+/// Set the prefixed id on a heading element and add the configured anchor
+pub(crate) fn decorate(heading: &mut Element, id: &str, options: &HeadingIdOptions) {
+    let id = format!("{}{}", options.prefix, id);
+    heading
+        .properties
+        .insert("id".to_string(), PropertyValue::String(id.clone()));
+    let Some(anchor) = &options.anchor else {
+        return;
+    };
+
+    let mut link = Element::new("a").with_property("href", format!("#{}", id));
+    if !anchor.class.is_empty() {
+        link.add_class(&anchor.class);
+    }
+    if anchor.placement == AnchorPlacement::Wrap {
+        link.children = std::mem::take(&mut heading.children);
+        heading.children.push(link.into());
+        return;
+    }
+
+    // The heading text already names the target; keep the icon out of the a11y tree
+    link = link
+        .with_property("ariaHidden", "true")
+        .with_property("tabIndex", PropertyValue::Number(-1.0));
+    if !anchor.content.is_empty() {
+        link.children.push(HastNode::text(&anchor.content));
+    }
+    match anchor.placement {
+        AnchorPlacement::Append => heading.children.push(link.into()),
+        _ => heading.children.insert(0, link.into()),
+    }
+}
@@ -0,0 +1,226 @@
+// Syntax highlighting for code blocks
+
+use std::fmt;
+
+/// Turns the text of a code block into highlighted HTML for inside its `<code>`
+///
+/// The result is inserted verbatim, so implementations must escape the code
+/// themselves. Languages they do not know should come back escaped but
+/// otherwise unchanged. Keeping every line's tags balanced (closing spans
+/// before each `\n`) lets renderers wrap lines individually.
+pub trait Highlighter: Send + Sync {
+    fn highlight(&self, code: &str, lang: Option<&str>) -> String;
+}
+
+impl fmt::Debug for dyn Highlighter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Highlighter")
+    }
+}
+
+#[cfg(feature = "highlight")]
+pub use bundled::{HighlightStyle, SyntectHighlighter, CLASS_PREFIX, THEMES};
+
+#[cfg(feature = "highlight")]
+mod bundled {
+    use super::Highlighter;
+    use crate::visitor::escape_html;
+    use once_cell::sync::Lazy;
+    use syntect::easy::HighlightLines;
+    use syntect::highlighting::{Theme, ThemeSet};
+    use syntect::html::{
+        css_for_theme_with_class_style, line_tokens_to_classed_spans,
+        styled_line_to_highlighted_html, ClassStyle, IncludeBackground,
+    };
+    use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
+    use syntect::util::LinesWithEndings;
+
+    static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+    static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+    /// Prefix of every class emitted by [`HighlightStyle::Classes`]
+    pub const CLASS_PREFIX: &str = "hl-";
+
+    /// Bundled theme names; `github` is an alias for `InspiredGitHub`
+    pub const THEMES: &[&str] = &[
+        "github",
+        "InspiredGitHub",
+        "Solarized (dark)",
+        "Solarized (light)",
+        "base16-eighties.dark",
+        "base16-mocha.dark",
+        "base16-ocean.dark",
+        "base16-ocean.light",
+    ];
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum HighlightStyle {
+        /// `<span style="color:#…">`, no stylesheet needed
+        #[default]
+        Inline,
+        /// `<span class="hl-keyword hl-rust">`, styled by [`SyntectHighlighter::theme_css`]
+        Classes,
+    }
+
+    /// Offline highlighter using syntect's bundled Sublime grammars and themes
+    #[derive(Debug, Clone)]
+    pub struct SyntectHighlighter {
+        theme: Theme,
+        style: HighlightStyle,
+    }
+
+    impl Default for SyntectHighlighter {
+        fn default() -> Self {
+            Self::new("github", HighlightStyle::Inline).expect("bundled theme")
+        }
+    }
+
+    impl SyntectHighlighter {
+        /// `None` if `theme` is not one of [`THEMES`]
+        pub fn new(theme: &str, style: HighlightStyle) -> Option<Self> {
+            let name = if theme == "github" {
+                "InspiredGitHub"
+            } else {
+                theme
+            };
+            let theme = THEME_SET.themes.get(name)?.clone();
+            Some(Self { theme, style })
+        }
+
+        /// Stylesheet for [`HighlightStyle::Classes`] output in this theme
+        pub fn theme_css(&self) -> String {
+            css_for_theme_with_class_style(
+                &self.theme,
+                ClassStyle::SpacedPrefixed {
+                    prefix: CLASS_PREFIX,
+                },
+            )
+            .unwrap_or_default()
+        }
+
+        fn syntax(lang: &str) -> Option<&'static SyntaxReference> {
+            SYNTAXES
+                .find_syntax_by_token(lang)
+                .or_else(|| SYNTAXES.find_syntax_by_name(lang))
+        }
+
+        fn inline(&self, code: &str, syntax: &SyntaxReference) -> Option<String> {
+            let mut highlighter = HighlightLines::new(syntax, &self.theme);
+            let mut out = String::with_capacity(code.len() * 4);
+            for line in LinesWithEndings::from(code) {
+                let regions = highlighter.highlight_line(line, &SYNTAXES).ok()?;
+                let (text, newline) = split_newline(&regions);
+                out.push_str(&styled_line_to_highlighted_html(&text, IncludeBackground::No).ok()?);
+                out.push_str(newline);
+            }
+            Some(out)
+        }
+
+        fn classes(&self, code: &str, syntax: &SyntaxReference) -> Option<String> {
+            let style = ClassStyle::SpacedPrefixed {
+                prefix: CLASS_PREFIX,
+            };
+            let mut state = ParseState::new(syntax);
+            let mut stack = ScopeStack::new();
+            let mut out = String::with_capacity(code.len() * 4);
+            for line in LinesWithEndings::from(code) {
+                let body = line.strip_suffix('\n').unwrap_or(line);
+                let ops = state.parse_line(line, &SYNTAXES).ok()?;
+                // Ops past the end of `body` only touch the newline; they still move the stack
+                let (body_ops, rest): (Vec<_>, Vec<_>) =
+                    ops.into_iter().partition(|(i, _)| *i < body.len());
+
+                // Reopen the scopes still open from earlier lines so this line is balanced
+                for scope in stack.as_slice() {
+                    out.push_str("<span class=\"");
+                    let classes: Vec<String> = scope
+                        .build_string()
+                        .split('.')
+                        .map(|atom| format!("{}{}", CLASS_PREFIX, atom))
+                        .collect();
+                    out.push_str(&classes.join(" "));
+                    out.push_str("\">");
+                }
+                let (html, _) =
+                    line_tokens_to_classed_spans(body, &body_ops, style, &mut stack).ok()?;
+                out.push_str(&html);
+                out.push_str(&"</span>".repeat(stack.len()));
+                for (_, op) in rest {
+                    stack.apply(&op).ok()?;
+                }
+                out.push_str(&line[body.len()..]);
+            }
+            Some(out)
+        }
+    }
+
+    /// Move a trailing newline out of the last region so spans close before it
+    fn split_newline<'a, S: Copy>(regions: &[(S, &'a str)]) -> (Vec<(S, &'a str)>, &'static str) {
+        let mut regions = regions.to_vec();
+        match regions.last_mut() {
+            Some((_, text)) if text.ends_with('\n') => {
+                *text = &text[..text.len() - 1];
+                (regions, "\n")
+            }
+            _ => (regions, ""),
+        }
+    }
+
+    impl Highlighter for SyntectHighlighter {
+        fn highlight(&self, code: &str, lang: Option<&str>) -> String {
+            let highlighted = lang
+                .and_then(Self::syntax)
+                .and_then(|syntax| match self.style {
+                    HighlightStyle::Inline => self.inline(code, syntax),
+                    HighlightStyle::Classes => self.classes(code, syntax),
+                });
+            highlighted.unwrap_or_else(|| escape_html(code))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_inline_styles() {
+            let highlighter = SyntectHighlighter::default();
+            let html = highlighter.highlight("let x = \"<a>\";\nx\n", Some("rust"));
+            assert!(html.starts_with("<span style=\"font-weight:bold;color:#a71d5d;\">let</span>"));
+            assert!(html.contains("&lt;a&gt;"));
+            // Each line closes its own spans
+            for line in html.lines() {
+                assert_eq!(
+                    line.matches("<span").count(),
+                    line.matches("</span>").count()
+                );
+            }
+
+            assert_eq!(highlighter.highlight("<b>", Some("nope")), "&lt;b&gt;");
+            assert_eq!(highlighter.highlight("<b>", None), "&lt;b&gt;");
+            assert!(SyntectHighlighter::new("no-such-theme", HighlightStyle::Inline).is_none());
+        }
+
+        #[test]
+        fn test_classes() {
+            let highlighter =
+                SyntectHighlighter::new("base16-ocean.dark", HighlightStyle::Classes).unwrap();
+            let html = highlighter.highlight("/* a\nb */ fn main() {}\n", Some("rs"));
+            assert!(html.contains("hl-comment"));
+            assert!(
+                html.contains("<span class=\"hl-storage hl-type hl-function hl-rust\">fn</span>")
+            );
+            let lines: Vec<_> = html.lines().collect();
+            assert_eq!(lines.len(), 2);
+            for line in &lines {
+                assert_eq!(
+                    line.matches("<span").count(),
+                    line.matches("</span>").count()
+                );
+            }
+            // The block comment is reopened on its second line
+            assert!(lines[1].contains("hl-comment"));
+            assert!(highlighter.theme_css().contains(".hl-comment"));
+        }
+    }
+}
@@ -0,0 +1,331 @@
+// Image attributes: lazy loading, intrinsic dimensions from local files, captioned figures
+
+use crate::hast::{Element, PropertyValue};
+use fmd_core::{Node, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageOptions {
+    /// `loading="lazy"`
+    pub lazy: bool,
+    /// `decoding="async"`
+    pub decode_async: bool,
+    /// Read `width` and `height` from local files, resolving relative `src`s against this directory;
+    /// only file headers are read, and symlinks out of the directory are skipped
+    pub root: Option<PathBuf>,
+    /// Render a paragraph holding only a titled image as `<figure>` with the title as `<figcaption>`
+    pub figures: bool,
+}
+
+/// Sizes read during one render, by path relative to the root
+pub(crate) type SizeCache = HashMap<PathBuf, Option<(u32, u32)>>;
+
+/// Bytes read for the header of a non-JPEG image; enough for an SVG root tag
+/// after an XML prolog
+const HEADER_BYTES: u64 = 16 * 1024;
+
+/// Add the configured attributes to an `<img>` rendered for `src`
+pub(crate) fn decorate(
+    img: &mut Element,
+    src: &str,
+    options: &ImageOptions,
+    sizes: &mut SizeCache,
+) {
+    let size = options.root.as_ref().and_then(|root| {
+        let relative = relative_path(src)?;
+        *sizes
+            .entry(relative)
+            .or_insert_with_key(|relative| local_size(root, relative))
+    });
+    if let Some((width, height)) = size {
+        for (name, value) in [("width", width), ("height", height)] {
+            img.properties
+                .insert(name.to_string(), PropertyValue::Number(value as f64));
+        }
+    }
+    if options.lazy {
+        img.properties.insert("loading".to_string(), "lazy".into());
+    }
+    if options.decode_async {
+        img.properties
+            .insert("decoding".to_string(), "async".into());
+    }
+}
+
+/// The image of a paragraph that holds nothing else, if it has a title
+pub(crate) fn figure_image(paragraph: &Node) -> Option<&Node> {
+    let mut content = paragraph.children.iter().filter(|child| {
+        child.node_type != NodeType::Text
+            || !child.value.as_deref().unwrap_or_default().trim().is_empty()
+    });
+    let image = content.next()?;
+    let titled = image
+        .title
+        .as_deref()
+        .is_some_and(|title| !title.is_empty());
+    (image.node_type == NodeType::Image && titled && content.next().is_none()).then_some(image)
+}
+
+/// Path of a relative `src`; URLs and paths with `..` are skipped
+fn relative_path(src: &str) -> Option<PathBuf> {
+    if src.starts_with("//") || url::Url::parse(src).is_ok() {
+        return None;
+    }
+    let path = src.split(['?', '#']).next().unwrap_or_default();
+    let path = percent_encoding::percent_decode_str(path)
+        .decode_utf8()
+        .ok()?;
+    let relative = PathBuf::from(path.trim_start_matches('/'));
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        .then_some(relative)
+}
+
+/// Size of the file at `relative` under `root`, read from its header only;
+/// symlinks leading out of `root` are skipped
+fn local_size(root: &Path, relative: &Path) -> Option<(u32, u32)> {
+    let root = root.canonicalize().ok()?;
+    let path = root.join(relative).canonicalize().ok()?;
+    if !path.starts_with(&root) {
+        return None;
+    }
+    let mut file = File::open(path).ok()?;
+    let mut header = Vec::new();
+    (&mut file)
+        .take(HEADER_BYTES)
+        .read_to_end(&mut header)
+        .ok()?;
+    if header.starts_with(b"\xff\xd8") {
+        // Frame headers can follow large metadata segments, which are skipped
+        return jpeg_size(file);
+    }
+    image_size(&header)
+}
+
+/// `(width, height)` from PNG, JPEG, GIF, WebP or SVG data
+pub fn image_size(data: &[u8]) -> Option<(u32, u32)> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(data, 16)?, be32(data, 20)?));
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some((le16(data, 6)?, le16(data, 8)?));
+    }
+    if data.starts_with(b"\xff\xd8") {
+        return jpeg_size(std::io::Cursor::new(data));
+    }
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        return webp_size(data);
+    }
+    svg_size(std::str::from_utf8(data).ok()?)
+}
+
+fn be16(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]) as u32)
+}
+
+fn be32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le16(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]) as u32)
+}
+
+fn le24(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 3)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+}
+
+/// Walk the segments up to the first start-of-frame marker, seeking past their data
+fn jpeg_size(mut reader: impl Read + Seek) -> Option<(u32, u32)> {
+    let mut byte = [0; 1];
+    reader.seek(SeekFrom::Start(2)).ok()?;
+    loop {
+        reader.read_exact(&mut byte).ok()?;
+        if byte[0] != 0xff {
+            return None;
+        }
+        // Markers may be padded with any number of 0xff bytes
+        while byte[0] == 0xff {
+            reader.read_exact(&mut byte).ok()?;
+        }
+        let marker = byte[0];
+        // Segment length, then for frames the sample precision, height and width
+        let mut header = [0; 7];
+        let is_frame = (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+        if is_frame {
+            reader.read_exact(&mut header).ok()?;
+            return Some((be16(&header, 5)?, be16(&header, 3)?));
+        }
+        reader.read_exact(&mut header[..2]).ok()?;
+        let length = be16(&header, 0)? as i64;
+        reader.seek(SeekFrom::Current((length - 2).max(0))).ok()?;
+    }
+}
+
+fn webp_size(data: &[u8]) -> Option<(u32, u32)> {
+    match data.get(12..16)? {
+        b"VP8 " => Some((le16(data, 26)? & 0x3fff, le16(data, 28)? & 0x3fff)),
+        b"VP8L" => {
+            let bits = u32::from_le_bytes(data.get(21..25)?.try_into().ok()?);
+            Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+        }
+        b"VP8X" => Some((le24(data, 24)? + 1, le24(data, 27)? + 1)),
+        _ => None,
+    }
+}
+
+/// `width`/`height` in px on the root `<svg>`, else the `viewBox` size
+fn svg_size(text: &str) -> Option<(u32, u32)> {
+    let start = text.find("<svg")?;
+    let tag = &text[start..start + text[start..].find('>')?];
+    let length = |name: &str| {
+        let value = attribute(tag, name)?;
+        let value = value.trim().strip_suffix("px").unwrap_or(value.trim());
+        value.parse::<f64>().ok().map(|v| v.round() as u32)
+    };
+    if let (Some(width), Some(height)) = (length("width"), length("height")) {
+        return Some((width, height));
+    }
+    let view_box: Vec<f64> = attribute(tag, "viewBox")?
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match view_box[..] {
+        [_, _, width, height] => Some((width.round() as u32, height.round() as u32)),
+        _ => None,
+    }
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(at) = rest.find(name) {
+        let before = rest[..at].chars().last();
+        let after = rest[at + name.len()..].trim_start();
+        rest = &rest[at + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &value[1..];
+        return value.find(quote).map(|end| &value[..end]);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_html, HtmlOptions};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        data.extend(width.to_be_bytes());
+        data.extend(height.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn test_image_size() {
+        assert_eq!(image_size(&png(640, 480)), Some((640, 480)));
+        assert_eq!(image_size(b"GIF89a\x0a\x00\x14\x00"), Some((10, 20)));
+
+        // APP0 segment, then SOF0 with height 0x0100 and width 0x0200
+        let jpeg = b"\xff\xd8\xff\xe0\x00\x04\x00\x00\xff\xc0\x00\x11\x08\x01\x00\x02\x00";
+        assert_eq!(image_size(jpeg), Some((512, 256)));
+
+        let mut vp8x = b"RIFF\0\0\0\0WEBPVP8X\0\0\0\0\0\0\0\0".to_vec();
+        vp8x.extend([99, 0, 0, 49, 0, 0]);
+        assert_eq!(image_size(&vp8x), Some((100, 50)));
+        let mut vp8l = b"RIFF\0\0\0\0WEBPVP8L\0\0\0\0\x2f".to_vec();
+        vp8l.extend(((15u32 << 14) | 31).to_le_bytes());
+        assert_eq!(image_size(&vp8l), Some((32, 16)));
+
+        let svg = r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" width="24px" height='12'>"#;
+        assert_eq!(image_size(svg.as_bytes()), Some((24, 12)));
+        let svg = r#"<svg stroke-width="2" viewBox="0 0 100.4 50" width="100%">"#;
+        assert_eq!(image_size(svg.as_bytes()), Some((100, 50)));
+        assert_eq!(image_size(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_image_options() {
+        let root = std::env::temp_dir().join(format!("fmd-images-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("img")).unwrap();
+        std::fs::write(root.join("img/a b.png"), png(64, 32)).unwrap();
+
+        let input = "![A](img/a%20b.png \"The caption\")\n\n\
+                     Text ![B](/img/a%20b.png) ![C](../secret.png) ![D](https://x.org/d.png)\n";
+        let ast = fmd_core::parse(&fmd_core::Document::new(input), Default::default()).ast;
+        let options = HtmlOptions {
+            images: Some(ImageOptions {
+                lazy: true,
+                decode_async: true,
+                root: Some(root.clone()),
+                figures: true,
+            }),
+            ..Default::default()
+        };
+        let html = to_html(&ast, options);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            html,
+            "<figure><img src=\"img/a%20b.png\" alt=\"A\" width=\"64\" height=\"32\" loading=\"lazy\" \
+             decoding=\"async\"><figcaption>The caption</figcaption></figure>\n\
+             <p>Text <img src=\"/img/a%20b.png\" alt=\"B\" width=\"64\" height=\"32\" loading=\"lazy\" \
+             decoding=\"async\"> <img src=\"../secret.png\" alt=\"C\" loading=\"lazy\" \
+             decoding=\"async\"> <img src=\"https://x.org/d.png\" alt=\"D\" loading=\"lazy\" \
+             decoding=\"async\"></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_local_size() {
+        let dir = std::env::temp_dir().join(format!("fmd-local-size-{}", std::process::id()));
+        let root = dir.join("root");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(dir.join("outside.png"), png(1, 1)).unwrap();
+
+        // A frame header past a metadata segment larger than the header read
+        let mut jpeg = b"\xff\xd8\xff\xe1\xff\xff".to_vec();
+        jpeg.resize(jpeg.len() + 0xfffd, 0);
+        jpeg.extend(b"\xff\xc2\x00\x11\x08\x00\x20\x00\x40");
+        std::fs::write(root.join("big.jpg"), &jpeg).unwrap();
+        assert_eq!(local_size(&root, Path::new("big.jpg")), Some((64, 32)));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.join("outside.png"), root.join("link.png")).unwrap();
+            assert_eq!(local_size(&root, Path::new("link.png")), None);
+        }
+
+        // Each path is read once per render
+        let options = ImageOptions {
+            root: Some(root.clone()),
+            ..Default::default()
+        };
+        let mut sizes = SizeCache::new();
+        let mut img = Element::new("img");
+        decorate(&mut img, "big.jpg", &options, &mut sizes);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let mut again = Element::new("img");
+        decorate(&mut again, "/big.jpg?v=2", &options, &mut sizes);
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(again.properties["width"], PropertyValue::Number(64.0));
+        assert_eq!(img.properties, again.properties);
+    }
+}
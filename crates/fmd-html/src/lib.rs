@@ -1,37 +1,323 @@
 // HTML renderer for faster-md AST
 
+pub mod code;
+mod dom;
+pub mod hast;
+pub mod headings;
+pub mod highlight;
+pub mod images;
+pub mod links;
+pub mod safety;
 pub mod sanitize;
+pub mod to_hast;
 pub mod visitor;
 
-use fmd_core::Node;
+use fmd_core::transforms::assign_heading_ids;
+use fmd_core::visit::{visit, Control};
+use fmd_core::{insert_toc, Node, Position, TocOptions};
+use fmd_slug::Slugger;
 use serde::{Deserialize, Serialize};
-use visitor::HtmlVisitor;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub use code::{CodeRenderer, CodeRenderers};
+pub use fmd_slug::{SlugOptions, SlugStrategy};
+pub use hast::{Element, HastNode, Layout, PropertyValue, Quote, StringifyOptions};
+pub use headings::{AnchorOptions, AnchorPlacement, HeadingIdOptions, SluggerFactory};
+pub use highlight::Highlighter;
+pub use images::ImageOptions;
+pub use links::{ExternalLinkOptions, LinkHook, LinkOptions, MarkdownLinks};
+pub use safety::{RawHtml, SafeOptions};
+pub use to_hast::to_hast;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HtmlOptions {
     pub sanitize: bool,
     pub allow_dangerous_html: bool,
+    /// `<br />` instead of `<br>`, for XHTML consumers such as EPUB
     pub xhtml: bool,
+    /// Quote character around attribute values
+    #[serde(default)]
+    pub quote: Quote,
+    /// Write `'` as `&#39;` everywhere, not only inside single-quoted attributes
+    #[serde(default)]
+    pub escape_apostrophe: bool,
+    /// Whitespace between blocks: as parsed, minified or indented; `pre`,
+    /// `textarea`, `script` and `style` are never touched
+    #[serde(default)]
+    pub layout: Layout,
+    /// Emit `data-sourcepos="line:col-line:col"` on block elements (needs parsing with `position`)
+    #[serde(default)]
+    pub sourcepos: bool,
+    /// Replace `[[toc]]` and `<!-- toc -->` markers with a table of contents;
+    /// turns on `heading_ids` so its links resolve
+    #[serde(default)]
+    pub toc: Option<TocOptions>,
+    /// How ids are generated for headings that do not have one
+    #[serde(default)]
+    pub slug: SlugOptions,
+    /// Render `id`s on headings, optionally with self-link anchors
+    #[serde(default)]
+    pub heading_ids: Option<HeadingIdOptions>,
+    /// Custom slugger, used instead of `slug` when set
+    #[serde(skip)]
+    pub slugger: Option<SluggerFactory>,
+    /// Highlight fenced code; see `SyntectHighlighter` with the `highlight` feature
     #[serde(skip)]
+    pub highlight: Option<Arc<dyn Highlighter>>,
+    /// Custom output for code fences by language, tried before `<pre><code>`
+    #[serde(skip)]
+    pub code_renderers: CodeRenderers,
+    /// How table column alignment, from `Table::align` or a cell's `data.align`, is written
+    #[serde(default)]
+    pub table_align: TableAlign,
+    /// Lazy loading, dimensions from local files and captioned figures for images
+    #[serde(default)]
+    pub images: Option<ImageOptions>,
+    /// Base URL, `.md` link rewriting, external link attributes and a link hook
+    #[serde(default)]
+    pub links: Option<LinkOptions>,
+    /// Check URLs against a protocol allowlist, limit attributes and neutralise
+    /// raw HTML while rendering; works without `sanitize`
+    #[serde(default)]
+    pub safe: Option<SafeOptions>,
+    /// Sanitizer policy used when `sanitize` is on
+    #[serde(default, alias = "sanitizePolicy")]
     pub sanitize_options: sanitize::SanitizeOptions,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableAlign {
+    /// `<td align="center">`
+    #[default]
+    Attribute,
+    /// `<td style="text-align:center">`
+    Style,
+}
+
+impl HtmlOptions {
+    /// Serializer settings taken from these options
+    pub fn stringify_options(&self) -> StringifyOptions {
+        StringifyOptions {
+            xhtml: self.xhtml,
+            quote: self.quote,
+            escape_apostrophe: self.escape_apostrophe,
+            layout: self.layout,
+        }
+    }
+
+    /// A fresh slugger for one document: the custom one if set, else from `slug`
+    pub fn slugger(&self) -> Box<dyn Slugger> {
+        match &self.slugger {
+            Some(factory) => factory.slugger(),
+            None => self.slug.slugger(),
+        }
+    }
+}
+
 pub fn render_html(ast: &Node, options: HtmlOptions) -> String {
     to_html(ast, options)
 }
 
-pub fn to_html(ast: &Node, options: HtmlOptions) -> String {
-    // Generate HTML using visitor pattern
-    let mut visitor = HtmlVisitor::new();
-    visitor.visit(ast);
-    let html = visitor.finish();
+/// Render a Markdown tree: [`to_hast`] followed by [`hast_to_html`]
+pub fn to_html(ast: &Node, mut options: HtmlOptions) -> String {
+    let tree = render_tree(ast, &mut options);
+    hast_to_html(&tree, &options)
+}
 
-    // Sanitize if needed
-    if options.sanitize && !options.allow_dangerous_html {
-        sanitize::sanitize_html(&html, &options.sanitize_options)
-    } else {
+/// [`to_hast`] after the tree changes `options` ask for, such as a table of contents
+fn render_tree(ast: &Node, options: &mut HtmlOptions) -> HastNode {
+    if let Some(mut toc_options) = options.toc.clone() {
+        // The table links to the headings, so they are rendered with their ids
+        let heading_ids = options.heading_ids.get_or_insert_with(Default::default);
+        if toc_options.prefix.is_empty() {
+            toc_options.prefix = heading_ids.prefix.clone();
+        }
+        let mut ast = ast.clone();
+        assign_heading_ids(&mut ast, options.slugger().as_mut());
+        insert_toc(&mut ast, &toc_options);
+        return to_hast(&ast, options);
+    }
+    to_hast(ast, options)
+}
+
+/// CSS properties in highlighter output with inline styles
+const HIGHLIGHT_STYLES: [&str; 5] = [
+    "color",
+    "background-color",
+    "font-weight",
+    "font-style",
+    "text-decoration",
+];
+
+/// Serialize a (possibly transformed) hast tree, sanitizing when enabled
+pub fn hast_to_html(tree: &HastNode, options: &HtmlOptions) -> String {
+    let stringify_options = options.stringify_options();
+    let html = hast::stringify_with(tree, &stringify_options);
+    if !options.sanitize || options.allow_dangerous_html {
+        return html;
+    }
+    let html = sanitize_output(&html, options);
+    // The sanitizer writes plain HTML5; read it back to apply the serializer settings
+    if stringify_options == StringifyOptions::default() {
         html
+    } else {
+        hast::stringify_with(&dom::parse_html(&html), &stringify_options)
+    }
+}
+
+/// Sanitize rendered HTML, allowing what the enabled features write
+fn sanitize_output(html: &str, options: &HtmlOptions) -> String {
+    let mut sanitize_options = options.sanitize_options.clone();
+    if options.sourcepos {
+        sanitize_options
+            .generic_attributes
+            .push("data-sourcepos".to_string());
+    }
+    // Line wrappers from code meta, and highlighter spans; their classes are
+    // left to the policy's class allowlist
+    let code_attributes = [("span", "data-line"), ("code", "data-line-numbers")];
+    if options.highlight.is_some() {
+        // Only what inline-style highlighting writes, not arbitrary CSS on user spans
+        sanitize_options
+            .allowed_styles
+            .entry("span".to_string())
+            .or_default()
+            .extend(HIGHLIGHT_STYLES.map(String::from));
+    }
+    for (tag, attribute) in code_attributes {
+        sanitize_options
+            .tag_attributes
+            .entry(tag.to_string())
+            .or_default()
+            .push(attribute.to_string());
+    }
+    for (tag, attributes) in options.code_renderers.allowed() {
+        sanitize_options.tags.push(tag.to_string());
+        sanitize_options
+            .tag_attributes
+            .entry(tag.to_string())
+            .or_default()
+            .extend(attributes.into_iter().map(String::from));
+    }
+    if options.table_align == TableAlign::Style {
+        for tag in ["th", "td"] {
+            sanitize_options
+                .allowed_styles
+                .entry(tag.to_string())
+                .or_default()
+                .extend(["left", "center", "right"].map(|a| format!("text-align:{}", a)));
+        }
+    }
+    if let Some(images) = &options.images {
+        let attributes = [("loading", images.lazy), ("decoding", images.decode_async)];
+        sanitize_options
+            .tag_attributes
+            .entry("img".to_string())
+            .or_default()
+            .extend(
+                attributes
+                    .into_iter()
+                    .filter(|(_, enabled)| *enabled)
+                    .map(|(name, _)| name.to_string()),
+            );
+    }
+    if let Some(external) = options.links.as_ref().and_then(|l| l.external.as_ref()) {
+        // `rel` is left to the policy's `link_rel`
+        if !external.target.is_empty() {
+            sanitize_options
+                .tag_attributes
+                .entry("a".to_string())
+                .or_default()
+                .push("target".to_string());
+        }
+        if !external.class.is_empty() {
+            sanitize_options
+                .allowed_classes
+                .entry("a".to_string())
+                .or_default()
+                .push(external.class.clone());
+        }
+    }
+    if let Some(heading_ids) = &options.heading_ids {
+        // Only on headings, so raw HTML elsewhere cannot clobber globals with ids
+        for level in 1..=6 {
+            sanitize_options
+                .tag_attributes
+                .entry(format!("h{}", level))
+                .or_default()
+                .push("id".to_string());
+        }
+        if let Some(anchor) = &heading_ids.anchor {
+            sanitize_options
+                .tag_attributes
+                .entry("a".to_string())
+                .or_default()
+                .extend(["aria-hidden", "tabindex"].map(String::from));
+            if !anchor.class.is_empty() {
+                sanitize_options
+                    .allowed_classes
+                    .entry("a".to_string())
+                    .or_default()
+                    .push(anchor.class.clone());
+            }
+        }
     }
+    sanitize::sanitize_html(html, &sanitize_options)
+}
+
+/// Byte range of the rendered HTML that came from a source node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceMapping {
+    /// Offset of the element's start tag
+    pub html_start: usize,
+    /// Offset just past the element's end tag
+    pub html_end: usize,
+    pub position: Position,
+}
+
+/// Render HTML and map block elements in the output back to source positions
+///
+/// Forces `sourcepos` on and records the ranges while serializing the final
+/// tree; with sanitizing, that is the sanitizer's output read back with
+/// html5ever, so the ranges always match the returned string.
+/// Mappings are ordered by `html_start`; nested blocks produce nested ranges.
+pub fn to_html_with_source_map(ast: &Node, options: HtmlOptions) -> (String, Vec<SourceMapping>) {
+    let mut options = HtmlOptions {
+        sourcepos: true,
+        ..options
+    };
+    let tree = render_tree(ast, &mut options);
+    let stringify_options = options.stringify_options();
+    let (html, ranges) = if options.sanitize && !options.allow_dangerous_html {
+        let html = sanitize_output(&hast::stringify_with(&tree, &stringify_options), &options);
+        hast::stringify_with_sourcepos(&dom::parse_html(&html), &stringify_options)
+    } else {
+        hast::stringify_with_sourcepos(&tree, &stringify_options)
+    };
+
+    let mut positions = HashMap::new();
+    visit(ast, |node, _| {
+        if let Some(position) = &node.position {
+            positions.insert(visitor::sourcepos(position), position.clone());
+        }
+        Control::Continue
+    });
+
+    let mut mappings: Vec<SourceMapping> = ranges
+        .into_iter()
+        .filter_map(|(start, end, attr)| {
+            positions.get(&attr).map(|position| SourceMapping {
+                html_start: start,
+                html_end: end,
+                position: position.clone(),
+            })
+        })
+        .collect();
+    mappings.sort_by_key(|m| m.html_start);
+    (html, mappings)
 }
 
 /// Render HTML with default options
@@ -75,4 +361,347 @@ mod tests {
         let html = render_html_default(&ast);
         assert!(html.contains("<p>Hello world</p>"));
     }
+
+    fn parse_with_positions(input: &str) -> Node {
+        let options = fmd_core::ProcessorOptions {
+            gfm: true,
+            position: true,
+            ..Default::default()
+        };
+        fmd_core::parse(&fmd_core::Document::new(input), options).ast
+    }
+
+    #[test]
+    fn test_sourcepos_attributes() {
+        let ast = parse_with_positions("# Title\n\nHello\nworld\n\n> - a\n\n---\n");
+        let html = to_html(
+            &ast,
+            HtmlOptions {
+                sourcepos: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            html,
+            "<h1 data-sourcepos=\"1:1-1:7\">Title</h1>\n\
+             <p data-sourcepos=\"3:1-4:5\">Hello\nworld</p>\n\
+             <blockquote data-sourcepos=\"6:1-6:5\">\n<ul data-sourcepos=\"6:3-6:5\">\n\
+             <li data-sourcepos=\"6:3-6:5\">a</li>\n</ul>\n</blockquote>\n\
+             <hr data-sourcepos=\"8:1-8:3\">\n"
+        );
+    }
+
+    #[test]
+    fn test_source_map_survives_sanitizing() {
+        let ast = parse_with_positions("Intro\n\n- one\n- two\n");
+        let (html, mappings) = to_html_with_source_map(
+            &ast,
+            HtmlOptions {
+                sanitize: true,
+                ..Default::default()
+            },
+        );
+
+        let spans: Vec<_> = mappings
+            .iter()
+            .map(|m| (&html[m.html_start..m.html_end], m.position.start.line))
+            .collect();
+        assert_eq!(spans[0], ("<p data-sourcepos=\"1:1-1:5\">Intro</p>", 1));
+        assert!(spans[1].0.starts_with("<ul") && spans[1].0.ends_with("</ul>"));
+        assert_eq!(spans[3].1, 4);
+        assert_eq!(mappings[3].position.start.offset, 13);
+    }
+
+    #[test]
+    fn test_source_map_with_single_quotes() {
+        let ast = parse_with_positions("Intro\n\n- one\n");
+        for sanitize in [false, true] {
+            let (html, mappings) = to_html_with_source_map(
+                &ast,
+                HtmlOptions {
+                    sanitize,
+                    quote: Quote::Single,
+                    ..Default::default()
+                },
+            );
+            let spans: Vec<_> = mappings
+                .iter()
+                .map(|m| &html[m.html_start..m.html_end])
+                .collect();
+            assert_eq!(
+                spans,
+                vec![
+                    "<p data-sourcepos='1:1-1:5'>Intro</p>",
+                    "<ul data-sourcepos='3:1-3:5'>\n<li data-sourcepos='3:1-3:5'>one</li>\n</ul>",
+                    "<li data-sourcepos='3:1-3:5'>one</li>",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_toc_marker() {
+        let ast = parse_with_positions("# A\n\n<!-- toc -->\n\n## B\n");
+        let options = HtmlOptions {
+            toc: Some(TocOptions {
+                min_depth: 2,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_html(&ast, options),
+            "<h1 id=\"a\">A</h1>\n<ul class=\"toc\">\n<li><a href=\"#b\">B</a></li>\n</ul>\n<h2 id=\"b\">B</h2>\n"
+        );
+    }
+
+    #[test]
+    fn test_toc_slug_options() {
+        let ast = parse_with_positions("[[toc]]\n\n## Über Größe\n\n## Uber Grosse\n");
+        let options = HtmlOptions {
+            toc: Some(TocOptions::default()),
+            slug: SlugOptions {
+                strategy: SlugStrategy::Ascii,
+                separator: "_".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let html = to_html(&ast, options);
+        assert!(html.contains("<a href=\"#uber_grosse\">Über Größe</a>"));
+        assert!(html.contains("<a href=\"#uber_grosse_1\">Uber Grosse</a>"));
+    }
+
+    #[test]
+    fn test_heading_attributes_are_limited() {
+        let options = fmd_core::ProcessorOptions {
+            heading_attributes: true,
+            ..Default::default()
+        };
+        let ast = fmd_core::parse(
+            &fmd_core::Document::new(
+                "# Hi {#top onclick=alert(1) style=position:fixed data-level=1}\n\n\
+                 <div id=\"login\">x</div>\n",
+            ),
+            options,
+        )
+        .ast;
+        assert_eq!(
+            ast.children[0].data["hProperties"],
+            serde_json::json!({ "data-level": "1" })
+        );
+        let options = HtmlOptions {
+            heading_ids: Some(HeadingIdOptions::default()),
+            sanitize: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_html(&ast, options),
+            "<h1 id=\"top\">Hi</h1>\n<div>x</div>\n"
+        );
+    }
+
+    #[test]
+    fn test_heading_ids_and_anchors() {
+        let options = fmd_core::ProcessorOptions {
+            heading_attributes: true,
+            ..Default::default()
+        };
+        let ast = fmd_core::parse(
+            &fmd_core::Document::new("# Intro\n\n## Setup {#install .big}\n\n## Intro\n"),
+            options,
+        )
+        .ast;
+
+        let mut options = HtmlOptions {
+            heading_ids: Some(HeadingIdOptions::default()),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_html(&ast, options.clone()),
+            "<h1 id=\"intro\">Intro</h1>\n<h2 id=\"install\" class=\"big\">Setup</h2>\n<h2 id=\"intro-1\">Intro</h2>\n"
+        );
+
+        options.heading_ids = Some(HeadingIdOptions {
+            prefix: "user-content-".to_string(),
+            anchor: Some(AnchorOptions::default()),
+        });
+        options.sanitize = true;
+        let html = to_html(&ast, options.clone());
+        assert!(html.starts_with(
+            "<h1 id=\"user-content-intro\"><a href=\"#user-content-intro\" class=\"anchor\" \
+             aria-hidden=\"true\" tabindex=\"-1\" rel=\"noopener noreferrer\">#</a>Intro</h1>"
+        ));
+
+        options.sanitize = false;
+        options.heading_ids = Some(HeadingIdOptions {
+            prefix: String::new(),
+            anchor: Some(AnchorOptions {
+                placement: AnchorPlacement::Wrap,
+                class: String::new(),
+                ..Default::default()
+            }),
+        });
+        let html = to_html(&ast, options.clone());
+        assert!(
+            html.contains("<h2 id=\"install\" class=\"big\"><a href=\"#install\">Setup</a></h2>")
+        );
+
+        options
+            .heading_ids
+            .as_mut()
+            .unwrap()
+            .anchor
+            .as_mut()
+            .unwrap()
+            .placement = AnchorPlacement::Append;
+        let html = to_html(&ast, options);
+        assert!(html.contains(
+            ">Setup<a href=\"#install\" aria-hidden=\"true\" tabindex=\"-1\">#</a></h2>"
+        ));
+    }
+
+    #[test]
+    fn test_custom_slugger() {
+        struct Numbered(usize);
+        impl Slugger for Numbered {
+            fn slug(&mut self, _text: &str) -> String {
+                self.0 += 1;
+                format!("section-{}", self.0)
+            }
+        }
+
+        let ast = fmd_core::parse(
+            &fmd_core::Document::new("[[toc]]\n\n# A\n\n# B\n"),
+            Default::default(),
+        )
+        .ast;
+        let options = HtmlOptions {
+            heading_ids: Some(HeadingIdOptions {
+                prefix: "h-".to_string(),
+                anchor: None,
+            }),
+            toc: Some(TocOptions::default()),
+            slugger: Some(SluggerFactory::new(|| Box::new(Numbered(0)))),
+            ..Default::default()
+        };
+        let html = to_html(&ast, options);
+        assert!(html.contains("<a href=\"#h-section-2\">B</a>"));
+        assert!(html.contains("<h1 id=\"h-section-2\">B</h1>"));
+    }
+
+    #[test]
+    fn test_highlighter() {
+        struct Upper;
+        impl Highlighter for Upper {
+            fn highlight(&self, code: &str, lang: Option<&str>) -> String {
+                format!(
+                    "<span class=\"hljs-{} x\" onclick=\"x\">{}</span>",
+                    lang.unwrap_or("plain"),
+                    code.to_uppercase()
+                )
+            }
+        }
+
+        let ast = parse_with_positions(
+            "```rust\nfn main() {}\n```\n\n<span style=\"position:fixed;top:0;color:red\">x</span>\n",
+        );
+        let options = HtmlOptions {
+            sanitize: true,
+            highlight: Some(Arc::new(Upper)),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_html(&ast, options),
+            "<pre><code class=\"language-rust\"><span class=\"hljs-rust\">FN MAIN() {}\n</span></code></pre>\n\
+             <p><span style=\"color:red\">x</span></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_safe_mode() {
+        let ast = parse_with_positions(
+            "[a](javascript:alert(1)) [b](/ok) <b onclick=\"x\">c</b>\n\n\
+             ![i](data:image/png;base64,AA)\n\n<script>alert(1)</script>\n",
+        );
+        let mut options = HtmlOptions {
+            safe: Some(SafeOptions::default()),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_html(&ast, options.clone()),
+            "<p><a>a</a> <a href=\"/ok\">b</a> &lt;b onclick=&quot;x&quot;&gt;c&lt;/b&gt;</p>\n\
+             <p><img src=\"data:image/png;base64,AA\" alt=\"i\"></p>\n\
+             &lt;script&gt;alert(1)&lt;/script&gt;\n"
+        );
+
+        options.safe = Some(SafeOptions {
+            raw_html: RawHtml::Drop,
+            ..Default::default()
+        });
+        assert_eq!(
+            to_html(&ast, options.clone()),
+            "<p><a>a</a> <a href=\"/ok\">b</a> c</p>\n\
+             <p><img src=\"data:image/png;base64,AA\" alt=\"i\"></p>\n"
+        );
+
+        // Raw HTML is trusted, URLs are still checked
+        options.allow_dangerous_html = true;
+        let html = to_html(&ast, options);
+        assert!(html.starts_with("<p><a>a</a> <a href=\"/ok\">b</a> <b onclick=\"x\">c</b></p>"));
+        assert!(html.contains("<script>alert(1)</script>"));
+    }
+
+    #[test]
+    fn test_serializer_options() {
+        let ast = parse_with_positions("> it's\n>\n> ![i](/i.png)\n\n---\n");
+        let mut options = HtmlOptions {
+            xhtml: true,
+            quote: Quote::Single,
+            ..Default::default()
+        };
+        let xhtml = "<blockquote>\n<p>it's</p>\n<p><img src='/i.png' alt='i' /></p>\n</blockquote>\n<hr />\n";
+        assert_eq!(to_html(&ast, options.clone()), xhtml);
+        // The sanitizer's HTML5 output is serialized again
+        options.sanitize = true;
+        assert_eq!(to_html(&ast, options.clone()), xhtml);
+
+        options.xhtml = false;
+        options.escape_apostrophe = true;
+        options.layout = Layout::Compact;
+        assert_eq!(
+            to_html(&ast, options.clone()),
+            "<blockquote><p>it&#39;s</p><p><img src='/i.png' alt='i'></p></blockquote><hr>"
+        );
+        options.escape_apostrophe = false;
+        options.quote = Quote::Double;
+        options.layout = Layout::Pretty;
+        assert_eq!(
+            to_html(&ast, options),
+            "<blockquote>\n  <p>it's</p>\n  <p><img src=\"/i.png\" alt=\"i\"></p>\n</blockquote>\n<hr>\n"
+        );
+    }
+
+    #[test]
+    fn test_hast_transform() {
+        let ast = parse_with_positions("[docs](/docs) and [site](https://example.com)\n");
+        let options = HtmlOptions::default();
+        let mut tree = to_hast(&ast, &options);
+        hast::visit_elements_mut(&mut tree, &mut |element| {
+            let external = element
+                .property("href")
+                .is_some_and(|href| href.starts_with("https://"));
+            if element.tag_name == "a" && external {
+                element
+                    .properties
+                    .insert("rel".to_string(), "noopener".into());
+                element.add_class("external");
+            }
+        });
+        assert_eq!(
+            hast_to_html(&tree, &options),
+            "<p><a href=\"/docs\">docs</a> and <a href=\"https://example.com\" rel=\"noopener\" \
+             class=\"external\">site</a></p>\n"
+        );
+    }
 }
@@ -0,0 +1,261 @@
+// Link rewriting: base URL, `.md` → site URLs, external link attributes and a per-link hook
+
+use crate::hast::{visit_elements_mut, Element, HastNode};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LinkOptions {
+    /// Relative link and image URLs are resolved against this, e.g. `https://example.com/docs/`
+    pub base_url: Option<String>,
+    /// How relative links to `.md` files are rewritten
+    pub markdown_links: MarkdownLinks,
+    /// Attributes for links to other hosts
+    pub external: Option<ExternalLinkOptions>,
+    /// Hosts that count as internal besides the base URL's
+    pub internal_hosts: Vec<String>,
+    /// Called on every `<a>` after the rewriting above
+    #[serde(skip)]
+    pub hook: Option<LinkHook>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkdownLinks {
+    /// Leave `.md` links alone
+    #[default]
+    Keep,
+    /// `guide/setup.md#x` → `guide/setup.html#x`, `README.md` → `index.html`
+    Html,
+    /// `guide/setup.md#x` → `guide/setup#x`, `guide/README.md` → `guide/`
+    Clean,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExternalLinkOptions {
+    /// `target`; empty for none
+    pub target: String,
+    /// `rel`; empty for none
+    pub rel: String,
+    /// Class on the `<a>`; empty for none
+    pub class: String,
+}
+
+impl Default for ExternalLinkOptions {
+    fn default() -> Self {
+        Self {
+            target: "_blank".to_string(),
+            rel: "noopener noreferrer".to_string(),
+            class: "external".to_string(),
+        }
+    }
+}
+
+/// Callback that can change any attribute of a rendered link
+#[derive(Clone)]
+pub struct LinkHook(Arc<dyn Fn(&mut Element) + Send + Sync>);
+
+impl LinkHook {
+    pub fn new(hook: impl Fn(&mut Element) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub fn call(&self, link: &mut Element) {
+        (self.0)(link)
+    }
+}
+
+impl fmt::Debug for LinkHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LinkHook")
+    }
+}
+
+/// Rewrite `href` on links and `src` on images; fragment-only URLs are left alone
+pub(crate) fn rewrite_links(tree: &mut HastNode, options: &LinkOptions) {
+    let base = options.base_url.as_deref().and_then(|b| Url::parse(b).ok());
+    visit_elements_mut(tree, &mut |element| {
+        let property = match element.tag_name.as_str() {
+            "a" => "href",
+            "img" => "src",
+            _ => return,
+        };
+        if let Some(url) = element.property(property) {
+            let mut url = url.to_string();
+            if property == "href" {
+                url = markdown_link(&url, options.markdown_links);
+            }
+            let relative = !url.starts_with('#') && !is_absolute(&url);
+            if let (Some(base), true) = (&base, relative) {
+                if let Ok(resolved) = base.join(&url) {
+                    url = resolved.to_string();
+                }
+            }
+            let external = property == "href" && is_external(&url, base.as_ref(), options);
+            element.properties.insert(property.to_string(), url.into());
+            if let (Some(attributes), true) = (&options.external, external) {
+                for (name, value) in [("target", &attributes.target), ("rel", &attributes.rel)] {
+                    if !value.is_empty() {
+                        element
+                            .properties
+                            .insert(name.to_string(), value.as_str().into());
+                    }
+                }
+                if !attributes.class.is_empty() {
+                    element.add_class(&attributes.class);
+                }
+            }
+        }
+        if let (Some(hook), "a") = (&options.hook, element.tag_name.as_str()) {
+            hook.call(element);
+        }
+    });
+}
+
+/// Whether `url` has a scheme or is protocol-relative
+fn is_absolute(url: &str) -> bool {
+    url.starts_with("//") || Url::parse(url).is_ok()
+}
+
+fn markdown_link(url: &str, mode: MarkdownLinks) -> String {
+    if mode == MarkdownLinks::Keep || is_absolute(url) {
+        return url.to_string();
+    }
+    let split = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(split);
+    let Some(stem) = path
+        .strip_suffix(".md")
+        .or_else(|| path.strip_suffix(".markdown"))
+    else {
+        return url.to_string();
+    };
+    let name = stem.rsplit('/').next().unwrap_or(stem);
+    let dir = &stem[..stem.len() - name.len()];
+    let index = name.eq_ignore_ascii_case("readme") || name == "index";
+    let path = match (mode, index) {
+        (MarkdownLinks::Clean, true) if dir.is_empty() => "./".to_string(),
+        (MarkdownLinks::Clean, true) => dir.to_string(),
+        (MarkdownLinks::Html, true) => format!("{}index.html", dir),
+        (MarkdownLinks::Html, false) => format!("{}.html", stem),
+        _ => stem.to_string(),
+    };
+    format!("{}{}", path, suffix)
+}
+
+fn is_external(url: &str, base: Option<&Url>, options: &LinkOptions) -> bool {
+    let parsed = match url.strip_prefix("//") {
+        Some(rest) => Url::parse(&format!("https://{}", rest)),
+        None => Url::parse(url),
+    };
+    let Ok(parsed) = parsed else {
+        return false;
+    };
+    let Some(host) = parsed
+        .host_str()
+        .filter(|_| parsed.scheme().starts_with("http"))
+    else {
+        return false;
+    };
+    let internal = base.and_then(Url::host_str) == Some(host)
+        || options
+            .internal_hosts
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case(host));
+    !internal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_html, HtmlOptions};
+
+    fn render(input: &str, links: LinkOptions) -> String {
+        let ast = fmd_core::parse(&fmd_core::Document::new(input), Default::default()).ast;
+        let options = HtmlOptions {
+            links: Some(links),
+            ..Default::default()
+        };
+        to_html(&ast, options)
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        let cases = [
+            ("setup.md", "setup.html", "setup"),
+            (
+                "../guide/setup.md#install",
+                "../guide/setup.html#install",
+                "../guide/setup#install",
+            ),
+            ("guide/README.md", "guide/index.html", "guide/"),
+            ("index.md?x=1", "index.html?x=1", "./?x=1"),
+            ("notes.txt", "notes.txt", "notes.txt"),
+            (
+                "https://github.com/a/b.md",
+                "https://github.com/a/b.md",
+                "https://github.com/a/b.md",
+            ),
+        ];
+        for (input, html, clean) in cases {
+            assert_eq!(markdown_link(input, MarkdownLinks::Html), html);
+            assert_eq!(markdown_link(input, MarkdownLinks::Clean), clean);
+            assert_eq!(markdown_link(input, MarkdownLinks::Keep), input);
+        }
+    }
+
+    #[test]
+    fn test_base_url_and_external() {
+        let options = LinkOptions {
+            base_url: Some("https://example.com/docs/".to_string()),
+            markdown_links: MarkdownLinks::Clean,
+            external: Some(ExternalLinkOptions::default()),
+            internal_hosts: vec!["cdn.example.com".to_string()],
+            hook: None,
+        };
+        assert_eq!(
+            render(
+                "[a](guide/setup.md#x) [b](#top) [c](/api) [d](https://rust-lang.org) \
+                 [e](https://cdn.example.com/x) ![i](img/logo.png)\n",
+                options
+            ),
+            "<p><a href=\"https://example.com/docs/guide/setup#x\">a</a> <a href=\"#top\">b</a> \
+             <a href=\"https://example.com/api\">c</a> <a href=\"https://rust-lang.org\" \
+             target=\"_blank\" rel=\"noopener noreferrer\" class=\"external\">d</a> \
+             <a href=\"https://cdn.example.com/x\">e</a> \
+             <img src=\"https://example.com/docs/img/logo.png\" alt=\"i\"></p>\n"
+        );
+
+        // Without a base URL every absolute http(s) link is external
+        let options = LinkOptions {
+            external: Some(ExternalLinkOptions {
+                target: String::new(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            render("[a](//x.org) [b](mailto:a@b.c) [c](./c)\n", options),
+            "<p><a href=\"//x.org\" rel=\"noopener noreferrer\" class=\"external\">a</a> \
+             <a href=\"mailto:a@b.c\">b</a> <a href=\"./c\">c</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_hook() {
+        let options = LinkOptions {
+            hook: Some(LinkHook::new(|link| {
+                let href = link.property("href").unwrap_or_default().to_uppercase();
+                link.properties.insert("dataHref".to_string(), href.into());
+            })),
+            ..Default::default()
+        };
+        assert_eq!(
+            render("[a](/x)\n", options),
+            "<p><a href=\"/x\" data-href=\"/X\">a</a></p>\n"
+        );
+    }
+}
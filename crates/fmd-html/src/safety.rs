@@ -0,0 +1,204 @@
+// Render-time safety: URL protocol allowlist and raw HTML handling (markdown-it `validateLink`)
+
+use crate::hast::{attribute_name, visit_elements_mut, HastNode, PropertyValue};
+use serde::{Deserialize, Serialize};
+
+/// Checks done while converting to hast, without a sanitizer pass over the output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SafeOptions {
+    /// Schemes allowed in URL attributes; relative URLs always pass
+    pub protocols: Vec<String>,
+    /// Allow `data:image/{gif,png,jpeg,webp}` in image sources
+    pub data_images: bool,
+    /// What happens to raw HTML when `allow_dangerous_html` is off
+    pub raw_html: RawHtml,
+}
+
+impl Default for SafeOptions {
+    fn default() -> Self {
+        Self {
+            protocols: ["http", "https", "mailto", "tel"]
+                .map(String::from)
+                .to_vec(),
+            data_images: true,
+            raw_html: RawHtml::Escape,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RawHtml {
+    /// Render the markup as text
+    #[default]
+    Escape,
+    /// Leave it out
+    Drop,
+}
+
+/// Properties holding a URL, by their hast names
+const URL_PROPERTIES: &[&str] = &["href", "src", "cite", "action", "formAction", "poster"];
+
+/// Attributes kept in safe mode besides `data-*` and `aria-*`; event
+/// handlers, other styles and anything else from `data.hProperties` are dropped
+const SAFE_ATTRIBUTES: &[&str] = &[
+    "align", "alt", "checked", "cite", "class", "colspan", "decoding", "dir", "disabled", "height",
+    "href", "id", "lang", "loading", "rel", "reversed", "rowspan", "src", "start", "tabindex",
+    "target", "title", "type", "width",
+];
+
+/// The only styles in safe output, from [`crate::TableAlign::Style`]
+const SAFE_STYLES: &[&str] = &["text-align:left", "text-align:center", "text-align:right"];
+
+const DATA_IMAGES: &[&str] = &["image/gif", "image/png", "image/jpeg", "image/webp"];
+
+/// Whether `url` may be rendered in an attribute of `tag_name`
+pub fn is_safe_url(url: &str, tag_name: &str, options: &SafeOptions) -> bool {
+    // Browsers ignore whitespace and control characters inside the scheme
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_control() && *c != ' ')
+        .collect();
+    let Some((scheme, rest)) = url.split_once(':') else {
+        return true;
+    };
+    let is_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !is_scheme {
+        // `:` after a `/`, `?` or `#` belongs to a relative URL
+        return true;
+    }
+    let scheme = scheme.to_ascii_lowercase();
+    if scheme == "data" && options.data_images && tag_name == "img" {
+        let rest = rest.to_ascii_lowercase();
+        return DATA_IMAGES.iter().any(|mime| rest.starts_with(mime));
+    }
+    options
+        .protocols
+        .iter()
+        .any(|p| p.eq_ignore_ascii_case(&scheme))
+}
+
+/// Remove properties outside [`SAFE_ATTRIBUTES`] and URLs that fail [`is_safe_url`]
+/// from every element
+pub(crate) fn check_properties(tree: &mut HastNode, options: &SafeOptions) {
+    visit_elements_mut(tree, &mut |element| {
+        element
+            .properties
+            .retain(|name, value| match attribute_name(name).as_str() {
+                "style" => matches!(value, PropertyValue::String(style) if is_safe_style(style)),
+                name => is_safe_attribute(name),
+            });
+        for name in URL_PROPERTIES {
+            let unsafe_url = element
+                .property(name)
+                .is_some_and(|url| !is_safe_url(url, &element.tag_name, options));
+            if unsafe_url {
+                element.properties.shift_remove(*name);
+            }
+        }
+    });
+}
+
+fn is_safe_attribute(name: &str) -> bool {
+    let custom = ["data-", "aria-"].iter().any(|prefix| {
+        name.strip_prefix(prefix).is_some_and(|rest| {
+            !rest.is_empty()
+                && rest
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+    });
+    custom || SAFE_ATTRIBUTES.contains(&name)
+}
+
+fn is_safe_style(style: &str) -> bool {
+    style
+        .split(';')
+        .map(|declaration| declaration.replace(char::is_whitespace, ""))
+        .filter(|declaration| !declaration.is_empty())
+        .all(|declaration| SAFE_STYLES.contains(&declaration.as_str()))
+}
+
+/// Hast for a raw HTML node in safe mode
+pub(crate) fn raw_html(
+    value: &str,
+    allow_dangerous_html: bool,
+    options: &SafeOptions,
+) -> Vec<HastNode> {
+    if allow_dangerous_html {
+        return vec![HastNode::Raw {
+            value: value.to_string(),
+        }];
+    }
+    match options.raw_html {
+        RawHtml::Escape => vec![HastNode::text(value)],
+        RawHtml::Drop => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_safe_url() {
+        let options = SafeOptions::default();
+        for url in [
+            "https://example.com",
+            "/docs",
+            "#intro",
+            "./a:b",
+            "?q=a:b",
+            "MAILTO:a@b.c",
+            "",
+        ] {
+            assert!(is_safe_url(url, "a", &options), "{}", url);
+        }
+        for url in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            " java\tscript:alert(1)",
+            "vbscript:x",
+            "file:///etc/passwd",
+            "data:text/html,<script>",
+        ] {
+            assert!(!is_safe_url(url, "a", &options), "{}", url);
+        }
+        assert!(is_safe_url("data:image/png;base64,AA", "img", &options));
+        assert!(!is_safe_url("data:image/png;base64,AA", "a", &options));
+        assert!(!is_safe_url("data:image/svg+xml,<svg>", "img", &options));
+    }
+
+    #[test]
+    fn test_check_properties() {
+        use crate::hast::{stringify, Element};
+
+        let mut tree: HastNode = Element::new("h1")
+            .with_property("onclick", "alert(1)")
+            .with_property("onMouseOver", "alert(1)")
+            .with_property("style", "color:red")
+            .with_property("data-x onload", "alert(1)")
+            .with_property("dataLevel", "1")
+            .with_property("aria-label", "a")
+            .with_property("id", "top")
+            .with_children(vec![
+                Element::new("th")
+                    .with_property("style", "text-align: center;")
+                    .into(),
+                Element::new("a")
+                    .with_property("href", "javascript:x")
+                    .into(),
+            ])
+            .into();
+        check_properties(&mut tree, &SafeOptions::default());
+        assert_eq!(
+            stringify(&tree),
+            "<h1 data-level=\"1\" aria-label=\"a\" id=\"top\">\
+             <th style=\"text-align: center;\"></th><a></a></h1>"
+        );
+    }
+}
@@ -1,51 +1,290 @@
 // HTML sanitization using ammonia
 
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
 /// Sanitize HTML to prevent XSS attacks
 pub fn sanitize_html(html: &str, options: &SanitizeOptions) -> String {
-    if !options.enabled {
+    if !options.enabled || options.allow_dangerous_html {
         return html.to_string();
     }
 
-    // For now, use ammonia's defaults with basic configuration
-    // The API has changed significantly and needs more investigation
-    if options.allow_dangerous_html {
-        // Don't sanitize at all
-        html.to_string()
-    } else {
-        // Use ammonia defaults which are safe
-        ammonia::clean(html)
+    let mut builder = ammonia::Builder::default();
+    if let Some(tags) = &options.allowed_tags {
+        builder.tags(tags.iter().map(String::as_str).collect());
+    }
+    builder.add_tags(options.tags.iter().map(String::as_str));
+    if let Some(attributes) = &options.allowed_attributes {
+        let mut generic = HashSet::new();
+        let mut per_tag: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for (tag, names) in attributes {
+            let names = names.iter().map(String::as_str);
+            match tag.as_str() {
+                "*" => generic.extend(names),
+                tag => per_tag.entry(tag).or_default().extend(names),
+            }
+        }
+        builder.generic_attributes(generic).tag_attributes(per_tag);
+    }
+    builder.add_generic_attributes(options.generic_attributes.iter().map(String::as_str));
+    for (tag, attributes) in &options.tag_attributes {
+        builder.add_tag_attributes(tag, attributes.iter().map(String::as_str));
+    }
+    for tag in options.allowed_classes.keys() {
+        builder.add_tag_attributes(tag, ["class"]);
     }
+    for tag in options.allowed_styles.keys() {
+        builder.add_tag_attributes(tag, ["style"]);
+    }
+    if options.link_rel.is_some() {
+        // ammonia refuses a kept `rel` when it injects its own
+        builder.rm_generic_attributes(["rel"]);
+        builder.rm_tag_attributes("a", ["rel"]);
+    }
+    builder.link_rel(options.link_rel.as_deref());
+    builder.id_prefix(options.id_prefix.as_deref());
+    if let Some(schemes) = &options.url_schemes {
+        builder.url_schemes(schemes.iter().map(String::as_str).collect());
+    }
+
+    // Allowing `script` or `style` means keeping them, not dropping their content
+    let allowed = builder.clone_tags();
+    let kept: Vec<&str> = builder
+        .clone_clean_content_tags()
+        .into_iter()
+        .filter(|tag| allowed.contains(tag) || options.tag_attributes.contains_key(*tag))
+        .collect();
+    builder.rm_clean_content_tags(kept);
+
+    if !options.allowed_classes.is_empty() || !options.allowed_styles.is_empty() {
+        let classes = options.allowed_classes.clone();
+        let styles = options.allowed_styles.clone();
+        builder.attribute_filter(move |element, attribute, value| {
+            let kept: Vec<&str> = match (attribute, classes.get(element), styles.get(element)) {
+                ("class", Some(patterns), _) => value
+                    .split_whitespace()
+                    .filter(|class| patterns.iter().any(|p| class_matches(p, class)))
+                    .collect(),
+                ("style", _, Some(declarations)) => value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|d| style_matches(declarations, d))
+                    .collect(),
+                _ => return Some(value.into()),
+            };
+            let separator = if attribute == "class" { " " } else { ";" };
+            (!kept.is_empty()).then(|| Cow::Owned(kept.join(separator)))
+        });
+    }
+
+    let html = match options.disallowed {
+        Disallowed::Strip => Cow::Borrowed(html),
+        Disallowed::Escape => escape_disallowed(html, &allowed),
+    };
+    builder.clean(&html).to_string()
+}
+
+/// `language-*` matches any class starting with `language-`; other patterns match exactly
+fn class_matches(pattern: &str, class: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => class.starts_with(prefix),
+        None => class == pattern,
+    }
+}
+
+/// Whether a `property:value` declaration is allowed: `property` allows any
+/// plain value (no functions, quotes or escapes), `property:value` only that one
+fn style_matches(allowed: &[String], declaration: &str) -> bool {
+    let Some((property, value)) = declaration.split_once(':') else {
+        return false;
+    };
+    let (property, value) = (property.trim(), value.trim());
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '#' | '%' | '.' | ',' | '-' | ' '));
+    allowed.iter().any(|entry| match entry.split_once(':') {
+        Some((p, v)) => p.eq_ignore_ascii_case(property) && v.eq_ignore_ascii_case(value),
+        None => plain && entry.eq_ignore_ascii_case(property),
+    })
 }
 
-#[derive(Debug, Clone)]
+/// Turn the `<` of tags outside `allowed` into `&lt;` so they survive as text
+fn escape_disallowed<'a>(html: &'a str, allowed: &HashSet<&str>) -> Cow<'a, str> {
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for (i, _) in html.match_indices('<') {
+        let rest = &html[i + 1..];
+        let name = rest.strip_prefix('/').unwrap_or(rest);
+        let end = name
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(name.len());
+        let name = name[..end].to_ascii_lowercase();
+        let is_tag = name.starts_with(|c: char| c.is_ascii_alphabetic());
+        if is_tag && !allowed.contains(name.as_str()) {
+            out.push_str(&html[last..i]);
+            out.push_str("&lt;");
+            last = i + 1;
+        }
+    }
+    if last == 0 {
+        return Cow::Borrowed(html);
+    }
+    out.push_str(&html[last..]);
+    Cow::Owned(out)
+}
+
+/// What happens to elements outside the allowlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Disallowed {
+    /// Drop the tags but keep their text; `script` and `style` lose their content too
+    #[default]
+    Strip,
+    /// Show the tags as literal text
+    Escape,
+}
+
+/// Sanitizer policy: `None` fields keep ammonia's defaults, the extras add to them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct SanitizeOptions {
     pub enabled: bool,
     pub allow_dangerous_html: bool,
+    /// Replaces the default set of kept elements
+    pub allowed_tags: Option<Vec<String>>,
+    /// Replaces the default attributes, by tag; `*` applies to every element
+    pub allowed_attributes: Option<HashMap<String, Vec<String>>>,
+    /// Extra elements kept
+    pub tags: Vec<String>,
+    /// Extra attributes kept on every element
+    pub generic_attributes: Vec<String>,
+    /// Extra attributes kept on specific elements, e.g. `span` → `class`, `style`
+    pub tag_attributes: HashMap<String, Vec<String>>,
+    /// Classes kept on the listed elements, e.g. `code` → `language-*`; a trailing
+    /// `*` matches a prefix. Listed elements may carry `class`, filtered by these
+    /// patterns; elements not listed keep `class` only if it is otherwise allowed.
+    pub allowed_classes: HashMap<String, Vec<String>>,
+    /// CSS declarations kept in `style` on the listed elements, e.g. `span` → `color`;
+    /// a bare property allows any plain value, `property:value` exactly that value
+    pub allowed_styles: HashMap<String, Vec<String>>,
+    /// Schemes allowed in URLs, replacing the default list; relative URLs are kept
+    pub url_schemes: Option<Vec<String>>,
+    /// `rel` set on every link, replacing any in the input; `None` keeps links as they are
+    pub link_rel: Option<String>,
+    /// Prepended to every kept `id`, so user content cannot clobber page ids
+    pub id_prefix: Option<String>,
+    pub disallowed: Disallowed,
 }
 
+/// Classes fmd itself renders: highlighting, code meta, math, directives, toc, footnotes
+const RENDERED_CLASSES: &[(&str, &[&str])] = &[
+    ("a", &["anchor", "data-footnote-backref"]),
+    ("code", &["language-*"]),
+    (
+        "span",
+        &[
+            "hljs",
+            "hljs-*",
+            "hl-*",
+            "line",
+            "highlighted",
+            "ins",
+            "del",
+            "math",
+            "math-inline",
+        ],
+    ),
+    ("pre", &["mermaid"]),
+    ("div", &["math", "math-display", "directive"]),
+    ("figure", &["code-block"]),
+    ("figcaption", &["code-title"]),
+    ("ul", &["toc", "contains-task-list"]),
+    ("li", &["task-list-item"]),
+    ("h2", &["sr-only"]),
+    ("section", &["footnotes"]),
+];
+
+const STRICT_TAGS: &[&str] = &[
+    "p",
+    "br",
+    "hr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "pre",
+    "code",
+    "ul",
+    "ol",
+    "li",
+    "strong",
+    "em",
+    "b",
+    "i",
+    "del",
+    "s",
+    "sub",
+    "sup",
+    "table",
+    "thead",
+    "tbody",
+    "tr",
+    "th",
+    "td",
+];
+
 impl Default for SanitizeOptions {
+    /// ammonia's defaults plus the classes fmd renders
     fn default() -> Self {
+        let allowed_classes = RENDERED_CLASSES
+            .iter()
+            .map(|(tag, classes)| {
+                let classes = classes.iter().map(|c| c.to_string()).collect();
+                (tag.to_string(), classes)
+            })
+            .collect();
         Self {
             enabled: true,
             allow_dangerous_html: false,
+            allowed_tags: None,
+            allowed_attributes: None,
+            tags: Vec::new(),
+            generic_attributes: Vec::new(),
+            tag_attributes: HashMap::new(),
+            allowed_classes,
+            allowed_styles: HashMap::new(),
+            url_schemes: None,
+            link_rel: Some("noopener noreferrer".to_string()),
+            id_prefix: None,
+            disallowed: Disallowed::Strip,
         }
     }
 }
 
 impl SanitizeOptions {
-    /// Create strict sanitization options
+    /// Create strict sanitization options: text formatting only, no links, images or attributes
     pub fn strict() -> Self {
         Self {
-            enabled: true,
-            allow_dangerous_html: false,
+            allowed_tags: Some(STRICT_TAGS.iter().map(|t| t.to_string()).collect()),
+            allowed_attributes: Some(HashMap::new()),
+            allowed_classes: HashMap::new(),
+            url_schemes: Some(Vec::new()),
+            link_rel: None,
+            ..Self::default()
         }
     }
 
-    /// Create permissive sanitization options (still sanitizes)
+    /// Create permissive sanitization options (still sanitizes): any `class` and `id`
     pub fn permissive() -> Self {
         Self {
-            enabled: true,
-            allow_dangerous_html: false,
+            generic_attributes: vec!["class".to_string(), "id".to_string()],
+            allowed_classes: HashMap::new(),
+            ..Self::default()
         }
     }
 
@@ -54,6 +293,18 @@ impl SanitizeOptions {
         Self {
             enabled: false,
             allow_dangerous_html: true,
+            ..Self::default()
+        }
+    }
+
+    /// `strict`, `default`, `permissive` or `disabled`
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "strict" => Some(Self::strict()),
+            "default" => Some(Self::default()),
+            "permissive" => Some(Self::permissive()),
+            "disabled" => Some(Self::disabled()),
+            _ => None,
         }
     }
 }
@@ -75,7 +326,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Strict sanitization not yet fully implemented"]
     fn test_strict_sanitization() {
         let html = "<p>Hello <a href='http://example.com'>link</a></p>";
         let sanitized = sanitize_html(html, &SanitizeOptions::strict());
@@ -98,4 +348,96 @@ mod tests {
         let sanitized = sanitize_html(html, &SanitizeOptions::disabled());
         assert_eq!(sanitized, html);
     }
+
+    #[test]
+    fn test_class_allowlist() {
+        let html = "<pre><code class=\"language-rust evil\"><span class=\"hljs-keyword x\">fn</span></code></pre><p class=\"x\">a</p>";
+        assert_eq!(
+            quick_sanitize(html),
+            "<pre><code class=\"language-rust\"><span class=\"hljs-keyword\">fn</span></code></pre><p>a</p>"
+        );
+        assert_eq!(
+            sanitize_html(html, &SanitizeOptions::permissive()),
+            html.replace('\'', "\"")
+        );
+        assert_eq!(
+            sanitize_html(html, &SanitizeOptions::strict()),
+            "<pre><code>fn</code></pre><p>a</p>"
+        );
+    }
+
+    #[test]
+    fn test_style_allowlist() {
+        let options = SanitizeOptions {
+            allowed_styles: HashMap::from([
+                ("span".to_string(), vec!["color".to_string()]),
+                ("td".to_string(), vec!["text-align:center".to_string()]),
+            ]),
+            ..Default::default()
+        };
+        let html = "<span style=\"position:fixed; color: #a71d5d;color:url(x)\">a</span>\
+                    <p style=\"color:red\">b</p><table><tbody><tr>\
+                    <td style=\"text-align:center\">c</td><td style=\"text-align:justify\">d</td>\
+                    </tr></tbody></table>";
+        assert_eq!(
+            sanitize_html(html, &options),
+            "<span style=\"color: #a71d5d\">a</span><p>b</p><table><tbody><tr>\
+             <td style=\"text-align:center\">c</td><td>d</td></tr></tbody></table>"
+        );
+    }
+
+    #[test]
+    fn test_url_schemes_rel_and_id_prefix() {
+        let options = SanitizeOptions {
+            url_schemes: Some(vec!["https".to_string()]),
+            link_rel: Some("nofollow".to_string()),
+            id_prefix: Some("user-content-".to_string()),
+            generic_attributes: vec!["id".to_string()],
+            ..Default::default()
+        };
+        let html = "<h1 id=\"top\">T</h1><a href=\"https://a.b\">x</a><a href=\"ftp://a.b\">y</a><a href=\"/rel\">z</a>";
+        assert_eq!(
+            sanitize_html(html, &options),
+            "<h1 id=\"user-content-top\">T</h1><a href=\"https://a.b\" rel=\"nofollow\">x</a>\
+             <a rel=\"nofollow\">y</a><a href=\"/rel\" rel=\"nofollow\">z</a>"
+        );
+
+        let options = SanitizeOptions {
+            link_rel: None,
+            tag_attributes: HashMap::from([("a".to_string(), vec!["rel".to_string()])]),
+            ..Default::default()
+        };
+        assert_eq!(
+            sanitize_html("<a href=\"/x\" rel=\"me\">x</a>", &options),
+            "<a href=\"/x\" rel=\"me\">x</a>"
+        );
+    }
+
+    #[test]
+    fn test_escape_disallowed() {
+        let options = SanitizeOptions {
+            disallowed: Disallowed::Escape,
+            ..Default::default()
+        };
+        assert_eq!(
+            sanitize_html("<p>a <script>x()</script> <b>b</b> 1 < 2</p>", &options),
+            "<p>a &lt;script&gt;x()&lt;/script&gt; <b>b</b> 1 &lt; 2</p>"
+        );
+    }
+
+    #[test]
+    fn test_policy_from_json() {
+        let options: SanitizeOptions = serde_json::from_str(
+            r#"{"allowedTags": ["p", "script"], "allowedClasses": {"p": ["note"]}, "linkRel": null}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            sanitize_html(
+                "<p class=\"note x\">a<script>b</script><em>c</em></p>",
+                &options
+            ),
+            "<p class=\"note\">a<script>b</script>c</p>"
+        );
+        assert_eq!(SanitizeOptions::preset("nope"), None);
+    }
 }
@@ -0,0 +1,691 @@
+// mdast → hast conversion (mdast-util-to-hast)
+
+use crate::code;
+use crate::hast::{Element, HastNode, PropertyValue};
+use crate::headings;
+use crate::images;
+use crate::links;
+use crate::safety;
+use crate::visitor::sourcepos;
+use crate::{HtmlOptions, TableAlign};
+use fmd_core::{Node, NodeType};
+use fmd_slug::Slugger;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Prefix for generated ids, so they cannot clobber ids on the host page
+const CLOBBER_PREFIX: &str = "user-content-";
+
+/// Convert a Markdown tree to a hast tree
+///
+/// Honours `data.hName`, `data.hProperties` and `data.hChildren` on any node,
+/// keeps raw HTML as `raw` nodes and collects footnote definitions into a
+/// GitHub-style `<section data-footnotes>` at the end. A non-root node is
+/// converted as a fragment. With `heading_ids`, headings get their `data.id`
+/// or a slug from [`HtmlOptions::slugger`]. `links` rewrites link and image
+/// URLs before the checks of `safe`. With `safe`, unsafe URLs and
+/// attributes outside an allowlist are removed and raw HTML is escaped or dropped unless `allow_dangerous_html`.
+pub fn to_hast(ast: &Node, options: &HtmlOptions) -> HastNode {
+    let mut state = State {
+        options,
+        definitions: HashMap::new(),
+        footnotes: Vec::new(),
+        reference_counts: HashMap::new(),
+        slugger: options.heading_ids.as_ref().map(|_| options.slugger()),
+        image_sizes: images::SizeCache::new(),
+    };
+    collect_definitions(ast, &mut state.definitions);
+
+    let mut children = if ast.node_type == NodeType::Root {
+        state.flow(&ast.children)
+    } else {
+        state.flow(std::slice::from_ref(ast))
+    };
+    if is_newline(children.first()) {
+        children.remove(0);
+    }
+    if let Some(section) = state.footnote_section() {
+        children.push(section);
+        children.push(newline());
+    }
+    let mut tree = HastNode::Root { children };
+    if let Some(links) = &options.links {
+        links::rewrite_links(&mut tree, links);
+    }
+    if let Some(safe) = &options.safe {
+        safety::check_properties(&mut tree, safe);
+    }
+    tree
+}
+
+struct State<'a> {
+    options: &'a HtmlOptions,
+    definitions: HashMap<String, &'a Node>,
+    /// Footnote keys in order of first reference
+    footnotes: Vec<String>,
+    reference_counts: HashMap<String, usize>,
+    /// Set when rendering heading ids
+    slugger: Option<Box<dyn Slugger>>,
+    image_sizes: images::SizeCache,
+}
+
+impl<'a> State<'a> {
+    /// Block children, each on its own line
+    fn flow(&mut self, children: &'a [Node]) -> Vec<HastNode> {
+        let mut out = Vec::new();
+        for child in children {
+            let converted = self.node(child);
+            if converted.is_empty() {
+                continue;
+            }
+            if is_flow(child) {
+                if !ends_with_newline(&out) {
+                    out.push(newline());
+                }
+                out.extend(converted);
+                out.push(newline());
+            } else {
+                out.extend(converted);
+            }
+        }
+        out
+    }
+
+    fn inlines(&mut self, children: &'a [Node]) -> Vec<HastNode> {
+        children.iter().flat_map(|child| self.node(child)).collect()
+    }
+
+    /// Block element: records the position and adds `data-sourcepos` when enabled
+    fn block(&self, node: &Node, mut element: Element) -> HastNode {
+        if let (Some(position), true) = (&node.position, self.options.sourcepos) {
+            element
+                .properties
+                .insert("dataSourcepos".to_string(), sourcepos(position).into());
+        }
+        element.position = node.position.clone();
+        element.into()
+    }
+
+    fn inline(&self, node: &Node, mut element: Element) -> HastNode {
+        element.position = node.position.clone();
+        element.into()
+    }
+
+    fn node(&mut self, node: &'a Node) -> Vec<HastNode> {
+        let converted = self.convert(node);
+        apply_data(node, converted)
+    }
+
+    fn convert(&mut self, node: &'a Node) -> Vec<HastNode> {
+        let value = node.value.as_deref().unwrap_or_default();
+        match node.node_type {
+            NodeType::Root => self.flow(&node.children),
+            NodeType::Paragraph => {
+                let figures = self.options.images.as_ref().is_some_and(|i| i.figures);
+                if let Some(image) = images::figure_image(node).filter(|_| figures) {
+                    let mut img = self.node(image);
+                    if let Some(HastNode::Element(img)) = img.first_mut() {
+                        img.properties.shift_remove("title");
+                    }
+                    let title = image.title.as_deref().unwrap_or_default();
+                    let caption =
+                        Element::new("figcaption").with_children(vec![HastNode::text(title)]);
+                    img.push(caption.into());
+                    let figure = Element::new("figure").with_children(img);
+                    return vec![self.block(node, figure)];
+                }
+                let p = Element::new("p").with_children(self.inlines(&node.children));
+                vec![self.block(node, p)]
+            }
+            NodeType::Heading => {
+                let tag = format!("h{}", node.depth.unwrap_or(1).clamp(1, 6));
+                let mut h = Element::new(&tag).with_children(self.inlines(&node.children));
+                if let (Some(slugger), Some(heading_ids)) =
+                    (self.slugger.as_mut(), &self.options.heading_ids)
+                {
+                    let id = match node.data.get("id").and_then(Value::as_str) {
+                        Some(id) => slugger.unique(id),
+                        None => slugger.slug(&node.text_content()),
+                    };
+                    headings::decorate(&mut h, &id, heading_ids);
+                }
+                vec![self.block(node, h)]
+            }
+            NodeType::ThematicBreak => vec![self.block(node, Element::new("hr"))],
+            NodeType::Blockquote => {
+                let q = Element::new("blockquote").with_children(self.container(&node.children));
+                vec![self.block(node, q)]
+            }
+            NodeType::List => {
+                let ordered = node.ordered.unwrap_or(false);
+                let mut list = Element::new(if ordered { "ol" } else { "ul" });
+                if let Some(start) = node.start.filter(|&start| ordered && start != 1) {
+                    list.properties
+                        .insert("start".to_string(), PropertyValue::Number(start as f64));
+                }
+                list.children = self.container(&node.children);
+                vec![self.block(node, list)]
+            }
+            NodeType::ListItem => {
+                let mut children = self.flow(&node.children);
+                if let Some(checked) = node.checked {
+                    let checkbox = Element::new("input")
+                        .with_property("type", "checkbox")
+                        .with_property("checked", checked)
+                        .with_property("disabled", true);
+                    // Loose task items keep the checkbox inside their first paragraph
+                    let target = match children.iter_mut().find(|c| !is_newline(Some(c))) {
+                        Some(HastNode::Element(p)) if p.tag_name == "p" => &mut p.children,
+                        _ => &mut children,
+                    };
+                    target.splice(0..0, [checkbox.into(), HastNode::text(" ")]);
+                }
+                vec![self.block(node, Element::new("li").with_children(children))]
+            }
+            NodeType::Code => match self.options.code_renderers.render(node) {
+                Some(html) => vec![HastNode::Raw { value: html }],
+                None => vec![self.block(node, code::code_block(node, self.options))],
+            },
+            NodeType::Html => match &self.options.safe {
+                Some(safe) => safety::raw_html(value, self.options.allow_dangerous_html, safe),
+                None => vec![HastNode::Raw {
+                    value: value.to_string(),
+                }],
+            },
+            NodeType::Text => vec![HastNode::text(value)],
+            NodeType::Emphasis => self.wrap(node, "em"),
+            NodeType::Strong => self.wrap(node, "strong"),
+            NodeType::Delete => self.wrap(node, "del"),
+            NodeType::InlineCode => {
+                let code = Element::new("code").with_children(vec![HastNode::text(value)]);
+                vec![self.inline(node, code)]
+            }
+            NodeType::Break => vec![self.inline(node, Element::new("br")), newline()],
+            NodeType::Link => {
+                let mut a = Element::new("a");
+                if let Some(url) = &node.url {
+                    a.properties.insert("href".to_string(), url.as_str().into());
+                }
+                if let Some(title) = &node.title {
+                    a.properties
+                        .insert("title".to_string(), title.as_str().into());
+                }
+                a.children = self.inlines(&node.children);
+                vec![self.inline(node, a)]
+            }
+            NodeType::Image => {
+                let mut img = Element::new("img");
+                if let Some(url) = &node.url {
+                    img.properties
+                        .insert("src".to_string(), url.as_str().into());
+                }
+                let alt = node.alt.clone().unwrap_or_else(|| node.text_content());
+                img.properties.insert("alt".to_string(), alt.into());
+                if let Some(title) = &node.title {
+                    img.properties
+                        .insert("title".to_string(), title.as_str().into());
+                }
+                if let (Some(options), Some(url)) = (&self.options.images, &node.url) {
+                    images::decorate(&mut img, url, options, &mut self.image_sizes);
+                }
+                vec![self.inline(node, img)]
+            }
+            // References are expected to be resolved before rendering
+            NodeType::LinkReference | NodeType::ImageReference => self.inlines(&node.children),
+            NodeType::Table => vec![self.table(node)],
+            NodeType::TableRow => self.table_row(node, is_header_row(node), &[]),
+            NodeType::TableCell => {
+                let align = node.data.get("align").and_then(Value::as_str);
+                self.table_cell(node, is_header(node), align)
+            }
+            NodeType::FootnoteReference => self.footnote_reference(node),
+            NodeType::MdxjsEsm
+            | NodeType::MdxJsxFlowElement
+            | NodeType::MdxJsxTextElement
+            | NodeType::MdxFlowExpression
+            | NodeType::MdxTextExpression => vec![HastNode::Comment {
+                value: format!(" MDX: {:?} ", node.node_type),
+            }],
+            NodeType::ContainerDirective => {
+                let mut div = Element::new("div").with_children(self.flow(&node.children));
+                div.add_class("directive");
+                vec![self.inline(node, div)]
+            }
+            NodeType::LeafDirective | NodeType::TextDirective => {
+                let mut div = Element::new("div").with_children(self.inlines(&node.children));
+                div.add_class("directive");
+                vec![self.inline(node, div)]
+            }
+            NodeType::Math => vec![self.block(node, code::math_display(value))],
+            NodeType::InlineMath => {
+                let mut span = Element::new("span").with_children(vec![HastNode::text(value)]);
+                span.add_class("math");
+                span.add_class("math-inline");
+                vec![self.inline(node, span)]
+            }
+            // Rendered in the footnote section, or not at all
+            NodeType::FootnoteDefinition
+            | NodeType::Definition
+            | NodeType::Yaml
+            | NodeType::FrontMatter => Vec::new(),
+        }
+    }
+
+    fn wrap(&mut self, node: &'a Node, tag: &str) -> Vec<HastNode> {
+        let element = Element::new(tag).with_children(self.inlines(&node.children));
+        vec![self.inline(node, element)]
+    }
+
+    /// Flow content of an element that always opens on a new line
+    fn container(&mut self, children: &'a [Node]) -> Vec<HastNode> {
+        let mut out = self.flow(children);
+        if out.is_empty() {
+            out.push(newline());
+        }
+        out
+    }
+
+    fn table(&mut self, node: &'a Node) -> HastNode {
+        let mut rows = node
+            .children
+            .iter()
+            .filter(|row| row.node_type == NodeType::TableRow);
+        let mut children = vec![newline()];
+        let mut section = |tag: &str, rows: Vec<HastNode>| {
+            if rows.is_empty() {
+                return;
+            }
+            let mut content = vec![newline()];
+            for row in rows {
+                content.push(row);
+                content.push(newline());
+            }
+            children.push(Element::new(tag).with_children(content).into());
+            children.push(newline());
+        };
+
+        // Rows marked by the parser, else the first row as in mdast
+        let marked = rows.clone().any(is_header_row);
+        let align = node.align.as_deref().unwrap_or_default();
+        let (mut head, mut body) = (Vec::new(), Vec::new());
+        for (index, row) in rows.by_ref().enumerate() {
+            let header = if marked {
+                is_header_row(row)
+            } else {
+                index == 0
+            };
+            let converted = apply_data(row, self.table_row(row, header, align));
+            if header {
+                head.extend(converted);
+            } else {
+                body.extend(converted);
+            }
+        }
+        section("thead", head);
+        section("tbody", body);
+        self.block(node, Element::new("table").with_children(children))
+    }
+
+    /// `<tr>` of `<th>` or `<td>`; a cell's own `data.align` wins over the table's `align`
+    fn table_row(&mut self, row: &'a Node, header: bool, align: &[String]) -> Vec<HastNode> {
+        let mut cells = Vec::new();
+        for (index, cell) in row.children.iter().enumerate() {
+            let cell_align = cell.data.get("align").and_then(Value::as_str);
+            let align = cell_align.or(align.get(index).map(String::as_str));
+            let converted = self.table_cell(cell, header, align);
+            cells.extend(apply_data(cell, converted));
+        }
+        vec![self.block(row, Element::new("tr").with_children(cells))]
+    }
+
+    fn table_cell(&mut self, cell: &'a Node, header: bool, align: Option<&str>) -> Vec<HastNode> {
+        let tag = if header { "th" } else { "td" };
+        let mut element = Element::new(tag).with_children(self.inlines(&cell.children));
+        if let Some(align) = align.filter(|a| matches!(*a, "left" | "center" | "right")) {
+            match self.options.table_align {
+                TableAlign::Attribute => {
+                    element.properties.insert("align".to_string(), align.into())
+                }
+                TableAlign::Style => element
+                    .properties
+                    .insert("style".to_string(), format!("text-align:{}", align).into()),
+            };
+        }
+        vec![self.block(cell, element)]
+    }
+
+    fn footnote_reference(&mut self, node: &Node) -> Vec<HastNode> {
+        let Some(key) = footnote_key(node).filter(|k| self.definitions.contains_key(k)) else {
+            let label = node.identifier.as_deref().unwrap_or_default();
+            return vec![HastNode::text(&format!("[^{}]", label))];
+        };
+        let number = match self.footnotes.iter().position(|k| *k == key) {
+            Some(index) => index + 1,
+            None => {
+                self.footnotes.push(key.clone());
+                self.footnotes.len()
+            }
+        };
+        let count = self.reference_counts.entry(key.clone()).or_insert(0);
+        *count += 1;
+
+        let a = Element::new("a")
+            .with_property("href", format!("#{}fn-{}", CLOBBER_PREFIX, key))
+            .with_property("id", reference_id(&key, *count))
+            .with_property("dataFootnoteRef", true)
+            .with_property("ariaDescribedBy", "footnote-label")
+            .with_children(vec![HastNode::text(&number.to_string())]);
+        vec![self.inline(node, Element::new("sup").with_children(vec![a.into()]))]
+    }
+
+    /// `<section data-footnotes>` listing referenced definitions in reference order
+    fn footnote_section(&mut self) -> Option<HastNode> {
+        if self.footnotes.is_empty() {
+            return None;
+        }
+        let mut items = vec![newline()];
+        // Definitions can reference further footnotes, which extends the list
+        let mut index = 0;
+        while index < self.footnotes.len() {
+            let key = self.footnotes[index].clone();
+            index += 1;
+            let definition = self.definitions[&key];
+            let mut content = self.flow(&definition.children);
+
+            let count = self.reference_counts.get(&key).copied().unwrap_or(1);
+            let mut backrefs = Vec::new();
+            for k in 1..=count {
+                let suffix = if k > 1 {
+                    format!("-{}", k)
+                } else {
+                    String::new()
+                };
+                let mut children = vec![HastNode::text("↩")];
+                if k > 1 {
+                    children.push(
+                        Element::new("sup")
+                            .with_children(vec![HastNode::text(&k.to_string())])
+                            .into(),
+                    );
+                }
+                let mut a = Element::new("a")
+                    .with_property("href", format!("#{}", reference_id(&key, k)))
+                    .with_property("dataFootnoteBackref", true)
+                    .with_property(
+                        "ariaLabel",
+                        format!("Back to reference {}{}", index, suffix),
+                    )
+                    .with_children(children);
+                a.add_class("data-footnote-backref");
+                backrefs.push(HastNode::text(" "));
+                backrefs.push(a.into());
+            }
+
+            let last_paragraph = content
+                .iter_mut()
+                .rev()
+                .find(|c| !is_newline(Some(c)))
+                .and_then(|c| match c {
+                    HastNode::Element(p) if p.tag_name == "p" => Some(p),
+                    _ => None,
+                });
+            match last_paragraph {
+                Some(p) => p.children.extend(backrefs),
+                None => content.extend(backrefs),
+            }
+
+            let li = Element::new("li")
+                .with_property("id", format!("{}fn-{}", CLOBBER_PREFIX, key))
+                .with_children(content);
+            items.push(self.block(definition, li));
+            items.push(newline());
+        }
+
+        let mut heading = Element::new("h2")
+            .with_property("id", "footnote-label")
+            .with_children(vec![HastNode::text("Footnotes")]);
+        heading.add_class("sr-only");
+        let mut section = Element::new("section")
+            .with_property("dataFootnotes", true)
+            .with_children(vec![
+                newline(),
+                heading.into(),
+                newline(),
+                Element::new("ol").with_children(items).into(),
+                newline(),
+            ]);
+        section.add_class("footnotes");
+        Some(section.into())
+    }
+}
+
+fn collect_definitions<'a>(node: &'a Node, definitions: &mut HashMap<String, &'a Node>) {
+    if node.node_type == NodeType::FootnoteDefinition {
+        if let Some(key) = footnote_key(node) {
+            definitions.entry(key).or_insert(node);
+        }
+    }
+    for child in &node.children {
+        collect_definitions(child, definitions);
+    }
+}
+
+fn footnote_key(node: &Node) -> Option<String> {
+    node.identifier.as_ref().map(|id| id.to_lowercase())
+}
+
+fn reference_id(key: &str, k: usize) -> String {
+    if k > 1 {
+        format!("{}fnref-{}-{}", CLOBBER_PREFIX, key, k)
+    } else {
+        format!("{}fnref-{}", CLOBBER_PREFIX, key)
+    }
+}
+
+/// Apply `data.hName`, `data.hProperties` and `data.hChildren`
+fn apply_data(node: &Node, mut converted: Vec<HastNode>) -> Vec<HastNode> {
+    let name = node.data.get("hName").and_then(Value::as_str);
+    let properties = node.data.get("hProperties").and_then(Value::as_object);
+    let children = node
+        .data
+        .get("hChildren")
+        .and_then(|v| serde_json::from_value::<Vec<HastNode>>(v.clone()).ok());
+    if name.is_none() && properties.is_none() && children.is_none() {
+        return converted;
+    }
+
+    let mut element = match (converted.as_mut_slice(), name) {
+        ([HastNode::Element(element)], _) => std::mem::replace(element, Element::new("")),
+        // Text-like nodes get wrapped when a tag name is given
+        (_, Some(name)) => Element::new(name).with_children(converted),
+        _ => return converted,
+    };
+    if let Some(name) = name {
+        element.tag_name = name.to_string();
+    }
+    for (key, value) in properties.into_iter().flatten() {
+        match PropertyValue::from_json(value) {
+            Some(value) => element.properties.insert(key.clone(), value),
+            None => element.properties.shift_remove(key),
+        };
+    }
+    if let Some(children) = children {
+        element.children = children;
+    }
+    vec![element.into()]
+}
+
+fn is_header(cell: &Node) -> bool {
+    cell.data.get("header").and_then(Value::as_bool) == Some(true)
+}
+
+/// A row marked `data.header`, or whose cells all are
+fn is_header_row(row: &Node) -> bool {
+    is_header(row) || (!row.children.is_empty() && row.children.iter().all(is_header))
+}
+
+fn is_flow(node: &Node) -> bool {
+    matches!(
+        node.node_type,
+        NodeType::Paragraph
+            | NodeType::Heading
+            | NodeType::ThematicBreak
+            | NodeType::Blockquote
+            | NodeType::List
+            | NodeType::ListItem
+            | NodeType::Code
+            | NodeType::Table
+            | NodeType::Math
+            | NodeType::ContainerDirective
+    )
+}
+
+fn newline() -> HastNode {
+    HastNode::text("\n")
+}
+
+fn is_newline(node: Option<&HastNode>) -> bool {
+    matches!(node, Some(HastNode::Text { value }) if value == "\n")
+}
+
+fn ends_with_newline(nodes: &[HastNode]) -> bool {
+    matches!(
+        nodes.last(),
+        Some(HastNode::Text { value } | HastNode::Raw { value }) if value.ends_with('\n')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hast::stringify;
+    use fmd_core::{parse, Document, ProcessorOptions};
+
+    fn render(input: &str) -> String {
+        let options = ProcessorOptions {
+            gfm: true,
+            ..Default::default()
+        };
+        let ast = parse(&Document::new(input), options).ast;
+        stringify(&to_hast(&ast, &HtmlOptions::default()))
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(
+            render("# Hi\n\n> quote\n\n3. a\n4. b\n\n- [x] done\n\n<div>raw</div>\n\n---\n"),
+            "<h1>Hi</h1>\n<blockquote>\n<p>quote</p>\n</blockquote>\n\
+             <ol start=\"3\">\n<li>a</li>\n<li>b</li>\n</ol>\n\
+             <ul>\n<li><input type=\"checkbox\" checked disabled> done</li>\n</ul>\n\
+             <div>raw</div>\n<hr>\n"
+        );
+    }
+
+    #[test]
+    fn test_tables() {
+        let input = "| a | b | c |\n|:--|:-:|--:|\n| 1 | 2 | 3 |\n";
+        assert_eq!(
+            render(input),
+            "<table>\n<thead>\n<tr><th align=\"left\">a</th><th align=\"center\">b</th>\
+             <th align=\"right\">c</th></tr>\n</thead>\n<tbody>\n\
+             <tr><td align=\"left\">1</td><td align=\"center\">2</td>\
+             <td align=\"right\">3</td></tr>\n</tbody>\n</table>\n"
+        );
+
+        let options = HtmlOptions {
+            table_align: TableAlign::Style,
+            ..Default::default()
+        };
+        let ast = parse(
+            &Document::new(input),
+            ProcessorOptions {
+                gfm: true,
+                ..Default::default()
+            },
+        )
+        .ast;
+        let html = stringify(&to_hast(&ast, &options));
+        assert!(html.contains("<th style=\"text-align:center\">b</th>"));
+        let sanitized = crate::to_html(
+            &ast,
+            HtmlOptions {
+                sanitize: true,
+                ..options
+            },
+        );
+        assert!(sanitized.contains("<th style=\"text-align:center\">b</th>"));
+
+        // Unmarked rows (hand-built mdast): the first row is the header
+        let cell = |text: &str| Node {
+            node_type: NodeType::TableCell,
+            children: vec![Node {
+                node_type: NodeType::Text,
+                value: Some(text.to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let row = |text: &str| Node {
+            node_type: NodeType::TableRow,
+            children: vec![cell(text)],
+            ..Default::default()
+        };
+        let table = Node {
+            node_type: NodeType::Table,
+            children: vec![row("h"), row("x")],
+            ..Default::default()
+        };
+        assert_eq!(
+            stringify(&to_hast(&table, &HtmlOptions::default())),
+            "<table>\n<thead>\n<tr><th>h</th></tr>\n</thead>\n\
+             <tbody>\n<tr><td>x</td></tr>\n</tbody>\n</table>\n"
+        );
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let html = render("A[^x] and again[^x].\n\n[^x]: Note.\n");
+        assert!(html.starts_with(
+            "<p>A<sup><a href=\"#user-content-fn-x\" id=\"user-content-fnref-x\" \
+             data-footnote-ref aria-describedby=\"footnote-label\">1</a></sup> and again\
+             <sup><a href=\"#user-content-fn-x\" id=\"user-content-fnref-x-2\""
+        ));
+        assert!(html.contains(
+            "<li id=\"user-content-fn-x\">\n<p>Note. <a href=\"#user-content-fnref-x\" \
+             data-footnote-backref aria-label=\"Back to reference 1\" \
+             class=\"data-footnote-backref\">↩</a> <a href=\"#user-content-fnref-x-2\""
+        ));
+        assert!(html.contains("<section data-footnotes class=\"footnotes\">"));
+    }
+
+    #[test]
+    fn test_data_fields() {
+        let mut ast = Node {
+            node_type: NodeType::Root,
+            children: vec![Node {
+                node_type: NodeType::Paragraph,
+                children: vec![Node {
+                    node_type: NodeType::Text,
+                    value: Some("note".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let paragraph = &mut ast.children[0];
+        paragraph.data.insert("hName".into(), "aside".into());
+        paragraph.data.insert(
+            "hProperties".into(),
+            serde_json::json!({ "className": ["callout"], "dataKind": "info" }),
+        );
+        paragraph.children[0]
+            .data
+            .insert("hName".into(), "strong".into());
+
+        assert_eq!(
+            stringify(&to_hast(&ast, &HtmlOptions::default())),
+            "<aside class=\"callout\" data-kind=\"info\"><strong>note</strong></aside>\n"
+        );
+    }
+}
@@ -1,32 +1,15 @@
 // Visitor pattern for HTML generation
 
-use fmd_core::{Node, NodeType};
-use std::fmt::Write;
+use crate::hast::stringify_with;
+use crate::to_hast::to_hast;
+use crate::HtmlOptions;
+use fmd_core::{Node, Position};
 
-/// HTML visitor for AST traversal
+/// Renders nodes to an HTML string through the hast tree
+#[derive(Default)]
 pub struct HtmlVisitor {
+    options: HtmlOptions,
     output: String,
-    in_pre: bool,
-    list_stack: Vec<ListContext>,
-}
-
-#[derive(Debug, Clone)]
-struct ListContext {
-    #[allow(dead_code)]
-    ordered: bool,
-    #[allow(dead_code)]
-    start: usize,
-    tight: bool,
-}
-
-impl Default for HtmlVisitor {
-    fn default() -> Self {
-        Self {
-            output: String::with_capacity(1024),
-            in_pre: false,
-            list_stack: Vec::new(),
-        }
-    }
 }
 
 impl HtmlVisitor {
@@ -34,316 +17,18 @@ impl HtmlVisitor {
         Self::default()
     }
 
-    /// Visit a node and generate HTML
-    pub fn visit(&mut self, node: &Node) {
-        match node.node_type {
-            NodeType::Root => {
-                for child in &node.children {
-                    self.visit(child);
-                }
-            }
-            NodeType::Paragraph => {
-                // Don't wrap in <p> if in tight list
-                let in_tight_list = self.list_stack.last().is_some_and(|ctx| ctx.tight);
-                if !in_tight_list {
-                    self.output.push_str("<p>");
-                }
-                for child in &node.children {
-                    self.visit(child);
-                }
-                if !in_tight_list {
-                    self.output.push_str("</p>\n");
-                } else {
-                    self.output.push('\n');
-                }
-            }
-            NodeType::Heading => {
-                let depth = node.depth.unwrap_or(1);
-                write!(self.output, "<h{}>", depth).unwrap();
-                for child in &node.children {
-                    self.visit(child);
-                }
-                writeln!(self.output, "</h{}>", depth).unwrap();
-            }
-            NodeType::ThematicBreak => {
-                self.output.push_str("<hr />\n");
-            }
-            NodeType::Blockquote => {
-                self.output.push_str("<blockquote>\n");
-                for child in &node.children {
-                    self.visit(child);
-                }
-                self.output.push_str("</blockquote>\n");
-            }
-            NodeType::List => {
-                let ordered = node.ordered.unwrap_or(false);
-                let tight = self.is_tight_list(node);
-
-                self.list_stack.push(ListContext {
-                    ordered,
-                    start: 1,
-                    tight,
-                });
-
-                if ordered {
-                    self.output.push_str("<ol>\n");
-                } else {
-                    self.output.push_str("<ul>\n");
-                }
-
-                for child in &node.children {
-                    self.visit(child);
-                }
-
-                if ordered {
-                    self.output.push_str("</ol>\n");
-                } else {
-                    self.output.push_str("</ul>\n");
-                }
-
-                self.list_stack.pop();
-            }
-            NodeType::ListItem => {
-                self.output.push_str("<li>");
-
-                // Check if item has checkbox
-                if let Some(checked) = node.checked {
-                    if checked {
-                        self.output
-                            .push_str("<input type=\"checkbox\" checked disabled /> ");
-                    } else {
-                        self.output
-                            .push_str("<input type=\"checkbox\" disabled /> ");
-                    }
-                }
-
-                for child in &node.children {
-                    self.visit(child);
-                }
-                self.output.push_str("</li>\n");
-            }
-            NodeType::Code => {
-                self.output.push_str("<pre>");
-                if let Some(lang) = &node.lang {
-                    write!(
-                        self.output,
-                        "<code class=\"language-{}\">",
-                        escape_html(lang)
-                    )
-                    .unwrap();
-                } else {
-                    self.output.push_str("<code>");
-                }
-
-                if let Some(value) = &node.value {
-                    self.output.push_str(&escape_html(value));
-                }
-
-                self.output.push_str("</code></pre>\n");
-            }
-            NodeType::Html => {
-                if let Some(value) = &node.value {
-                    self.output.push_str(value);
-                }
-            }
-            NodeType::Text => {
-                if let Some(value) = &node.value {
-                    if self.in_pre {
-                        self.output.push_str(value);
-                    } else {
-                        self.output.push_str(&escape_html(value));
-                    }
-                }
-            }
-            NodeType::Emphasis => {
-                self.output.push_str("<em>");
-                for child in &node.children {
-                    self.visit(child);
-                }
-                self.output.push_str("</em>");
-            }
-            NodeType::Strong => {
-                self.output.push_str("<strong>");
-                for child in &node.children {
-                    self.visit(child);
-                }
-                self.output.push_str("</strong>");
-            }
-            NodeType::InlineCode => {
-                self.output.push_str("<code>");
-                if let Some(value) = &node.value {
-                    self.output.push_str(&escape_html(value));
-                }
-                self.output.push_str("</code>");
-            }
-            NodeType::Break => {
-                self.output.push_str("<br />\n");
-            }
-            NodeType::Link => {
-                self.output.push_str("<a");
-                if let Some(url) = &node.url {
-                    write!(self.output, " href=\"{}\"", escape_attr(url)).unwrap();
-                }
-                if let Some(title) = &node.title {
-                    write!(self.output, " title=\"{}\"", escape_attr(title)).unwrap();
-                }
-                self.output.push('>');
-                for child in &node.children {
-                    self.visit(child);
-                }
-                self.output.push_str("</a>");
-            }
-            NodeType::Image => {
-                self.output.push_str("<img");
-                if let Some(url) = &node.url {
-                    write!(self.output, " src=\"{}\"", escape_attr(url)).unwrap();
-                }
-                if let Some(alt) = &node.alt {
-                    write!(self.output, " alt=\"{}\"", escape_attr(alt)).unwrap();
-                }
-                if let Some(title) = &node.title {
-                    write!(self.output, " title=\"{}\"", escape_attr(title)).unwrap();
-                }
-                self.output.push_str(" />");
-            }
-            NodeType::LinkReference | NodeType::ImageReference => {
-                // These should be resolved before rendering
-                // For now, render as text
-                for child in &node.children {
-                    self.visit(child);
-                }
-            }
-            NodeType::Definition => {
-                // Definitions are not rendered
-            }
-            // GFM Extensions
-            NodeType::Table => {
-                self.output.push_str("<table>\n");
-
-                // Separate thead and tbody
-                let mut in_header = true;
-                let mut thead_content = String::new();
-                let mut tbody_content = String::new();
-
-                for child in &node.children {
-                    if child.node_type == NodeType::TableRow {
-                        let mut row_visitor = HtmlVisitor::new();
-                        row_visitor.visit(child);
-
-                        if in_header {
-                            thead_content.push_str(&row_visitor.output);
-                            in_header = false;
-                        } else {
-                            tbody_content.push_str(&row_visitor.output);
-                        }
-                    }
-                }
-
-                if !thead_content.is_empty() {
-                    self.output.push_str("<thead>\n");
-                    self.output.push_str(&thead_content);
-                    self.output.push_str("</thead>\n");
-                }
-
-                if !tbody_content.is_empty() {
-                    self.output.push_str("<tbody>\n");
-                    self.output.push_str(&tbody_content);
-                    self.output.push_str("</tbody>\n");
-                }
-
-                self.output.push_str("</table>\n");
-            }
-            NodeType::TableRow => {
-                self.output.push_str("<tr>");
-                for child in &node.children {
-                    self.visit(child);
-                }
-                self.output.push_str("</tr>\n");
-            }
-            NodeType::TableCell => {
-                // Check if header cell (simplified - would need context)
-                let tag = "td"; // Would be "th" for header cells
-                write!(self.output, "<{}>", tag).unwrap();
-                for child in &node.children {
-                    self.visit(child);
-                }
-                write!(self.output, "</{}>", tag).unwrap();
-            }
-            NodeType::Delete => {
-                self.output.push_str("<del>");
-                for child in &node.children {
-                    self.visit(child);
-                }
-                self.output.push_str("</del>");
-            }
-            NodeType::FootnoteDefinition => {
-                // Footnotes need special handling
-                if let Some(id) = &node.identifier {
-                    writeln!(
-                        self.output,
-                        "<div class=\"footnote\" id=\"fn-{}\">",
-                        escape_attr(id)
-                    )
-                    .unwrap();
-                    for child in &node.children {
-                        self.visit(child);
-                    }
-                    self.output.push_str("</div>\n");
-                }
-            }
-            NodeType::FootnoteReference => {
-                if let Some(id) = &node.identifier {
-                    write!(
-                        self.output,
-                        "<sup><a href=\"#fn-{}\">{}</a></sup>",
-                        escape_attr(id),
-                        escape_html(id)
-                    )
-                    .unwrap();
-                }
-            }
-            // MDX nodes - render as comments for now
-            NodeType::MdxjsEsm
-            | NodeType::MdxJsxFlowElement
-            | NodeType::MdxJsxTextElement
-            | NodeType::MdxFlowExpression
-            | NodeType::MdxTextExpression => {
-                write!(self.output, "<!-- MDX: {:?} -->", node.node_type).unwrap();
-            }
-            // Directives
-            NodeType::ContainerDirective | NodeType::LeafDirective | NodeType::TextDirective => {
-                self.output.push_str("<div class=\"directive\">");
-                for child in &node.children {
-                    self.visit(child);
-                }
-                self.output.push_str("</div>");
-            }
-            // Math
-            NodeType::Math => {
-                self.output.push_str("<div class=\"math math-display\">");
-                if let Some(value) = &node.value {
-                    self.output.push_str(&escape_html(value));
-                }
-                self.output.push_str("</div>\n");
-            }
-            NodeType::InlineMath => {
-                self.output.push_str("<span class=\"math math-inline\">");
-                if let Some(value) = &node.value {
-                    self.output.push_str(&escape_html(value));
-                }
-                self.output.push_str("</span>");
-            }
-            // YAML frontmatter (typically not rendered in HTML)
-            NodeType::Yaml | NodeType::FrontMatter => {
-                // Skip rendering frontmatter in HTML output
-            }
+    pub fn with_options(options: HtmlOptions) -> Self {
+        Self {
+            options,
+            output: String::with_capacity(1024),
         }
     }
 
-    /// Check if a list is tight (no blank lines between items)
-    fn is_tight_list(&self, _list: &Node) -> bool {
-        // Simplified check - would need to analyze spacing
-        true
+    /// Visit a node and generate HTML
+    pub fn visit(&mut self, node: &Node) {
+        let tree = to_hast(node, &self.options);
+        self.output
+            .push_str(&stringify_with(&tree, &self.options.stringify_options()));
     }
 
     /// Get generated HTML
@@ -352,6 +37,22 @@ impl HtmlVisitor {
     }
 }
 
+/// cmark-style `line:col-line:col` with an inclusive end column
+pub fn sourcepos(position: &Position) -> String {
+    format!(
+        "{}:{}-{}:{}",
+        position.start.line,
+        position.start.column,
+        position.end.line,
+        position.end.column.saturating_sub(1).max(1)
+    )
+}
+
+pub(crate) const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
 /// Escape HTML special characters
 pub fn escape_html(text: &str) -> String {
     let mut escaped = String::with_capacity(text.len());
@@ -376,6 +77,7 @@ pub fn escape_attr(text: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use fmd_core::NodeType;
 
     #[test]
     fn test_escape_html() {
@@ -114,7 +114,6 @@ fn test_render_table() {
 }
 
 #[test]
-#[ignore = "Implementation not complete - will fail"]
 fn test_render_with_custom_sanitize_options() {
     let ast = Node {
         node_type: NodeType::Root,
@@ -140,6 +139,7 @@ fn test_render_with_custom_sanitize_options() {
         sanitize_options: SanitizeOptions {
             enabled: true,
             allow_dangerous_html: false,
+            ..Default::default()
         },
         ..Default::default()
     };
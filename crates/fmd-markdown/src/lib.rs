@@ -0,0 +1,319 @@
+// Markdown serializer for faster-md AST
+
+pub mod serializer;
+
+use fmd_core::{Node, NodeType};
+use serde::{Deserialize, Serialize};
+use serializer::Serializer;
+
+/// How paragraph text is broken into lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProseWrap {
+    /// Keep the line breaks of the source
+    #[default]
+    Preserve,
+    /// Re-wrap paragraphs at `line_width`
+    Always,
+    /// Put each paragraph on a single line
+    Never,
+}
+
+/// Style choices for generated Markdown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MarkdownOptions {
+    /// Marker for unordered list items: `-`, `*` or `+`
+    pub bullet: char,
+    /// Delimiter after ordered list numbers: `.` or `)`
+    pub ordered_delimiter: char,
+    /// Number ordered items `1. 2. 3.` instead of repeating the start number
+    pub increment_list_marker: bool,
+    /// Emphasis marker: `*` or `_`
+    pub emphasis: char,
+    /// Strong marker: `*` or `_`
+    pub strong: char,
+    /// Code fence character: `` ` `` or `~`
+    pub fence: char,
+    /// Thematic break character: `*`, `-` or `_`
+    pub rule: char,
+    /// Use setext underlines for level 1 and 2 headings
+    pub setext: bool,
+    /// Pad table cells so pipes line up
+    pub align_tables: bool,
+    /// Line breaking for paragraph text
+    pub prose_wrap: ProseWrap,
+    /// Target width for `ProseWrap::Always`
+    pub line_width: usize,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            bullet: '-',
+            ordered_delimiter: '.',
+            increment_list_marker: true,
+            emphasis: '*',
+            strong: '*',
+            fence: '`',
+            rule: '-',
+            setext: false,
+            align_tables: true,
+            prose_wrap: ProseWrap::Preserve,
+            line_width: 80,
+        }
+    }
+}
+
+/// Serialize an AST back to Markdown
+pub fn to_markdown(ast: &Node, options: &MarkdownOptions) -> String {
+    Serializer::new(options).serialize(ast)
+}
+
+/// Serialize with the default style
+pub fn to_markdown_default(ast: &Node) -> String {
+    to_markdown(ast, &MarkdownOptions::default())
+}
+
+/// Whether two trees describe the same document
+///
+/// Positions and `data` are ignored, adjacent text nodes are merged, soft
+/// line breaks count as spaces and trailing newlines of literal blocks are
+/// trimmed, so a document and the reparse of its serialization compare equal.
+pub fn equivalent(a: &Node, b: &Node) -> bool {
+    normalize(a) == normalize(b)
+}
+
+/// Canonical form used by [`equivalent`]
+pub fn normalize(node: &Node) -> Node {
+    let mut normalized = Node {
+        position: None,
+        data: Default::default(),
+        children: Vec::with_capacity(node.children.len()),
+        ..node.clone()
+    };
+
+    if matches!(
+        node.node_type,
+        NodeType::Code | NodeType::Html | NodeType::Yaml | NodeType::FrontMatter | NodeType::Math
+    ) {
+        normalized.value = node
+            .value
+            .as_deref()
+            .map(|v| v.trim_end_matches('\n').to_string())
+            .filter(|v| !v.is_empty());
+    }
+
+    if node.node_type == NodeType::Text {
+        normalized.value = node.value.as_ref().map(|v| v.replace('\n', " "));
+    }
+
+    for child in &node.children {
+        let child = normalize(child);
+        if child.node_type == NodeType::Text {
+            if child.value.as_deref().unwrap_or_default().is_empty() {
+                continue;
+            }
+            if let Some(last) = normalized.children.last_mut() {
+                if last.node_type == NodeType::Text {
+                    last.value
+                        .get_or_insert_with(String::new)
+                        .push_str(child.value.as_deref().unwrap_or_default());
+                    continue;
+                }
+            }
+        }
+        normalized.children.push(child);
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fmd_core::{parse, Document, ProcessorOptions};
+
+    fn parse_gfm(input: &str) -> Node {
+        let options = ProcessorOptions {
+            gfm: true,
+            frontmatter: true,
+            ..Default::default()
+        };
+        parse(&Document::new(input), options).ast
+    }
+
+    fn assert_round_trip(input: &str, options: &MarkdownOptions) -> String {
+        let ast = parse_gfm(input);
+        let output = to_markdown(&ast, options);
+        let reparsed = parse_gfm(&output);
+        assert!(
+            equivalent(&ast, &reparsed),
+            "round trip changed the AST\n--- input\n{}\n--- output\n{}",
+            input,
+            output
+        );
+        output
+    }
+
+    const SAMPLE: &str = r#"---
+title: Sample
+---
+
+Setext Title
+============
+
+## Intro *emphasis* and __strong__ ##
+
+Text with `code`, ``a ` tick``, a [link](https://example.com "Title"),
+<https://auto.link>, ![alt *text*](img.png) and ~~gone~~.
+Hard break\
+next line with \* stars \_ and 1\. literal. Also snake_case_name & co.
+
+> Quote
+> - nested list
+>
+> continued
+
+* one
+* two
+    1. inner
+    2. inner two
+
+3) three
+4) four
+
+- [ ] todo
+- [x] done
+
++ loose
+
++ list
+
+***
+
+```rust title="main.rs"
+fn main() {}
+```
+
+~~~
+``` fence inside
+~~~
+
+    indented code
+
+| Left | Center | Right |
+|:-----|:------:|------:|
+| a    | b \| c | d     |
+
+Footnote[^note] here.
+
+[^note]: The note.
+
+<div>
+html block
+</div>
+
+# Heading with # hash \#
+"#;
+
+    #[test]
+    fn test_round_trip_default_style() {
+        let output = assert_round_trip(SAMPLE, &MarkdownOptions::default());
+        assert!(output.contains("# Setext Title\n"));
+        assert!(output.contains("- one\n- two\n  1. inner\n  2. inner two"));
+        assert!(output.contains("- [ ] todo\n- [x] done"));
+        assert!(output.contains("| Left | Center | Right |\n| :--- | :----: | ----: |"));
+    }
+
+    #[test]
+    fn test_round_trip_alternate_style() {
+        let options = MarkdownOptions {
+            bullet: '*',
+            ordered_delimiter: ')',
+            increment_list_marker: false,
+            emphasis: '_',
+            strong: '_',
+            fence: '~',
+            rule: '_',
+            setext: true,
+            align_tables: false,
+            ..Default::default()
+        };
+        let output = assert_round_trip(SAMPLE, &options);
+        assert!(output.contains("Setext Title\n============\n"));
+        assert!(output.contains("_emphasis_ and __strong__"));
+        assert!(output.contains("~~~rust title=\"main.rs\""));
+        assert!(output.contains("| Left | Center | Right |\n| :- | :-: | -: |"));
+    }
+
+    #[test]
+    fn test_prose_wrap() {
+        let input =
+            "> Lorem ipsum dolor sit amet,\n> consectetur adipiscing elit - 1. not a list\n";
+        let always = MarkdownOptions {
+            prose_wrap: ProseWrap::Always,
+            line_width: 30,
+            ..Default::default()
+        };
+        let output = assert_round_trip(input, &always);
+        assert!(
+            output.lines().all(|l| l.chars().count() <= 30),
+            "{}",
+            output
+        );
+        assert!(output.contains("\\- 1. not"));
+
+        let never = MarkdownOptions {
+            prose_wrap: ProseWrap::Never,
+            ..Default::default()
+        };
+        assert_eq!(
+            assert_round_trip(input, &never),
+            "> Lorem ipsum dolor sit amet, consectetur adipiscing elit - 1. not a list\n"
+        );
+    }
+
+    #[test]
+    fn test_pipe_in_table_code_span() {
+        let output = assert_round_trip("| a |\n| - |\n| `x\\|y` |\n", &MarkdownOptions::default());
+        assert!(output.contains("| `x\\|y` |"), "{}", output);
+    }
+
+    #[test]
+    fn test_adjacent_lists_stay_separate() {
+        let output = assert_round_trip("- a\n- b\n\n* c\n", &MarkdownOptions::default());
+        assert_eq!(output, "- a\n- b\n\n* c\n");
+    }
+
+    #[test]
+    fn test_adjacent_emphasis_and_empty_tasks() {
+        let options = MarkdownOptions::default();
+        let output = assert_round_trip("foo_bar_baz *emph*_x_\n", &options);
+        assert_eq!(output, "foo_bar_baz *emph*_x_\n");
+        assert_eq!(assert_round_trip("**a**__b__\n", &options), "**a**__b__\n");
+        assert_eq!(assert_round_trip("- [ ] \n", &options), "- [ ] \n");
+        assert_eq!(assert_round_trip(&output, &options), output);
+    }
+
+    #[test]
+    fn test_equivalent_ignores_positions_and_text_splits() {
+        let a = parse_gfm("a \\* b");
+        let mut b = a.clone();
+        b.children[0].children = vec![
+            Node {
+                node_type: NodeType::Text,
+                value: Some("a ".to_string()),
+                ..Default::default()
+            },
+            Node {
+                node_type: NodeType::Text,
+                value: Some("* b".to_string()),
+                ..Default::default()
+            },
+        ];
+        assert!(equivalent(&a, &b));
+        b.children[0].children.pop();
+        assert!(!equivalent(&a, &b));
+    }
+}
@@ -0,0 +1,851 @@
+// AST -> Markdown serialization
+
+use crate::{MarkdownOptions, ProseWrap};
+use fmd_core::{Node, NodeType};
+use serde_json::Value;
+
+/// Stateful serializer; create one per document
+pub struct Serializer<'o> {
+    options: &'o MarkdownOptions,
+    in_table: bool,
+    /// Emphasis marker to avoid directly inside a strong node using the same character
+    avoid_marker: Option<char>,
+    /// Serializing paragraph text, where `prose_wrap` applies
+    prose: bool,
+    /// Width taken by container prefixes (`> `, list markers) on each line
+    indent: usize,
+}
+
+impl<'o> Serializer<'o> {
+    pub fn new(options: &'o MarkdownOptions) -> Self {
+        Self {
+            options,
+            in_table: false,
+            avoid_marker: None,
+            prose: false,
+            indent: 0,
+        }
+    }
+
+    /// Serialize a root (or any single node) to Markdown ending in a newline
+    pub fn serialize(&mut self, node: &Node) -> String {
+        let mut output = if node.node_type == NodeType::Root {
+            self.blocks(&node.children, false)
+        } else {
+            self.blocks(std::slice::from_ref(node), false)
+        };
+
+        let trimmed = output.trim_end_matches('\n').len();
+        output.truncate(trimmed);
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output
+    }
+
+    fn bullet(&self, alternate: bool) -> char {
+        let bullet = match self.options.bullet {
+            c @ ('-' | '*' | '+') => c,
+            _ => '-',
+        };
+        match (alternate, bullet) {
+            (false, c) => c,
+            (true, '-') => '*',
+            (true, _) => '-',
+        }
+    }
+
+    fn ordered_delimiter(&self, alternate: bool) -> char {
+        let delimiter = if self.options.ordered_delimiter == ')' {
+            ')'
+        } else {
+            '.'
+        };
+        match (alternate, delimiter) {
+            (false, c) => c,
+            (true, '.') => ')',
+            (true, _) => '.',
+        }
+    }
+
+    fn emphasis_marker(&self) -> char {
+        if self.options.emphasis == '_' {
+            '_'
+        } else {
+            '*'
+        }
+    }
+
+    fn strong_marker(&self) -> char {
+        if self.options.strong == '_' {
+            '_'
+        } else {
+            '*'
+        }
+    }
+
+    fn fence_char(&self) -> char {
+        if self.options.fence == '~' {
+            '~'
+        } else {
+            '`'
+        }
+    }
+
+    fn rule_char(&self) -> char {
+        match self.options.rule {
+            c @ ('*' | '-' | '_') => c,
+            _ => '*',
+        }
+    }
+
+    /// Serialize a sequence of flow children; runs of phrasing nodes (as in
+    /// tight list items) are serialized as one paragraph-like line group
+    fn blocks(&mut self, nodes: &[Node], tight: bool) -> String {
+        let mut parts = Vec::new();
+        let mut previous: Option<&Node> = None;
+        let mut after_inline = false;
+        let mut alternate = false;
+        let mut i = 0;
+
+        while i < nodes.len() {
+            if is_phrasing(&nodes[i]) {
+                let start = i;
+                while i < nodes.len() && is_phrasing(&nodes[i]) {
+                    i += 1;
+                }
+                parts.push(self.paragraph(&nodes[start..i]));
+                previous = None;
+                after_inline = true;
+                continue;
+            }
+
+            let node = &nodes[i];
+            if node.node_type == NodeType::List {
+                // Adjacent lists of the same kind would merge unless the marker changes
+                let continues_list = previous.is_some_and(|prev| {
+                    prev.node_type == NodeType::List
+                        && prev.ordered.unwrap_or(false) == node.ordered.unwrap_or(false)
+                });
+                alternate = continues_list && !alternate;
+            }
+
+            parts.push(self.block(node, alternate, after_inline));
+            previous = Some(node);
+            after_inline = false;
+            i += 1;
+        }
+
+        parts.join(if tight { "\n" } else { "\n\n" })
+    }
+
+    fn block(&mut self, node: &Node, alternate: bool, after_inline: bool) -> String {
+        match node.node_type {
+            NodeType::Root => self.blocks(&node.children, false),
+            NodeType::Paragraph => self.paragraph(&node.children),
+            NodeType::Heading => self.heading(node),
+            NodeType::ThematicBreak => {
+                // `---` right under a line of text would turn it into a setext heading
+                let rule = match self.rule_char() {
+                    '-' if after_inline => '*',
+                    c => c,
+                };
+                rule.to_string().repeat(3)
+            }
+            NodeType::Blockquote => {
+                let content = self.indented(2, |s| s.blocks(&node.children, false));
+                prefix_lines(&content, "> ", "> ", ">")
+            }
+            NodeType::List => self.list(node, alternate),
+            NodeType::ListItem => {
+                let marker = self.bullet(false).to_string();
+                self.list_item(node, &marker, true)
+            }
+            NodeType::Code => self.code(node),
+            NodeType::Html | NodeType::MdxjsEsm => node
+                .value
+                .as_deref()
+                .unwrap_or_default()
+                .trim_end_matches('\n')
+                .to_string(),
+            NodeType::Definition => {
+                let label = node.identifier.as_deref().unwrap_or_default();
+                let mut out = format!("[{}]: {}", label, destination(node.url.as_deref()));
+                if let Some(title) = &node.title {
+                    out.push(' ');
+                    out.push_str(&quote_title(title));
+                }
+                out
+            }
+            NodeType::Yaml | NodeType::FrontMatter => format!(
+                "---\n{}\n---",
+                node.value
+                    .as_deref()
+                    .unwrap_or_default()
+                    .trim_end_matches('\n')
+            ),
+            NodeType::Table => self.table(node),
+            NodeType::TableRow => {
+                let cells: Vec<_> = node.children.iter().map(|c| self.cell(c)).collect();
+                format!("| {} |", cells.join(" | "))
+            }
+            NodeType::FootnoteDefinition => {
+                let label = node.identifier.as_deref().unwrap_or_default();
+                let content = self.indented(4, |s| s.blocks(&node.children, false));
+                prefix_lines(&content, &format!("[^{}]: ", label), "    ", "")
+            }
+            NodeType::MdxFlowExpression => {
+                format!("{{{}}}", node.value.as_deref().unwrap_or_default())
+            }
+            NodeType::MdxJsxFlowElement => {
+                let open = jsx_open(node, false);
+                if node.children.is_empty() {
+                    jsx_open(node, true)
+                } else {
+                    let content = self.blocks(&node.children, false);
+                    format!("{}\n{}\n</{}>", open, content, directive_name(node))
+                }
+            }
+            NodeType::ContainerDirective => {
+                let content = self.blocks(&node.children, false);
+                format!(
+                    ":::{}{}\n{}\n:::",
+                    directive_name(node),
+                    directive_attributes(node),
+                    content
+                )
+            }
+            NodeType::LeafDirective => format!(
+                "::{}[{}]{}",
+                directive_name(node),
+                self.inlines(&node.children),
+                directive_attributes(node)
+            ),
+            NodeType::Math => format!(
+                "$$\n{}\n$$",
+                node.value
+                    .as_deref()
+                    .unwrap_or_default()
+                    .trim_end_matches('\n')
+            ),
+            _ => self.inlines(std::slice::from_ref(node)),
+        }
+    }
+
+    fn heading(&mut self, node: &Node) -> String {
+        let depth = node.depth.unwrap_or(1).clamp(1, 6) as usize;
+        let content = self.inlines(&node.children);
+
+        if depth <= 2 && !content.is_empty() && (self.options.setext || content.contains('\n')) {
+            let width = content
+                .lines()
+                .map(|l| l.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(3);
+            let underline = if depth == 1 { "=" } else { "-" };
+            return format!("{}\n{}", content, underline.repeat(width));
+        }
+
+        let mut content = content.replace('\n', " ");
+        if content.ends_with('#') {
+            // A trailing `#` would be read as a closing sequence
+            content.insert(content.len() - 1, '\\');
+        }
+        if content.is_empty() {
+            "#".repeat(depth)
+        } else {
+            format!("{} {}", "#".repeat(depth), content)
+        }
+    }
+
+    fn list(&mut self, node: &Node, alternate: bool) -> String {
+        let ordered = node.ordered.unwrap_or(false);
+        let loose = node.children.iter().any(|item| {
+            item.children
+                .iter()
+                .any(|c| c.node_type == NodeType::Paragraph)
+        });
+        let start = node.start.unwrap_or(1);
+
+        let items: Vec<_> = node
+            .children
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let marker = if ordered {
+                    let number = if self.options.increment_list_marker {
+                        start + i as u64
+                    } else {
+                        start
+                    };
+                    format!("{}{}", number, self.ordered_delimiter(alternate))
+                } else {
+                    self.bullet(alternate).to_string()
+                };
+                self.list_item(item, &marker, !loose)
+            })
+            .collect();
+
+        items.join(if loose { "\n\n" } else { "\n" })
+    }
+
+    fn list_item(&mut self, item: &Node, marker: &str, tight: bool) -> String {
+        let task = match item.checked {
+            Some(true) => "[x] ",
+            Some(false) => "[ ] ",
+            None => "",
+        };
+        let content = self.indented(marker.len() + 1, |s| s.blocks(&item.children, tight));
+        if content.is_empty() {
+            // An empty task item needs the space after `[ ]` to stay a task
+            return match task {
+                "" => marker.to_string(),
+                task => format!("{} {}", marker, task),
+            };
+        }
+
+        let first = format!("{} {}", marker, task);
+        let rest = " ".repeat(marker.len() + 1);
+        prefix_lines(&content, &first, &rest, "")
+    }
+
+    fn code(&self, node: &Node) -> String {
+        let value = node.value.as_deref().unwrap_or_default();
+        let info = match (&node.lang, &node.meta) {
+            (Some(lang), Some(meta)) => format!("{} {}", lang, meta),
+            (Some(lang), None) => lang.clone(),
+            (None, Some(meta)) => meta.clone(),
+            (None, None) => String::new(),
+        };
+
+        // Backtick fences cannot carry backticks in their info string
+        let fence_char = if info.contains('`') {
+            '~'
+        } else {
+            self.fence_char()
+        };
+        let fence = fence_char
+            .to_string()
+            .repeat((longest_run(value, fence_char) + 1).max(3));
+
+        let mut out = format!("{}{}\n", fence, info);
+        out.push_str(value);
+        if !value.is_empty() && !value.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&fence);
+        out
+    }
+
+    fn cell(&mut self, cell: &Node) -> String {
+        self.in_table = true;
+        let content = self.inlines(&cell.children).replace('\n', " ");
+        self.in_table = false;
+        content
+    }
+
+    fn table(&mut self, node: &Node) -> String {
+        let rows: Vec<Vec<String>> = node
+            .children
+            .iter()
+            .map(|row| row.children.iter().map(|c| self.cell(c)).collect())
+            .collect();
+        let columns = rows
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .max(node.align.as_ref().map_or(0, Vec::len));
+        let align: Vec<&str> = (0..columns)
+            .map(|i| {
+                node.align
+                    .as_ref()
+                    .and_then(|a| a.get(i))
+                    .map_or("none", String::as_str)
+            })
+            .collect();
+
+        let widths: Vec<usize> = (0..columns)
+            .map(|i| {
+                if !self.options.align_tables {
+                    return 0;
+                }
+                rows.iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    .max(3)
+            })
+            .collect();
+
+        let format_row = |row: &[String]| {
+            let cells: Vec<String> = (0..columns)
+                .map(|i| {
+                    let cell = row.get(i).map_or("", String::as_str);
+                    pad(cell, widths[i], align[i])
+                })
+                .collect();
+            format!("| {} |", cells.join(" | ")).replace("|  |", "| |")
+        };
+
+        let delimiter: Vec<String> = (0..columns)
+            .map(|i| {
+                let (left, right) = match align[i] {
+                    "left" => (true, false),
+                    "right" => (false, true),
+                    "center" => (true, true),
+                    _ => (false, false),
+                };
+                let dashes = widths[i]
+                    .saturating_sub(left as usize + right as usize)
+                    .max(1);
+                format!(
+                    "{}{}{}",
+                    if left { ":" } else { "" },
+                    "-".repeat(dashes),
+                    if right { ":" } else { "" }
+                )
+            })
+            .collect();
+
+        let mut lines = Vec::with_capacity(rows.len() + 1);
+        let empty = Vec::new();
+        lines.push(format_row(rows.first().unwrap_or(&empty)));
+        lines.push(format!("| {} |", delimiter.join(" | ")));
+        for row in rows.iter().skip(1) {
+            lines.push(format_row(row));
+        }
+        lines.join("\n")
+    }
+
+    fn indented<T>(&mut self, width: usize, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.indent += width;
+        let result = f(self);
+        self.indent -= width;
+        result
+    }
+
+    fn paragraph(&mut self, nodes: &[Node]) -> String {
+        self.prose = true;
+        let content = self.inlines(nodes);
+        self.prose = false;
+        content
+    }
+
+    fn inlines(&mut self, nodes: &[Node]) -> String {
+        let mut out = String::new();
+        self.inlines_into(nodes, &mut out);
+        out
+    }
+
+    fn inlines_into(&mut self, nodes: &[Node], out: &mut String) {
+        for (i, node) in nodes.iter().enumerate() {
+            let next = nodes.get(i + 1).and_then(first_char);
+            self.inline(node, out, next);
+        }
+    }
+
+    fn inline(&mut self, node: &Node, out: &mut String, next: Option<char>) {
+        match node.node_type {
+            NodeType::Text => self.escape_text(node.value.as_deref().unwrap_or_default(), out),
+            NodeType::Emphasis => {
+                let mut marker = self.emphasis_marker();
+                if self.avoid_marker == Some(marker) {
+                    marker = if marker == '*' { '_' } else { '*' };
+                }
+                if marker == '_' && is_intraword(out, next) {
+                    marker = '*';
+                }
+                marker = after_run(out, marker);
+                let saved = self.avoid_marker.take();
+                out.push(marker);
+                self.inlines_into(&node.children, out);
+                out.push(marker);
+                self.avoid_marker = saved;
+            }
+            NodeType::Strong => {
+                let mut marker = self.strong_marker();
+                if marker == '_' && is_intraword(out, next) {
+                    marker = '*';
+                }
+                marker = after_run(out, marker);
+                let saved = self.avoid_marker.replace(marker);
+                out.push(marker);
+                out.push(marker);
+                self.inlines_into(&node.children, out);
+                out.push(marker);
+                out.push(marker);
+                self.avoid_marker = saved;
+            }
+            NodeType::Delete => {
+                out.push_str("~~");
+                self.inlines_into(&node.children, out);
+                out.push_str("~~");
+            }
+            NodeType::InlineCode => {
+                let value = node.value.as_deref().unwrap_or_default();
+                // GFM splits cells before code spans, so a literal pipe needs `\|` there too
+                if self.in_table {
+                    out.push_str(&code_span(&value.replace('|', "\\|")))
+                } else {
+                    out.push_str(&code_span(value))
+                }
+            }
+            NodeType::Break => out.push_str("\\\n"),
+            NodeType::Link => {
+                if let Some(autolink) = autolink(node) {
+                    out.push_str(&autolink);
+                    return;
+                }
+                out.push('[');
+                self.inlines_into(&node.children, out);
+                out.push(']');
+                out.push_str(&resource(node));
+            }
+            NodeType::Image => {
+                out.push_str("![");
+                if node.children.is_empty() {
+                    self.escape_text(node.alt.as_deref().unwrap_or_default(), out);
+                } else {
+                    self.inlines_into(&node.children, out);
+                }
+                out.push(']');
+                out.push_str(&resource(node));
+            }
+            NodeType::LinkReference | NodeType::ImageReference => {
+                if node.node_type == NodeType::ImageReference {
+                    out.push('!');
+                }
+                out.push('[');
+                if node.children.is_empty() {
+                    self.escape_text(node.alt.as_deref().unwrap_or_default(), out);
+                } else {
+                    self.inlines_into(&node.children, out);
+                }
+                out.push(']');
+                if let Some(identifier) = &node.identifier {
+                    out.push_str(&format!("[{}]", identifier));
+                }
+            }
+            NodeType::FootnoteReference => {
+                out.push_str(&format!(
+                    "[^{}]",
+                    node.identifier.as_deref().unwrap_or_default()
+                ));
+            }
+            NodeType::Html => out.push_str(node.value.as_deref().unwrap_or_default()),
+            NodeType::InlineMath => {
+                out.push('$');
+                out.push_str(node.value.as_deref().unwrap_or_default());
+                out.push('$');
+            }
+            NodeType::MdxTextExpression => {
+                out.push_str(&format!(
+                    "{{{}}}",
+                    node.value.as_deref().unwrap_or_default()
+                ));
+            }
+            NodeType::MdxJsxTextElement => {
+                let open = jsx_open(node, false);
+                if node.children.is_empty() {
+                    out.push_str(&jsx_open(node, true));
+                } else {
+                    out.push_str(&open);
+                    self.inlines_into(&node.children, out);
+                    out.push_str(&format!("</{}>", directive_name(node)));
+                }
+            }
+            NodeType::TextDirective => {
+                out.push(':');
+                out.push_str(&directive_name(node));
+                if !node.children.is_empty() {
+                    out.push('[');
+                    self.inlines_into(&node.children, out);
+                    out.push(']');
+                }
+                out.push_str(&directive_attributes(node));
+            }
+            // Flow content in a phrasing position: keep its text
+            _ => self.inlines_into(&node.children, out),
+        }
+    }
+
+    /// Append `text`, backslash-escaping anything that would otherwise parse as markup
+    fn escape_text(&self, text: &str, out: &mut String) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut line: String = out.rsplit('\n').next().unwrap_or_default().to_string();
+
+        for (i, &c) in chars.iter().enumerate() {
+            let prev = if i == 0 {
+                out.chars().last()
+            } else {
+                Some(chars[i - 1])
+            };
+            let next = chars.get(i + 1).copied();
+            let line_start = line.is_empty();
+
+            // Soft line breaks follow `prose_wrap`
+            if self.prose && (c == '\n' || c == ' ') {
+                let wrap = match self.options.prose_wrap {
+                    ProseWrap::Preserve => None,
+                    ProseWrap::Never => Some(false),
+                    ProseWrap::Always => {
+                        let word = chars[i + 1..]
+                            .iter()
+                            .take_while(|c| !c.is_whitespace())
+                            .count();
+                        let column = self.indent + line.chars().count();
+                        Some(
+                            word > 0
+                                && !line.is_empty()
+                                && column + 1 + word > self.options.line_width,
+                        )
+                    }
+                };
+                if let Some(wrap) = wrap {
+                    if wrap {
+                        out.push('\n');
+                        line.clear();
+                    } else if c == ' ' || !line.is_empty() {
+                        out.push(' ');
+                        line.push(' ');
+                    }
+                    continue;
+                }
+            }
+
+            let escape = match c {
+                '\\' | '`' | '*' | '[' | ']' | '~' => true,
+                '_' => {
+                    !(prev.is_some_and(char::is_alphanumeric)
+                        && next.is_some_and(char::is_alphanumeric))
+                }
+                '<' => {
+                    next.is_some_and(|n| n.is_ascii_alphabetic() || matches!(n, '/' | '!' | '?'))
+                }
+                '&' => next.is_some_and(|n| n.is_ascii_alphanumeric() || n == '#'),
+                '|' => self.in_table,
+                '#' | '>' | '-' | '+' | '=' => line_start,
+                // `1.` / `1)` at the start of a line would begin an ordered list
+                '.' | ')' => {
+                    !line.is_empty() && line.len() <= 9 && line.chars().all(|d| d.is_ascii_digit())
+                }
+                _ => false,
+            };
+
+            if escape {
+                out.push('\\');
+            }
+            out.push(c);
+
+            if c == '\n' {
+                line.clear();
+            } else {
+                line.push(c);
+            }
+        }
+    }
+}
+
+/// Node types serialized inline (mdast "phrasing content")
+fn is_phrasing(node: &Node) -> bool {
+    matches!(
+        node.node_type,
+        NodeType::Text
+            | NodeType::Emphasis
+            | NodeType::Strong
+            | NodeType::Delete
+            | NodeType::InlineCode
+            | NodeType::Break
+            | NodeType::Link
+            | NodeType::Image
+            | NodeType::LinkReference
+            | NodeType::ImageReference
+            | NodeType::FootnoteReference
+            | NodeType::InlineMath
+            | NodeType::MdxTextExpression
+            | NodeType::MdxJsxTextElement
+            | NodeType::TextDirective
+    )
+}
+
+fn first_char(node: &Node) -> Option<char> {
+    match node.node_type {
+        NodeType::Text => node.value.as_deref().and_then(|v| v.chars().next()),
+        _ => Some('\0'),
+    }
+}
+
+/// The other marker when `out` ends with `marker`, so two delimiter runs do not merge
+fn after_run(out: &str, marker: char) -> char {
+    match (out.ends_with(marker), marker) {
+        (false, marker) => marker,
+        (true, '*') => '_',
+        (true, _) => '*',
+    }
+}
+
+/// `_` runs touching word characters cannot open or close emphasis
+fn is_intraword(out: &str, next: Option<char>) -> bool {
+    out.chars().last().is_some_and(char::is_alphanumeric) || next.is_some_and(char::is_alphanumeric)
+}
+
+fn longest_run(value: &str, ch: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in value.chars() {
+        if c == ch {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+fn code_span(value: &str) -> String {
+    let ticks = "`".repeat(longest_run(value, '`') + 1);
+    let pad = value.starts_with('`')
+        || value.ends_with('`')
+        || (value.starts_with(' ') && value.ends_with(' ') && !value.trim().is_empty());
+    if pad {
+        format!("{} {} {}", ticks, value, ticks)
+    } else {
+        format!("{}{}{}", ticks, value, ticks)
+    }
+}
+
+/// `<url>` form when a link's text is exactly its destination
+fn autolink(node: &Node) -> Option<String> {
+    let url = node.url.as_deref()?;
+    if node.title.is_some() || node.children.len() != 1 {
+        return None;
+    }
+    let text = node.children[0].value.as_deref()?;
+    if node.children[0].node_type != NodeType::Text
+        || url.contains(|c: char| c.is_whitespace() || c == '<' || c == '>')
+    {
+        return None;
+    }
+
+    let has_scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() >= 2
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'))
+    });
+    let is_email = !url.contains(':') && url.contains('@');
+
+    if text == url && (has_scheme || is_email) {
+        Some(format!("<{}>", url))
+    } else {
+        None
+    }
+}
+
+fn destination(url: Option<&str>) -> String {
+    let url = url.unwrap_or_default();
+    if url.is_empty() || url.contains(|c: char| c.is_whitespace() || c.is_control()) {
+        let escaped = url
+            .replace('\\', "\\\\")
+            .replace('<', "\\<")
+            .replace('>', "\\>");
+        format!("<{}>", escaped)
+    } else {
+        url.replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)")
+    }
+}
+
+fn quote_title(title: &str) -> String {
+    format!("\"{}\"", title.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn resource(node: &Node) -> String {
+    let mut out = format!("({}", destination(node.url.as_deref()));
+    if let Some(title) = &node.title {
+        out.push(' ');
+        out.push_str(&quote_title(title));
+    }
+    out.push(')');
+    out
+}
+
+fn pad(cell: &str, width: usize, align: &str) -> String {
+    let gap = width.saturating_sub(cell.chars().count());
+    match align {
+        "right" => format!("{}{}", " ".repeat(gap), cell),
+        "center" => format!(
+            "{}{}{}",
+            " ".repeat(gap / 2),
+            cell,
+            " ".repeat(gap - gap / 2)
+        ),
+        _ => format!("{}{}", cell, " ".repeat(gap)),
+    }
+}
+
+/// Prefix the first line with `first` and the rest with `rest` (`blank` for empty lines)
+fn prefix_lines(content: &str, first: &str, rest: &str, blank: &str) -> String {
+    let mut out = String::with_capacity(content.len() + first.len());
+    for (i, line) in content.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if i == 0 {
+            out.push_str(first);
+        } else if line.is_empty() {
+            out.push_str(blank);
+            continue;
+        } else {
+            out.push_str(rest);
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+fn directive_name(node: &Node) -> String {
+    node.data
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn attribute_pairs(node: &Node) -> Vec<String> {
+    let Some(Value::Object(attributes)) = node.data.get("attributes") else {
+        return Vec::new();
+    };
+    attributes
+        .iter()
+        .map(|(name, value)| match value {
+            Value::Bool(true) | Value::Null => name.clone(),
+            Value::String(s) => format!("{}=\"{}\"", name, s.replace('"', "&quot;")),
+            other => format!("{}={{{}}}", name, other),
+        })
+        .collect()
+}
+
+fn jsx_open(node: &Node, self_closing: bool) -> String {
+    let mut open = format!("<{}", directive_name(node));
+    for attribute in attribute_pairs(node) {
+        open.push(' ');
+        open.push_str(&attribute);
+    }
+    open.push_str(if self_closing { " />" } else { ">" });
+    open
+}
+
+fn directive_attributes(node: &Node) -> String {
+    let attributes = attribute_pairs(node);
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", attributes.join(" "))
+    }
+}
@@ -1,13 +1,241 @@
 // Stable slug generation for faster-md
 
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Turns heading text into ids, tracking earlier results when it needs unique slugs
+pub trait Slugger: Send + Sync {
+    fn slug(&mut self, text: &str) -> String;
+
+    /// Make an already-slugged value (e.g. an explicit id) unique; keeps it as is by default
+    fn unique(&mut self, slug: &str) -> String {
+        slug.to_string()
+    }
+
+    /// Forget earlier slugs, e.g. before starting the next document
+    fn reset(&mut self) {}
+}
+
+/// Everything github-slugger strips: control and format characters,
+/// punctuation, symbols (including emoji) and separators, except space,
+/// `-` and `_`
+static REMOVE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[\p{C}\p{P}\p{S}\p{Z}&&[^ \-_]]").expect("valid slug regex"));
+
+/// Stateless GitHub slug of `text`: lowercase, strip, spaces to `-`
 pub fn slugify(text: &str) -> String {
-    // TODO: Implement proper slug generation
-    text.to_lowercase()
-        .chars()
-        .map(|c| if c.is_alphanumeric() { c } else { '-' })
-        .collect::<String>()
-        .trim_matches('-')
-        .to_string()
+    github_slug(text, false)
+}
+
+/// GitHub slug, optionally keeping the original case
+pub fn github_slug(text: &str, maintain_case: bool) -> String {
+    let text = if maintain_case {
+        text.to_string()
+    } else {
+        text.to_lowercase()
+    };
+    REMOVE.replace_all(&text, "").replace(' ', "-")
+}
+
+/// How [`SlugOptions`] turns text into a slug
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlugStrategy {
+    /// Byte-for-byte github-slugger output
+    #[default]
+    Github,
+    /// Transliterate to ASCII (`Über` → `uber`, kana → romaji) and keep `a-z0-9`
+    Ascii,
+    /// Keep Unicode letters and digits; every other run becomes one separator
+    Unicode,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SlugOptions {
+    pub strategy: SlugStrategy,
+    /// Longest slug in characters, before any dedup suffix; cuts at a separator when it can
+    pub max_length: Option<usize>,
+    /// Joins words and dedup suffixes
+    pub separator: String,
+    /// Words left out of slugs, compared case-insensitively; kept if nothing else remains
+    pub stop_words: Vec<String>,
+}
+
+impl Default for SlugOptions {
+    fn default() -> Self {
+        Self {
+            strategy: SlugStrategy::Github,
+            max_length: None,
+            separator: "-".to_string(),
+            stop_words: Vec::new(),
+        }
+    }
+}
+
+impl SlugOptions {
+    /// A fresh slugger for one document
+    pub fn slugger(&self) -> Box<dyn Slugger> {
+        if *self == Self::default() {
+            Box::new(GithubSlugger::new())
+        } else {
+            Box::new(ConfiguredSlugger::new(self.clone()))
+        }
+    }
+}
+
+/// Stateless slug of `text` under `options`
+pub fn slug_with(text: &str, options: &SlugOptions) -> String {
+    let sep = options.separator.as_str();
+    let words: Vec<String> = match options.strategy {
+        SlugStrategy::Github => {
+            let kept = without_stop_words(text.split(' ').collect(), &options.stop_words);
+            let slug = github_slug(&kept.join(" "), false);
+            if sep == "-" {
+                return truncate(slug, options.max_length, sep);
+            }
+            // Only the separators that came from spaces change
+            let slug = REMOVE
+                .replace_all(&kept.join(" ").to_lowercase(), "")
+                .replace(' ', sep);
+            return truncate(slug, options.max_length, sep);
+        }
+        SlugStrategy::Ascii => any_ascii::any_ascii(text)
+            .to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect(),
+        SlugStrategy::Unicode => text
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect(),
+    };
+    let words = without_stop_words(
+        words.iter().map(String::as_str).collect(),
+        &options.stop_words,
+    );
+    truncate(words.join(sep), options.max_length, sep)
+}
+
+fn without_stop_words<'a>(words: Vec<&'a str>, stop_words: &[String]) -> Vec<&'a str> {
+    if stop_words.is_empty() {
+        return words;
+    }
+    let kept: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|word| {
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+            !word.is_empty()
+                && !stop_words
+                    .iter()
+                    .any(|stop| stop.eq_ignore_ascii_case(word))
+        })
+        .collect();
+    if kept.is_empty() {
+        words
+    } else {
+        kept
+    }
+}
+
+fn truncate(slug: String, max_length: Option<usize>, sep: &str) -> String {
+    let Some(max) = max_length else {
+        return slug;
+    };
+    if slug.chars().count() <= max {
+        return slug;
+    }
+    let cut: String = slug.chars().take(max).collect();
+    let next_is_boundary = slug[cut.len()..].starts_with(sep);
+    let cut = match cut.rfind(sep) {
+        Some(index) if !next_is_boundary && !sep.is_empty() && index > 0 => {
+            cut[..index].to_string()
+        }
+        _ => cut,
+    };
+    cut.trim_end_matches(sep).to_string()
+}
+
+/// Slugger producing the same anchors as GitHub (github-slugger), including
+/// `-1`, `-2`, … suffixes for repeated slugs within one document
+#[derive(Debug, Clone, Default)]
+pub struct GithubSlugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl GithubSlugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make an already-slugged value unique among the slugs seen so far
+    pub fn unique(&mut self, slug: &str) -> String {
+        unique(&mut self.occurrences, slug, "-")
+    }
+}
+
+impl Slugger for GithubSlugger {
+    fn slug(&mut self, text: &str) -> String {
+        let slug = slugify(text);
+        self.unique(&slug)
+    }
+
+    fn unique(&mut self, slug: &str) -> String {
+        GithubSlugger::unique(self, slug)
+    }
+
+    fn reset(&mut self) {
+        self.occurrences.clear();
+    }
+}
+
+/// Slugger for any [`SlugOptions`]; dedup suffixes use the configured separator
+#[derive(Debug, Clone, Default)]
+pub struct ConfiguredSlugger {
+    options: SlugOptions,
+    occurrences: HashMap<String, usize>,
+}
+
+impl ConfiguredSlugger {
+    pub fn new(options: SlugOptions) -> Self {
+        Self {
+            options,
+            occurrences: HashMap::new(),
+        }
+    }
+}
+
+impl Slugger for ConfiguredSlugger {
+    fn slug(&mut self, text: &str) -> String {
+        let slug = slug_with(text, &self.options);
+        unique(&mut self.occurrences, &slug, &self.options.separator)
+    }
+
+    fn unique(&mut self, slug: &str) -> String {
+        unique(&mut self.occurrences, slug, &self.options.separator)
+    }
+
+    fn reset(&mut self) {
+        self.occurrences.clear();
+    }
+}
+
+/// github-slugger's dedup: bump the base slug's counter until the result is unused
+fn unique(occurrences: &mut HashMap<String, usize>, slug: &str, sep: &str) -> String {
+    let mut result = slug.to_string();
+    while occurrences.contains_key(&result) {
+        let count = occurrences.entry(slug.to_string()).or_insert(0);
+        *count += 1;
+        result = format!("{}{}{}", slug, sep, count);
+    }
+    occurrences.insert(result.clone(), 0);
+    result
 }
 
 #[cfg(test)]
@@ -19,4 +247,86 @@ mod tests {
         assert_eq!(slugify("Hello World"), "hello-world");
         assert_eq!(slugify("Test 123"), "test-123");
     }
+
+    #[test]
+    fn test_github_compatibility() {
+        let cases = [
+            ("Foo Bar", "foo-bar"),
+            ("C++ & Rust!", "c--rust"),
+            ("foo.bar(baz)", "foobarbaz"),
+            ("snake_case and kebab-case", "snake_case-and-kebab-case"),
+            (" leading and trailing ", "-leading-and-trailing-"),
+            ("I ♥ unicode", "i--unicode"),
+            ("🚀 Features", "-features"),
+            ("Über Größe", "über-größe"),
+            ("Привет мир", "привет-мир"),
+            ("日本語の見出し", "日本語の見出し"),
+            ("ΟΔΟΣ", "οδος"),
+            ("$1,000 “quoted”", "1000-quoted"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(slugify(input), expected, "{:?}", input);
+        }
+        assert_eq!(github_slug("Foo Bar", true), "Foo-Bar");
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let mut slugger = GithubSlugger::new();
+        let slugs: Vec<_> = ["Usage", "Usage", "usage-1", "Usage", "Other"]
+            .iter()
+            .map(|text| slugger.slug(text))
+            .collect();
+        assert_eq!(slugs, ["usage", "usage-1", "usage-1-1", "usage-2", "other"]);
+
+        slugger.reset();
+        assert_eq!(slugger.slug("Usage"), "usage");
+    }
+
+    #[test]
+    fn test_strategies() {
+        let ascii = SlugOptions {
+            strategy: SlugStrategy::Ascii,
+            ..Default::default()
+        };
+        assert_eq!(slug_with("Über Größe", &ascii), "uber-grosse");
+        assert_eq!(
+            slug_with("ひらがな と カタカナ", &ascii),
+            "hiragana-to-katakana"
+        );
+        assert_eq!(slug_with("C++ & Rust!", &ascii), "c-rust");
+
+        let unicode = SlugOptions {
+            strategy: SlugStrategy::Unicode,
+            separator: "_".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(slug_with("Über -- Größe!", &unicode), "über_größe");
+
+        let github = SlugOptions {
+            separator: "_".to_string(),
+            stop_words: vec!["the".to_string(), "a".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(slug_with("The Rust-lang Book", &github), "rust-lang_book");
+        assert_eq!(slug_with("The", &github), "the");
+    }
+
+    #[test]
+    fn test_max_length_and_dedup() {
+        let options = SlugOptions {
+            strategy: SlugStrategy::Ascii,
+            max_length: Some(12),
+            ..Default::default()
+        };
+        assert_eq!(slug_with("Configuring the server", &options), "configuring");
+        assert_eq!(slug_with("Configure it", &options), "configure-it");
+        assert_eq!(slug_with("Internationalization", &options), "internationa");
+
+        let mut slugger = options.slugger();
+        assert_eq!(slugger.slug("Über"), "uber");
+        assert_eq!(slugger.slug("Uber"), "uber-1");
+        slugger.reset();
+        assert_eq!(slugger.slug("Uber"), "uber");
+    }
 }
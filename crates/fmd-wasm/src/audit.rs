@@ -0,0 +1,22 @@
+// WASM accessibility audit module
+use fmd_core::{a11y, ProcessorOptions};
+use serde_wasm_bindgen::from_value;
+use wasm_bindgen::prelude::*;
+
+/// Audit a document for accessibility problems; returns `[{ ruleId, message, position, wcag }]`
+#[wasm_bindgen(js_name = auditAccessibility)]
+pub fn audit_accessibility(content: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let mut opts: ProcessorOptions = if options.is_undefined() || options.is_null() {
+        ProcessorOptions::default()
+    } else {
+        from_value(options).map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
+    };
+    opts.position = true;
+
+    let (ast, _) = crate::parse::process(content, opts);
+    let issues = a11y::audit(&ast);
+
+    let json = serde_json::to_string(&issues)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+    Ok(JsValue::from_str(&json))
+}
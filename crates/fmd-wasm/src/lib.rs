@@ -1,11 +1,15 @@
 // WASM bindings for faster-md
 use wasm_bindgen::prelude::*;
 
+mod audit;
 mod parse;
+mod query;
 mod render;
 mod session;
 
+pub use audit::*;
 pub use parse::*;
+pub use query::*;
 pub use render::*;
 pub use session::*;
 
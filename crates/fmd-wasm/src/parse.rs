@@ -1,5 +1,5 @@
 // WASM parsing module
-use fmd_core::{Document, Node, ProcessorOptions};
+use fmd_core::{Document, Node, ProcessorOptions, VFile};
 use serde_wasm_bindgen::from_value;
 use wasm_bindgen::prelude::*;
 
@@ -20,24 +20,59 @@ pub fn parse_to_ast(content: &str, options: JsValue) -> Result<JsValue, JsValue>
         opts.gfm_options.tasklists = true;
     }
 
-    let doc = Document::new(content);
-    let result = fmd_core::parse(&doc, opts);
+    let start = js_sys::Date::now();
+    let (ast, file) = process(content, opts);
+    let parse_time = js_sys::Date::now() - start;
 
-    // Debug logging disabled for production
+    let errors: Vec<_> = file
+        .messages
+        .iter()
+        .filter(|m| m.severity == fmd_core::Severity::Error)
+        .map(|m| m.reason.clone())
+        .collect();
 
     // Return full parse result with AST and metadata
     let js_result = serde_json::json!({
-        "ast": result.ast,
-        "success": result.success,
-        "errors": result.errors,
-        "parseTime": result.parse_time_ns,
-        "nodeCount": count_nodes(&result.ast),
+        "ast": ast,
+        "success": errors.is_empty(),
+        "errors": errors,
+        "messages": file.messages,
+        "parseTime": (parse_time * 1_000_000.0) as u64,
+        "nodeCount": count_nodes(&ast),
     });
 
     // Convert to JSON string then parse in JavaScript
     Ok(JsValue::from_str(&js_result.to_string()))
 }
 
+/// Parse Markdown and return the AST in the `fmd_core::binary` format
+#[wasm_bindgen(js_name = parseToBinary)]
+pub fn parse_to_binary(content: &str, options: JsValue) -> Result<Vec<u8>, JsValue> {
+    let mut opts: ProcessorOptions = if options.is_undefined() || options.is_null() {
+        ProcessorOptions::default()
+    } else {
+        from_value(options).map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
+    };
+
+    if opts.gfm {
+        opts.gfm_options.tables = true;
+        opts.gfm_options.strikethrough = true;
+        opts.gfm_options.autolinks = true;
+        opts.gfm_options.tasklists = true;
+    }
+
+    let (ast, _) = process(content, opts);
+    Ok(fmd_core::binary::encode(&ast))
+}
+
+/// Decode a binary AST (e.g. from a persisted cache) back to a JSON string
+#[wasm_bindgen(js_name = decodeBinaryAst)]
+pub fn decode_binary_ast(bytes: &[u8]) -> Result<String, JsValue> {
+    let ast = fmd_core::binary::decode(bytes).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_json::to_string(&ast)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
 /// Parse with position information
 #[wasm_bindgen(js_name = parseWithPositions)]
 pub fn parse_with_positions(content: &str, options: JsValue) -> Result<JsValue, JsValue> {
@@ -51,7 +86,13 @@ pub fn parse_with_positions(content: &str, options: JsValue) -> Result<JsValue,
     opts.track_positions = true;
 
     let doc = Document::new(content);
-    let result = fmd_core::parse(&doc, opts);
+    let mut result = fmd_core::parse(&doc, opts);
+    if result.success {
+        let file = transform(&mut result.ast, content, opts);
+        result
+            .warnings
+            .extend(file.messages.iter().map(ToString::to_string));
+    }
 
     serde_wasm_bindgen::to_value(&result)
         .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
@@ -76,6 +117,36 @@ pub fn get_parse_stats(content: &str) -> Result<JsValue, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Statistics error: {}", e)))
 }
 
+/// Document metadata as `{ headings, links, images, codeLanguages, wordCount, charCount, readingTime, summary }`
+#[wasm_bindgen(js_name = analyzeDocument)]
+pub fn analyze_document(content: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let mut opts: ProcessorOptions = if options.is_undefined() || options.is_null() {
+        ProcessorOptions::default()
+    } else {
+        from_value(options).map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
+    };
+    opts.position = true;
+
+    let (ast, _) = process(content, opts);
+    let json = serde_json::to_string(&fmd_core::analyze(&ast))
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+    Ok(JsValue::from_str(&json))
+}
+
+/// Parse and run the built-in transforms natively
+pub(crate) fn process(content: &str, options: ProcessorOptions) -> (Node, VFile) {
+    let mut file = VFile::new(content);
+    let ast = fmd_gfm::processor(options).process(&mut file);
+    (ast, file)
+}
+
+/// Run the built-in transforms over a tree parsed without them
+pub(crate) fn transform(ast: &mut Node, content: &str, options: ProcessorOptions) -> VFile {
+    let mut file = VFile::new(content);
+    fmd_gfm::processor(options).run(ast, &mut file);
+    file
+}
+
 fn count_nodes(node: &Node) -> usize {
     let mut count = 1;
     for child in &node.children {
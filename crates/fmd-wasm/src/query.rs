@@ -0,0 +1,44 @@
+// WASM AST query module
+use fmd_core::{ProcessorOptions, Selector};
+use serde_wasm_bindgen::from_value;
+use wasm_bindgen::prelude::*;
+
+/// Select every node matching a CSS-like selector; returns `[{ path, node }]`
+#[wasm_bindgen(js_name = selectAll)]
+pub fn select_all(content: &str, selector: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let opts: ProcessorOptions = if options.is_undefined() || options.is_null() {
+        ProcessorOptions::default()
+    } else {
+        from_value(options).map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
+    };
+
+    let selector = Selector::parse(selector).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let (ast, _) = crate::parse::process(content, opts);
+    let matches = selector.select_all(&ast);
+
+    let json = serde_json::to_string(&matches)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+    Ok(JsValue::from_str(&json))
+}
+
+/// Select the first node matching a CSS-like selector; returns `{ path, node }` or `null`
+#[wasm_bindgen(js_name = select)]
+pub fn select(content: &str, selector: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let opts: ProcessorOptions = if options.is_undefined() || options.is_null() {
+        ProcessorOptions::default()
+    } else {
+        from_value(options).map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
+    };
+
+    let selector = Selector::parse(selector).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let (ast, _) = crate::parse::process(content, opts);
+
+    match selector.select(&ast) {
+        Some(found) => {
+            let json = serde_json::to_string(&found)
+                .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+            Ok(JsValue::from_str(&json))
+        }
+        None => Ok(JsValue::NULL),
+    }
+}
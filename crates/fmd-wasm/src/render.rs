@@ -1,6 +1,8 @@
 // WASM HTML rendering module
 use fmd_core::{Document, ProcessorOptions};
-use fmd_html::{render_html as fmd_render_html, sanitize::SanitizeOptions, HtmlOptions};
+use fmd_html::{
+    render_html as fmd_render_html, sanitize::SanitizeOptions, CodeRenderers, HtmlOptions,
+};
 use serde_wasm_bindgen::from_value;
 use wasm_bindgen::prelude::*;
 
@@ -15,7 +17,13 @@ pub fn render_html(content: &str, options: JsValue) -> Result<String, JsValue> {
     };
 
     // Extract processor options
-    let mut processor_opts = ProcessorOptions::default();
+    let mut processor_opts = ProcessorOptions {
+        heading_attributes: js_opts
+            .get("headingAttributes")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        ..Default::default()
+    };
     if let Some(gfm) = js_opts.get("gfm").and_then(|v| v.as_bool()) {
         processor_opts.gfm = gfm;
         // Enable all GFM features when gfm is true
@@ -27,14 +35,38 @@ pub fn render_html(content: &str, options: JsValue) -> Result<String, JsValue> {
         }
     }
 
-    // Parse the document
-    let doc = Document::new(content);
+    let sourcepos = js_opts
+        .get("sourcepos")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    processor_opts.position |= sourcepos;
 
-    let parse_result = fmd_core::parse(&doc, processor_opts);
+    // `slug: { strategy, maxLength, separator, stopWords }`
+    let slug: fmd_html::SlugOptions = match js_opts.get("slug") {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| JsValue::from_str(&format!("Invalid slug options: {}", e)))?,
+        None => Default::default(),
+    };
 
-    if !parse_result.success {
-        let errors = parse_result.errors.join("; ");
-        return Err(JsValue::from_str(&format!("Parse error: {}", errors)));
+    // Parse the document and run the built-in transforms
+    let mut file = fmd_core::VFile::new(content);
+    let mut processor = fmd_gfm::processor(processor_opts);
+    processor.push(Box::new(fmd_core::transforms::HeadingIds::with_slugger(
+        slug.slugger(),
+    )));
+    let ast = processor.process(&mut file);
+
+    if file.has_errors() {
+        let errors: Vec<_> = file
+            .messages
+            .iter()
+            .filter(|m| m.severity == fmd_core::Severity::Error)
+            .map(|m| m.reason.as_str())
+            .collect();
+        return Err(JsValue::from_str(&format!(
+            "Parse error: {}",
+            errors.join("; ")
+        )));
     }
 
     // Extract HTML options
@@ -45,8 +77,165 @@ pub fn render_html(content: &str, options: JsValue) -> Result<String, JsValue> {
     if let Some(xhtml) = js_opts.get("xhtml").and_then(|v| v.as_bool()) {
         html_opts.xhtml = xhtml;
     }
+    // `sanitizePolicy: "strict" | "default" | "permissive"` or a policy object
+    match js_opts.get("sanitizePolicy") {
+        Some(serde_json::Value::String(name)) => {
+            html_opts.sanitize_options = SanitizeOptions::preset(name)
+                .ok_or_else(|| JsValue::from_str(&format!("Unknown sanitize policy: {}", name)))?;
+        }
+        Some(value @ serde_json::Value::Object(_)) => {
+            html_opts.sanitize_options = serde_json::from_value(value.clone())
+                .map_err(|e| JsValue::from_str(&format!("Invalid sanitize policy: {}", e)))?;
+        }
+        _ => {}
+    }
+    // `quote: "double" | "single"`, `escapeApostrophe`, `layout: "newlines" | "compact" | "pretty"`
+    if let Some(value) = js_opts.get("quote") {
+        html_opts.quote = serde_json::from_value(value.clone())
+            .map_err(|e| JsValue::from_str(&format!("Invalid quote: {}", e)))?;
+    }
+    if let Some(escape) = js_opts.get("escapeApostrophe").and_then(|v| v.as_bool()) {
+        html_opts.escape_apostrophe = escape;
+    }
+    if let Some(value) = js_opts.get("layout") {
+        html_opts.layout = serde_json::from_value(value.clone())
+            .map_err(|e| JsValue::from_str(&format!("Invalid layout: {}", e)))?;
+    }
+    // `tableAlign: "attribute" | "style"`
+    if let Some(value) = js_opts.get("tableAlign") {
+        html_opts.table_align = serde_json::from_value(value.clone())
+            .map_err(|e| JsValue::from_str(&format!("Invalid tableAlign: {}", e)))?;
+    }
+    // `images: { lazy, decodeAsync, figures }`; `root` needs a filesystem
+    if let Some(value) = js_opts.get("images") {
+        html_opts.images = Some(
+            serde_json::from_value(value.clone())
+                .map_err(|e| JsValue::from_str(&format!("Invalid images options: {}", e)))?,
+        );
+    }
+    // `links: { baseUrl, markdownLinks: "keep" | "html" | "clean", external: { target, rel, class }, internalHosts }`
+    if let Some(value) = js_opts.get("links") {
+        html_opts.links = Some(
+            serde_json::from_value(value.clone())
+                .map_err(|e| JsValue::from_str(&format!("Invalid links options: {}", e)))?,
+        );
+    }
+    // `safe: true` or `safe: { protocols, dataImages, rawHtml: "escape" | "drop" }`
+    html_opts.safe = match js_opts.get("safe") {
+        Some(serde_json::Value::Bool(true)) => Some(fmd_html::SafeOptions::default()),
+        Some(value @ serde_json::Value::Object(_)) => Some(
+            serde_json::from_value(value.clone())
+                .map_err(|e| JsValue::from_str(&format!("Invalid safe options: {}", e)))?,
+        ),
+        _ => None,
+    };
+    html_opts.sourcepos = sourcepos;
+    html_opts.slug = slug;
+    // `headingIds: true` or `headingIds: { prefix, anchor: { placement, class, content } }`
+    html_opts.heading_ids = match js_opts.get("headingIds") {
+        Some(serde_json::Value::Bool(true)) => Some(fmd_html::HeadingIdOptions::default()),
+        Some(value @ serde_json::Value::Object(_)) => Some(
+            serde_json::from_value(value.clone())
+                .map_err(|e| JsValue::from_str(&format!("Invalid headingIds options: {}", e)))?,
+        ),
+        _ => None,
+    };
+    // `toc: true` or `toc: { minDepth, maxDepth, ordered }`
+    html_opts.toc = match js_opts.get("toc") {
+        Some(serde_json::Value::Bool(true)) => Some(fmd_core::TocOptions::default()),
+        Some(value @ serde_json::Value::Object(_)) => Some(
+            serde_json::from_value(value.clone())
+                .map_err(|e| JsValue::from_str(&format!("Invalid toc options: {}", e)))?,
+        ),
+        _ => None,
+    };
 
-    Ok(fmd_render_html(&parse_result.ast, html_opts))
+    // `codeRenderers: true` for all built-ins, or a list like `["mermaid", "csv"]`
+    match js_opts.get("codeRenderers") {
+        Some(serde_json::Value::Bool(true)) => html_opts.code_renderers = CodeRenderers::builtin(),
+        Some(serde_json::Value::Array(langs)) => {
+            for lang in langs {
+                let lang = lang.as_str().unwrap_or_default();
+                if !html_opts.code_renderers.register_builtin(lang) {
+                    return Err(JsValue::from_str(&format!(
+                        "Unknown code renderer: {}",
+                        lang
+                    )));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    // `highlight: true`, with `highlightTheme` and `highlightClasses` (class names instead of inline styles)
+    if js_opts.get("highlight").and_then(|v| v.as_bool()) == Some(true) {
+        html_opts.highlight = Some(highlighter(&js_opts)?);
+    }
+
+    Ok(fmd_render_html(&ast, html_opts))
+}
+
+#[cfg(feature = "highlight")]
+fn highlighter(
+    js_opts: &serde_json::Value,
+) -> Result<std::sync::Arc<dyn fmd_html::Highlighter>, JsValue> {
+    use fmd_html::highlight::{HighlightStyle, SyntectHighlighter};
+
+    let theme = js_opts
+        .get("highlightTheme")
+        .and_then(|v| v.as_str())
+        .unwrap_or("github");
+    let style = match js_opts.get("highlightClasses").and_then(|v| v.as_bool()) {
+        Some(true) => HighlightStyle::Classes,
+        _ => HighlightStyle::Inline,
+    };
+    SyntectHighlighter::new(theme, style)
+        .map(|h| std::sync::Arc::new(h) as _)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown highlight theme: {}", theme)))
+}
+
+#[cfg(not(feature = "highlight"))]
+fn highlighter(
+    _js_opts: &serde_json::Value,
+) -> Result<std::sync::Arc<dyn fmd_html::Highlighter>, JsValue> {
+    Err(JsValue::from_str(
+        "Highlighting is not available in this build (enable the `highlight` feature)",
+    ))
+}
+
+/// Stylesheet for `highlightClasses` output in the given theme
+#[cfg(feature = "highlight")]
+#[wasm_bindgen(js_name = highlightThemeCss)]
+pub fn highlight_theme_css(theme: &str) -> Result<String, JsValue> {
+    use fmd_html::highlight::{HighlightStyle, SyntectHighlighter};
+
+    SyntectHighlighter::new(theme, HighlightStyle::Classes)
+        .map(|h| h.theme_css())
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown highlight theme: {}", theme)))
+}
+
+/// Render with `data-sourcepos` attributes and return `{ html, mappings }`,
+/// where each mapping is `{ htmlStart, htmlEnd, position }` (byte offsets into `html`)
+#[wasm_bindgen(js_name = renderHtmlWithSourceMap)]
+pub fn render_html_with_source_map(content: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let mut opts: ProcessorOptions = if options.is_undefined() || options.is_null() {
+        ProcessorOptions::default()
+    } else {
+        from_value(options).map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
+    };
+    opts.position = true;
+
+    let (ast, _) = crate::parse::process(content, opts);
+    let html_opts = HtmlOptions {
+        sanitize: !opts.allow_dangerous_html,
+        allow_dangerous_html: opts.allow_dangerous_html,
+        ..Default::default()
+    };
+    let (html, mappings) = fmd_html::to_html_with_source_map(&ast, html_opts);
+
+    let json = serde_json::to_string(&serde_json::json!({ "html": html, "mappings": mappings }))
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+    Ok(JsValue::from_str(&json))
 }
 
 /// Render with custom sanitization options
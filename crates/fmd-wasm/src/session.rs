@@ -1,5 +1,5 @@
 // WASM session management for incremental parsing
-use fmd_core::{Document, IncrementalCache, Node, ProcessorOptions};
+use fmd_core::{AstPatch, Document, IncrementalCache, Node, ParseResult, ProcessorOptions};
 use serde_wasm_bindgen::{from_value, to_value};
 use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
@@ -10,7 +10,11 @@ pub struct ParseSession {
     session_id: String,
     cache: IncrementalCache,
     last_content: String,
+    /// Last transformed tree, the base for deltas
     last_ast: Option<Node>,
+    /// Last tree before transforms, reused by the incremental parser
+    last_parsed: Option<Node>,
+    last_options: ProcessorOptions,
     parse_count: usize,
     total_reuse: usize,
 }
@@ -26,6 +30,8 @@ impl ParseSession {
             cache: IncrementalCache::new(),
             last_content: String::new(),
             last_ast: None,
+            last_parsed: None,
+            last_options: ProcessorOptions::default(),
             parse_count: 0,
             total_reuse: 0,
         }
@@ -46,28 +52,9 @@ impl ParseSession {
                 .map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
         };
 
-        self.parse_count += 1;
-
-        // Calculate diff if we have previous content
-        let reuse_stats = if !self.last_content.is_empty() {
-            calculate_diff(&self.last_content, content)
-        } else {
-            DiffStats::default()
-        };
-
-        // Parse with incremental cache
-        let doc = Document::new(content);
-        let result = if let Some(ref last_ast) = self.last_ast {
-            fmd_core::parse_incremental(&doc, opts, last_ast, &mut self.cache)
-        } else {
-            fmd_core::parse(&doc, opts)
-        };
-
-        // Update session state
+        let (result, reuse_stats) = self.parse_native(content, opts);
         if result.success {
             self.last_ast = Some(result.ast.clone());
-            self.last_content = content.to_string();
-            self.total_reuse += reuse_stats.unchanged_lines;
         }
 
         // Build response with session metadata
@@ -91,11 +78,49 @@ impl ParseSession {
         to_value(&response).map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
     }
 
+    /// Parse and return only the changes since the previous parse:
+    /// `{ changes: AstPatch[], diagnostics, stats }`
+    ///
+    /// The first parse yields a single `replace` of the root (path `[]`).
+    #[wasm_bindgen(js_name = parseDelta)]
+    pub fn parse_delta(&mut self, content: &str, options: JsValue) -> Result<JsValue, JsValue> {
+        let opts: ProcessorOptions = if options.is_undefined() || options.is_null() {
+            self.last_options
+        } else {
+            from_value(options)
+                .map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
+        };
+        self.delta(content, opts)
+    }
+
+    /// Apply a `{ start, end, text }` byte-offset edit to the last content and
+    /// return the resulting delta
+    #[wasm_bindgen(js_name = applyPatch)]
+    pub fn apply_patch(&mut self, patch: JsValue) -> Result<JsValue, JsValue> {
+        let patch: TextPatch =
+            from_value(patch).map_err(|e| JsValue::from_str(&format!("Invalid patch: {}", e)))?;
+        let content = &self.last_content;
+        if patch.start > patch.end
+            || patch.end > content.len()
+            || !content.is_char_boundary(patch.start)
+            || !content.is_char_boundary(patch.end)
+        {
+            return Err(JsValue::from_str("Patch range out of bounds"));
+        }
+
+        let mut next = String::with_capacity(content.len() + patch.text.len());
+        next.push_str(&content[..patch.start]);
+        next.push_str(&patch.text);
+        next.push_str(&content[patch.end..]);
+        self.delta(&next, self.last_options)
+    }
+
     /// Clear the session cache
     pub fn clear(&mut self) {
         self.cache.clear();
         self.last_content.clear();
         self.last_ast = None;
+        self.last_parsed = None;
         self.total_reuse = 0;
     }
 
@@ -120,6 +145,83 @@ impl ParseSession {
     }
 }
 
+impl ParseSession {
+    fn parse_native(&mut self, content: &str, opts: ProcessorOptions) -> (ParseResult, DiffStats) {
+        self.parse_count += 1;
+
+        // Calculate diff if we have previous content
+        let reuse_stats = if !self.last_content.is_empty() {
+            calculate_diff(&self.last_content, content)
+        } else {
+            DiffStats::default()
+        };
+
+        // Parse with incremental cache
+        let doc = Document::new(content);
+        let mut result = if let Some(ref last_parsed) = self.last_parsed {
+            fmd_core::parse_incremental(&doc, opts, last_parsed, &mut self.cache)
+        } else {
+            fmd_core::parse(&doc, opts)
+        };
+
+        // Update session state
+        if result.success {
+            // Same heading ids, code meta and footnote numbers as `parseToAst`
+            self.last_parsed = Some(result.ast.clone());
+            let file = crate::parse::transform(&mut result.ast, content, opts);
+            result
+                .warnings
+                .extend(file.messages.iter().map(ToString::to_string));
+            self.last_content = content.to_string();
+            self.last_options = opts;
+            self.total_reuse += reuse_stats.unchanged_lines;
+        }
+
+        (result, reuse_stats)
+    }
+
+    fn delta(&mut self, content: &str, opts: ProcessorOptions) -> Result<JsValue, JsValue> {
+        let start = js_sys::Date::now();
+        let (result, reuse_stats) = self.parse_native(content, opts);
+        let previous = self.last_ast.take();
+
+        let changes = match &previous {
+            Some(old) if result.success => fmd_core::diff(old, &result.ast),
+            _ => vec![AstPatch::Replace {
+                path: Vec::new(),
+                node: result.ast.clone(),
+            }],
+        };
+        self.last_ast = if result.success {
+            Some(result.ast)
+        } else {
+            previous
+        };
+
+        let response = serde_json::json!({
+            "changes": changes,
+            "diagnostics": result.errors,
+            "stats": {
+                "reuseRatio": if reuse_stats.total_lines > 0 {
+                    reuse_stats.unchanged_lines as f64 / reuse_stats.total_lines as f64
+                } else {
+                    0.0
+                },
+                "parseTime": js_sys::Date::now() - start,
+            }
+        });
+        Ok(JsValue::from_str(&response.to_string()))
+    }
+}
+
+/// Byte-offset text edit, as in the `TextPatch` contract
+#[derive(serde::Deserialize)]
+struct TextPatch {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
 /// Global session manager
 #[wasm_bindgen]
 pub struct SessionManager {
@@ -163,6 +265,20 @@ impl SessionManager {
         self.sessions.remove(session_id)
     }
 
+    /// Apply a text patch to a session and return the AST delta
+    #[wasm_bindgen(js_name = parseIncremental)]
+    pub fn parse_incremental(
+        &mut self,
+        session_id: &str,
+        patch: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let session = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Session not found: {}", session_id)))?;
+        session.apply_patch(patch)
+    }
+
     /// Remove a session
     #[wasm_bindgen(js_name = removeSession)]
     pub fn remove_session(&mut self, session_id: &str) -> bool {